@@ -1,9 +1,6 @@
-#version 460 core
 layout (location = 0) in vec3 pos;
 
-uniform mat4 proj;
-uniform mat4 view;
-uniform mat4 model;
+#include "common/matrices.glsl"
 
 void main() {
     gl_Position = proj * view * model * vec4(pos, 1.0);
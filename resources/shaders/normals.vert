@@ -0,0 +1,12 @@
+layout (location = 0) in vec3 pos;
+layout (location = 2) in vec3 normals;
+
+out vec3 _normal;
+
+#include "common/matrices.glsl"
+uniform mat3 normal;
+
+void main() {
+    gl_Position = view * model * vec4(pos, 1.0);
+    _normal = normalize(mat3(view) * normal * normals);
+}
@@ -1,4 +1,3 @@
-#version 460 core
 layout (location = 0) in vec3 pos;
 layout (location = 1) in vec2 tex_coords;
 layout (location = 2) in vec3 normals;
@@ -7,9 +6,7 @@ out vec3 _frag_pos;
 out vec2 _tex_coords;
 out vec3 _normals;
 
-uniform mat4 proj;
-uniform mat4 view;
-uniform mat4 model;
+#include "common/matrices.glsl"
 uniform mat3 normal;
 
 void main() {
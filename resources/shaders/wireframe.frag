@@ -0,0 +1,15 @@
+noperspective in vec3 _edge_distance;
+
+out vec4 FragColor;
+
+uniform vec4 line_color = vec4(0.0, 1.0, 0.0, 1.0);
+uniform float line_width = 1.0;
+
+void main() {
+    float nearest = min(_edge_distance.x, min(_edge_distance.y, _edge_distance.z));
+    float coverage = 1.0 - smoothstep(line_width * 0.5 - 0.5, line_width * 0.5 + 0.5, nearest);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    FragColor = vec4(line_color.rgb, line_color.a * coverage);
+}
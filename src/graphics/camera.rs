@@ -1,19 +1,51 @@
 use cgmath::{num_traits::clamp, Matrix4};
 
 use crate::{
-    maths::{Matrix, Vector},
-    vector,
+    maths::{geometry::Frustum, DepthRange, Mat4, Quaternion, Vec2, Vec3, Vec4},
+    vector_n,
 };
 
+/// Projection built by `Camera::proj`.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Projection {
+    /// OpenGL's default perspective.
+    #[default]
+    Perspective,
+    /// Perspective with no far plane.
+    Infinite,
+    /// Reversed-Z with no far plane, the best depth precision for large
+    /// maps. Needs the GL state set by `Window::set_projection`.
+    ReversedZ,
+    /// Orthographic, `height` world units tall.
+    Orthographic { height: f32 },
+    /// Asymmetric frustum, bounds on the near plane.
+    OffAxis {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+    },
+}
+
+impl Projection {
+    pub fn depth_range(&self) -> DepthRange {
+        match self {
+            Projection::ReversedZ => DepthRange::Reversed,
+            _ => DepthRange::NegativeOneToOne,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Camera {
     aspect: f32,
     speed: f32,
-    position: Vector,
-    world_up: Vector,
-    front: Vector,
-    right: Vector,
-    up: Vector,
+    position: Vec3,
+    world_up: Vec3,
+    front: Vec3,
+    right: Vec3,
+    up: Vec3,
+    rotation: Quaternion,
     rotation_sensitivity: f32,
     yaw: f32,
     pitch: f32,
@@ -22,14 +54,17 @@ pub struct Camera {
     fov: f32,
     min_fov: f32,
     max_fov: f32,
+    projection: Projection,
+    jitter: Vec2,
+    clip_plane: Option<Vec4>,
 }
 
 impl Camera {
     pub fn new(
         aspect: f32,
         speed: f32,
-        position: Vector,
-        world_up: Vector,
+        position: Vec3,
+        world_up: Vec3,
         rotation_sensitivity: f32,
         yaw: f32,
         pitch: f32,
@@ -44,9 +79,10 @@ impl Camera {
             speed,
             position,
             world_up,
-            front: vector![0.0, 0.0, -1.0],
-            right: vector![1.0, 0.0, 0.0],
-            up: vector![0.0, 1.0, 0.0],
+            front: vector_n![0.0, 0.0, -1.0],
+            right: vector_n![1.0, 0.0, 0.0],
+            up: vector_n![0.0, 1.0, 0.0],
+            rotation: Quaternion::identity(),
             rotation_sensitivity,
             yaw,
             pitch,
@@ -55,23 +91,72 @@ impl Camera {
             fov,
             min_fov,
             max_fov,
+            projection: Projection::default(),
+            jitter: Vec2::zeroes(),
+            clip_plane: None,
         };
-        c.update_vectors();
+        c.update_rotation();
         c
     }
 
-    pub fn pos(&self) -> Vector {
-        self.position.clone()
+    pub fn pos(&self) -> Vec3 {
+        self.position
     }
 
     pub fn orientation(&self) -> (f32, f32) {
         (self.yaw, self.pitch)
     }
 
+    pub fn rotation(&self) -> Quaternion {
+        self.rotation
+    }
+
+    /// Sets the orientation directly. Yaw and pitch are recomputed from the
+    /// new front vector, so the next yaw/pitch change drops any roll.
+    pub fn set_rotation(&mut self, rotation: Quaternion) {
+        self.rotation = rotation.unit();
+        self.update_vectors();
+        self.pitch = self.front[1].clamp(-1.0, 1.0).asin().to_degrees();
+        self.yaw = self.front[2].atan2(self.front[0]).to_degrees();
+        self.clamp_yaw();
+    }
+
+    pub fn set_pos(&mut self, position: Vec3) {
+        self.position = position;
+    }
+
+    /// Turns to face `target`, e.g. while following a spline.
+    pub fn look_at(&mut self, target: &Vec3) {
+        self.set_rotation(Quaternion::look_rotation(
+            &(target - self.position),
+            &self.world_up,
+        ));
+    }
+
     pub fn set_aspect(&mut self, aspect: f32) {
         self.aspect = aspect;
     }
 
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    /// Sub-pixel offset in normalized device coordinates, see
+    /// `Mat4::jittered`. Zero disables jittering.
+    pub fn set_jitter(&mut self, jitter: Vec2) {
+        self.jitter = jitter;
+    }
+
+    /// Oblique near plane in view space for mirrors and portals, see
+    /// `Mat4::oblique_near_plane`.
+    pub fn set_clip_plane(&mut self, plane: Option<Vec4>) {
+        self.clip_plane = plane;
+    }
+
     pub fn set_speed(&mut self, speed: f32) {
         self.speed = speed
     }
@@ -79,13 +164,13 @@ impl Camera {
     pub fn set_yaw(&mut self, yaw: f32) {
         self.yaw = yaw;
         self.clamp_yaw();
-        self.update_vectors();
+        self.update_rotation();
     }
 
     pub fn set_yaw_w_offset(&mut self, yaw_offset: f32) {
         self.yaw += yaw_offset;
         self.clamp_yaw();
-        self.update_vectors();
+        self.update_rotation();
     }
 
     fn clamp_yaw(&mut self) {
@@ -107,51 +192,87 @@ impl Camera {
     pub fn set_pitch(&mut self, pitch: f32) {
         self.pitch = pitch;
         self.clamp_pitch();
-        self.update_vectors();
+        self.update_rotation();
     }
 
     pub fn set_pitch_w_offset(&mut self, pitch_offset: f32) {
         self.pitch += pitch_offset;
         self.clamp_pitch();
-        self.update_vectors();
+        self.update_rotation();
     }
 
     fn clamp_pitch(&mut self) {
         self.pitch = clamp(self.pitch, -89.0, 89.0)
     }
 
+    /// A yaw of -90 degrees looks down -z.
+    fn update_rotation(&mut self) {
+        let yaw = Quaternion::from_axis_angle(&self.world_up, -(self.yaw + 90.0).to_radians());
+        let pitch = Quaternion::from_axis_angle(&vector_n![1.0, 0.0, 0.0], self.pitch.to_radians());
+        self.rotation = (yaw * pitch).unit();
+        self.update_vectors();
+    }
+
     fn update_vectors(&mut self) {
-        self.front = vector![
-            self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
-            self.pitch.to_radians().sin(),
-            self.yaw.to_radians().sin() * self.pitch.to_radians().cos()
-        ]
-        .unit();
-        self.right = self.front.cross(&self.world_up).unit();
-        self.up = self.right.cross(&self.front).unit();
+        self.front = self.rotation.rotate(&vector_n![0.0, 0.0, -1.0]).unit();
+        self.right = self.rotation.rotate(&vector_n![1.0, 0.0, 0.0]).unit();
+        self.up = self.rotation.rotate(&vector_n![0.0, 1.0, 0.0]).unit();
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at(&self.position, &(self.position + self.front), &self.up)
     }
 
-    pub fn view(&self) -> Matrix {
-        Matrix::look_at(&self.position, &(&self.position + &self.front), &self.up)
+    pub fn proj(&self) -> Mat4 {
+        let fov = self.fov.to_radians();
+        let mut proj = match self.projection {
+            Projection::Perspective => {
+                Mat4::projection_perspective(fov, self.aspect, self.near, self.far)
+            }
+            Projection::Infinite => {
+                Mat4::projection_perspective_infinite(fov, self.aspect, self.near)
+            }
+            Projection::ReversedZ => {
+                Mat4::projection_perspective_reversed_infinite(fov, self.aspect, self.near)
+            }
+            Projection::Orthographic { height } => {
+                let (w, h) = (height * self.aspect / 2.0, height / 2.0);
+                Mat4::projection_orthographic(-w, w, -h, h, self.near, self.far)
+            }
+            Projection::OffAxis {
+                left,
+                right,
+                bottom,
+                top,
+            } => Mat4::projection_frustum(left, right, bottom, top, self.near, self.far),
+        };
+        if self.jitter != Vec2::zeroes() {
+            proj = proj.jittered(&self.jitter);
+        }
+        if let Some(plane) = &self.clip_plane {
+            proj = proj.oblique_near_plane(plane, self.projection.depth_range());
+        }
+        proj
     }
 
-    pub fn proj(&self) -> Matrix {
-        Matrix::projection_perspective(self.fov.to_radians(), self.aspect, self.near, self.far)
+    /// World space view frustum, for culling.
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.proj() * self.view(), self.projection.depth_range())
     }
 
     pub fn update_pos(&mut self, time_delta: f64, window: &glfw::Window) {
         let speed = self.speed * time_delta as f32;
         if window.get_key(glfw::Key::W) == glfw::Action::Press {
-            self.position += speed * &self.front;
+            self.position += speed * self.front;
         }
         if window.get_key(glfw::Key::S) == glfw::Action::Press {
-            self.position -= speed * &self.front;
+            self.position -= speed * self.front;
         }
         if window.get_key(glfw::Key::A) == glfw::Action::Press {
-            self.position -= speed * &self.right;
+            self.position -= speed * self.right;
         }
         if window.get_key(glfw::Key::D) == glfw::Action::Press {
-            self.position += speed * &self.right;
+            self.position += speed * self.right;
         }
     }
 
@@ -171,8 +292,8 @@ impl Default for Camera {
         Self::new(
             1920.0 / 1080.0,
             3.0,
-            vector![0.0, 0.0, 5.0],
-            vector![0.0, 1.0, 0.0],
+            vector_n![0.0, 0.0, 5.0],
+            vector_n![0.0, 1.0, 0.0],
             0.05,
             -90.0,
             0.0,
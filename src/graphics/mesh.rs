@@ -1,41 +1,41 @@
 use std::{mem::size_of, ptr};
 
-use gl::types::{GLfloat, GLsizei};
+use gl::types::GLsizei;
 use obj::Obj;
 
-use crate::maths::{Matrix, Vector};
+use crate::maths::{Mat4, Quaternion, Transform, Vec3};
+use crate::vector_n;
 
 use super::{
     camera::Camera,
-    wrapper::{ShaderProgram, Texture2D, VertexAttrib, BO, EBO, VAO, VBO},
+    wrapper::{AttribType, Buffer, BufferTarget, ShaderProgram, Texture2D, VertexLayout, VAO},
 };
 
 pub struct Cube<'a> {
-    translation: Matrix,
-    rotation: Matrix,
-    scaling: Matrix,
+    transform: Transform,
     vao: VAO,
-    vbo: VBO,
-    ebo: EBO,
-    attrib: VertexAttrib,
+    // Only read through the VAO, kept alive with it
+    _vbo: Buffer<Vec3>,
+    // Also read for the index count and type when drawing
+    ebo: Buffer<u8>,
     texture: Option<&'a Texture2D>,
 }
 
 impl<'a> Cube<'a> {
     pub fn new(
-        model_matrices: Option<(Matrix, Matrix, Matrix)>,
+        transform: Option<Transform>,
         texture: Option<&'a Texture2D>,
         tex_coords: Option<Vec<f32>>,
     ) -> Self {
-        let mut vertex_data = vec![
-            0.5, 0.5, 0.5, //0
-            0.5, 0.5, -0.5, //1
-            0.5, -0.5, 0.5, //2
-            0.5, -0.5, -0.5, //3
-            -0.5, 0.5, 0.5, //4
-            -0.5, 0.5, -0.5, //5
-            -0.5, -0.5, 0.5, //6
-            -0.5, -0.5, -0.5, //7
+        let vertex_data = [
+            vector_n![0.5, 0.5, 0.5],    //0
+            vector_n![0.5, 0.5, -0.5],   //1
+            vector_n![0.5, -0.5, 0.5],   //2
+            vector_n![0.5, -0.5, -0.5],  //3
+            vector_n![-0.5, 0.5, 0.5],   //4
+            vector_n![-0.5, 0.5, -0.5],  //5
+            vector_n![-0.5, -0.5, 0.5],  //6
+            vector_n![-0.5, -0.5, -0.5], //7
         ];
 
         // match texture {
@@ -55,10 +55,11 @@ impl<'a> Cube<'a> {
         // }
 
         let vao = VAO::new();
-        let vbo: VBO = BO::new(gl::STATIC_DRAW, vertex_data);
-        let ebo: EBO = BO::new(
+        let vbo = Buffer::new(BufferTarget::Array, gl::STATIC_DRAW, &vertex_data);
+        let ebo = Buffer::<u8>::new(
+            BufferTarget::ElementArray,
             gl::STATIC_DRAW,
-            vec![
+            &[
                 4, 5, 1, 4, 1, 0, //Top
                 7, 6, 2, 7, 2, 3, //Bottom
                 7, 5, 4, 7, 4, 6, //Left
@@ -67,71 +68,73 @@ impl<'a> Cube<'a> {
                 3, 1, 5, 3, 5, 7, //Back
             ],
         );
-        let attrib = VertexAttrib::new(
-            0,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            3 * size_of::<GLfloat>() as GLsizei,
-            ptr::null(),
+        vao.attach(
+            &vbo,
+            &VertexLayout::new(size_of::<Vec3>()).attribute("pos", 0, Vec3::FORMAT, 0),
         );
-
-        attrib.disable();
-        ebo.unbind();
-        vbo.unbind();
-        vao.unbind();
-
-        let (t, r, s) = model_matrices.unwrap_or((
-            Matrix::identity(4),
-            Matrix::identity(4),
-            Matrix::identity(4),
-        ));
+        vao.set_index_buffer(&ebo);
 
         Cube {
-            translation: t,
-            rotation: r,
-            scaling: s,
+            transform: transform.unwrap_or_default(),
             vao,
-            vbo,
+            _vbo: vbo,
             ebo,
-            attrib,
             texture,
         }
     }
 
-    pub fn set_pos(&mut self, pos: Vector) {
-        self.translation = Matrix::translation(pos)
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
     }
 
-    pub fn translate(&mut self, v: Vector) {
-        self.translation = &self.translation * Matrix::translation(v);
+    pub fn set_pos(&mut self, pos: Vec3) {
+        self.transform.set_position(pos)
     }
 
-    pub fn model(&self) -> Matrix {
-        &self.scaling * &self.rotation * &self.translation
+    pub fn translate(&mut self, v: Vec3) {
+        self.transform.translate(v);
     }
 
-    pub fn pos(&self) -> Vector {
-        let mut p = self.translation.clone().col(3);
-        p.pop();
-        p.into()
+    pub fn rotation(&self) -> Quaternion {
+        self.transform.rotation()
     }
 
+    pub fn set_rotation(&mut self, rotation: Quaternion) {
+        self.transform.set_rotation(rotation);
+    }
+
+    pub fn rotate(&mut self, rotation: Quaternion) {
+        self.transform.rotate(rotation);
+    }
+
+    pub fn model(&self) -> Mat4 {
+        self.transform.to_mat4()
+    }
+
+    pub fn pos(&self) -> Vec3 {
+        self.transform.position()
+    }
+
+    /// Leaves the shader and VAO bound, so drawing several cubes in a row
+    /// only binds them once.
     pub fn draw(&mut self, camera: &Camera, shader: &mut ShaderProgram) {
         shader.bind();
         self.vao.bind();
-        self.vbo.bind();
-        self.ebo.bind();
-        self.attrib.enable();
-        shader.uniform_matrix_4fv("proj", &camera.proj());
-        shader.uniform_matrix_4fv("view", &camera.view());
-        shader.uniform_matrix_4fv("model", &self.model());
-        unsafe { gl::DrawElements(gl::TRIANGLES, 36, gl::UNSIGNED_INT, ptr::null()) }
-        self.attrib.disable();
-        self.ebo.unbind();
-        self.vbo.unbind();
-        self.vao.unbind();
-        shader.unbind();
+        shader.uniform_matrix_4fv("proj", camera.proj());
+        shader.uniform_matrix_4fv("view", camera.view());
+        shader.uniform_matrix_4fv("model", self.model());
+        unsafe {
+            gl::DrawElements(
+                gl::TRIANGLES,
+                self.ebo.len() as GLsizei,
+                self.ebo.index_type(),
+                ptr::null(),
+            )
+        }
     }
 }
 
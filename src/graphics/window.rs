@@ -6,7 +6,9 @@ use std::os::raw::c_void;
 use std::ptr;
 use std::sync::mpsc::Receiver;
 
-use super::camera::Camera;
+use super::camera::{Camera, Projection};
+use super::wrapper::{invalidate_state, load_bindless, ShaderProgram};
+use crate::maths::DepthRange;
 
 pub struct Window {
     glfw: glfw::Glfw,
@@ -50,6 +52,7 @@ impl Window {
         gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
         gl::Viewport::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+        load_bindless(|symbol| window.get_proc_address(symbol) as *const _);
 
         glfw.set_swap_interval(glfw::SwapInterval::Sync(1));
 
@@ -102,6 +105,26 @@ impl Window {
         &self.camera
     }
 
+    pub fn camera_handle_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Sets the camera's projection along with the matching clip depth
+    /// range, depth test and depth clear value.
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.camera.set_projection(projection);
+        let (depth_mode, depth_func, clear_depth) = match projection.depth_range() {
+            DepthRange::NegativeOneToOne => (gl::NEGATIVE_ONE_TO_ONE, gl::LESS, 1.0),
+            DepthRange::ZeroToOne => (gl::ZERO_TO_ONE, gl::LESS, 1.0),
+            DepthRange::Reversed => (gl::ZERO_TO_ONE, gl::GREATER, 0.0),
+        };
+        unsafe {
+            gl::ClipControl(gl::LOWER_LEFT, depth_mode);
+            gl::DepthFunc(depth_func);
+            gl::ClearDepth(clear_depth);
+        }
+    }
+
     pub fn is_camera_still(&self) -> bool {
         self.window.get_cursor_mode() == glfw::CursorMode::Normal
     }
@@ -117,6 +140,8 @@ impl Window {
     pub fn end_ui(&mut self) {
         let (w, h) = self.window.get_framebuffer_size();
         let output = self.ui.end_frame((w as _, h as _));
+        // The UI renderer binds its own program, VAO and texture
+        invalidate_state();
         if !output.platform_output.copied_text.is_empty() {
             match copypasta_ext::try_context() {
                 Some(mut context) => context
@@ -134,6 +159,7 @@ impl Window {
         self.process_events();
         self.process_errors();
         self.window.swap_buffers();
+        ShaderProgram::reload_changed();
 
         let cur_frame = self.glfw.get_time();
         self.time_delta = cur_frame - self.last_frame;
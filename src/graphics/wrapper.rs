@@ -1,11 +1,39 @@
-pub mod bo;
+pub mod bindless;
+pub mod buffer;
+pub mod compute;
+pub mod preprocessor;
+pub mod program_builder;
+pub mod program_pipeline;
+pub mod reflection;
+pub mod shader_error;
 pub mod shader_program;
+pub mod ssbo;
+pub mod state;
+pub mod std140;
 pub mod texture;
+pub mod texture_builder;
+pub mod texture_set;
+pub mod ubo;
+pub mod uniform;
 pub mod vao;
-pub mod vertex_attrib;
+pub mod vertex_layout;
 
-pub use bo::*;
+pub use bindless::*;
+pub use buffer::*;
+pub use compute::*;
+pub use preprocessor::*;
+pub use program_builder::*;
+pub use program_pipeline::*;
+pub use reflection::*;
+pub use shader_error::*;
 pub use shader_program::*;
+pub use ssbo::*;
+pub use state::*;
+pub use std140::*;
 pub use texture::*;
+pub use texture_builder::*;
+pub use texture_set::*;
+pub use ubo::*;
+pub use uniform::*;
 pub use vao::*;
-pub use vertex_attrib::*;
+pub use vertex_layout::*;
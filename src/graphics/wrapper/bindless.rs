@@ -0,0 +1,90 @@
+use std::{cell::Cell, ffi::CStr, mem, os::raw::c_void};
+
+use gl::types::*;
+
+// GL_ARB_bindless_texture isn't in the generated bindings, so its entry
+// points are loaded by hand
+type GetTextureHandle = unsafe extern "system" fn(GLuint) -> GLuint64;
+type MakeHandleResident = unsafe extern "system" fn(GLuint64);
+type ProgramUniformHandle = unsafe extern "system" fn(GLuint, GLint, GLuint64);
+
+#[derive(Clone, Copy)]
+struct Functions {
+    get_texture_handle: GetTextureHandle,
+    make_resident: MakeHandleResident,
+    make_non_resident: MakeHandleResident,
+    program_uniform_handle: ProgramUniformHandle,
+}
+
+thread_local! {
+    /// Loaded by `load_bindless` when the context supports the extension.
+    static BINDLESS: Cell<Option<Functions>> = const { Cell::new(None) };
+}
+
+/// Loads `GL_ARB_bindless_texture` if the current context has it, with the
+/// same loader as `gl::load_with`. Called by `Window::new`.
+pub fn load_bindless(mut loadfn: impl FnMut(&'static str) -> *const c_void) {
+    let supported = unsafe {
+        let mut count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        (0..count as GLuint).any(|i| {
+            let name = gl::GetStringi(gl::EXTENSIONS, i);
+            !name.is_null() && CStr::from_ptr(name.cast()).to_bytes() == b"GL_ARB_bindless_texture"
+        })
+    };
+    if !supported {
+        return;
+    }
+
+    let pointers = [
+        loadfn("glGetTextureHandleARB"),
+        loadfn("glMakeTextureHandleResidentARB"),
+        loadfn("glMakeTextureHandleNonResidentARB"),
+        loadfn("glProgramUniformHandleui64ARB"),
+    ];
+    if pointers.iter().any(|pointer| pointer.is_null()) {
+        return;
+    }
+    // SAFETY: non-null entry points of the extension, with the signatures
+    // from its specification
+    let functions = unsafe {
+        Functions {
+            get_texture_handle: mem::transmute::<*const c_void, GetTextureHandle>(pointers[0]),
+            make_resident: mem::transmute::<*const c_void, MakeHandleResident>(pointers[1]),
+            make_non_resident: mem::transmute::<*const c_void, MakeHandleResident>(pointers[2]),
+            program_uniform_handle: mem::transmute::<*const c_void, ProgramUniformHandle>(
+                pointers[3],
+            ),
+        }
+    };
+    BINDLESS.with(|bindless| bindless.set(Some(functions)));
+}
+
+/// Whether textures can be sampled through handles instead of units.
+pub fn bindless_available() -> bool {
+    BINDLESS.with(|bindless| bindless.get().is_some())
+}
+
+/// A resident handle of texture `id`. The texture's sampling parameters
+/// can't change afterwards.
+pub(crate) fn texture_handle(id: GLuint) -> Option<GLuint64> {
+    let functions = BINDLESS.with(Cell::get)?;
+    unsafe {
+        let handle = (functions.get_texture_handle)(id);
+        (functions.make_resident)(handle);
+        Some(handle)
+    }
+}
+
+pub(crate) fn release_handle(handle: GLuint64) {
+    if let Some(functions) = BINDLESS.with(Cell::get) {
+        unsafe { (functions.make_non_resident)(handle) };
+    }
+}
+
+/// Points sampler `location` of `program` at a texture handle.
+pub(crate) fn set_handle_uniform(program: GLuint, location: GLint, handle: GLuint64) {
+    if let Some(functions) = BINDLESS.with(Cell::get) {
+        unsafe { (functions.program_uniform_handle)(program, location, handle) };
+    }
+}
@@ -0,0 +1,415 @@
+use std::{marker::PhantomData, mem, ops::Range, ptr, slice};
+
+use bytemuck::Pod;
+use gl::types::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BufferTarget {
+    Array,
+    ElementArray,
+    Uniform,
+    ShaderStorage,
+    DrawIndirect,
+    DispatchIndirect,
+    CopyRead,
+    CopyWrite,
+    PixelPack,
+    PixelUnpack,
+}
+
+impl BufferTarget {
+    pub fn gl_enum(&self) -> GLenum {
+        match self {
+            BufferTarget::Array => gl::ARRAY_BUFFER,
+            BufferTarget::ElementArray => gl::ELEMENT_ARRAY_BUFFER,
+            BufferTarget::Uniform => gl::UNIFORM_BUFFER,
+            BufferTarget::ShaderStorage => gl::SHADER_STORAGE_BUFFER,
+            BufferTarget::DrawIndirect => gl::DRAW_INDIRECT_BUFFER,
+            BufferTarget::DispatchIndirect => gl::DISPATCH_INDIRECT_BUFFER,
+            BufferTarget::CopyRead => gl::COPY_READ_BUFFER,
+            BufferTarget::CopyWrite => gl::COPY_WRITE_BUFFER,
+            BufferTarget::PixelPack => gl::PIXEL_PACK_BUFFER,
+            BufferTarget::PixelUnpack => gl::PIXEL_UNPACK_BUFFER,
+        }
+    }
+}
+
+/// Element types of index buffers.
+pub trait IndexType: Pod {
+    const GL_TYPE: GLenum;
+}
+
+impl IndexType for u8 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_BYTE;
+}
+
+impl IndexType for u16 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_SHORT;
+}
+
+impl IndexType for u32 {
+    const GL_TYPE: GLenum = gl::UNSIGNED_INT;
+}
+
+/// A buffer of `len` elements of `T`, e.g. a `#[repr(C)]` vertex struct
+/// deriving `Pod`. Offsets and lengths are in elements, not bytes.
+pub struct Buffer<T: Pod> {
+    id: GLuint,
+    target: BufferTarget,
+    usage: GLenum,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Buffer<T> {
+    pub fn new(target: BufferTarget, usage: GLenum, data: &[T]) -> Buffer<T> {
+        let mut id = 0;
+        unsafe {
+            gl::CreateBuffers(1, &mut id);
+        }
+        let mut buffer = Buffer {
+            id,
+            target,
+            usage,
+            len: 0,
+            _marker: PhantomData,
+        };
+        buffer.set_data(data);
+        buffer
+    }
+
+    /// `len` elements with undefined contents.
+    pub fn with_len(target: BufferTarget, usage: GLenum, len: usize) -> Buffer<T> {
+        let mut buffer = Self::new(target, usage, &[]);
+        buffer.resize(len);
+        buffer
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn target(&self) -> BufferTarget {
+        self.target
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn size_bytes(&self) -> usize {
+        self.len * mem::size_of::<T>()
+    }
+
+    /// Replaces the storage with `data`, resizing if needed.
+    pub fn set_data(&mut self, data: &[T]) {
+        unsafe {
+            gl::NamedBufferData(
+                self.id,
+                mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr().cast(),
+                self.usage,
+            );
+        }
+        self.len = data.len();
+    }
+
+    /// Overwrites the elements starting at `offset`.
+    pub fn sub_data(&self, offset: usize, data: &[T]) {
+        assert!(
+            offset + data.len() <= self.len,
+            "Write past the end of the buffer"
+        );
+        unsafe {
+            gl::NamedBufferSubData(
+                self.id,
+                (offset * mem::size_of::<T>()) as GLintptr,
+                mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr().cast(),
+            );
+        }
+    }
+
+    /// Orphans the storage: the driver hands out fresh memory, so the next
+    /// writes don't wait for draws still reading the old contents, which are
+    /// lost.
+    pub fn orphan(&self) {
+        unsafe {
+            gl::NamedBufferData(
+                self.id,
+                self.size_bytes() as GLsizeiptr,
+                ptr::null(),
+                self.usage,
+            );
+        }
+    }
+
+    /// Orphans the storage with room for `len` elements. The contents are
+    /// undefined afterwards.
+    pub fn resize(&mut self, len: usize) {
+        self.len = len;
+        self.orphan();
+    }
+
+    /// Copies `len` elements from `src` at `src_offset` to `dst_offset`, on
+    /// the GPU.
+    pub fn copy_from(&self, src: &Buffer<T>, src_offset: usize, dst_offset: usize, len: usize) {
+        assert!(
+            src_offset + len <= src.len,
+            "Copy past the end of the source"
+        );
+        assert!(
+            dst_offset + len <= self.len,
+            "Copy past the end of the buffer"
+        );
+        let size = mem::size_of::<T>();
+        unsafe {
+            gl::CopyNamedBufferSubData(
+                src.id,
+                self.id,
+                (src_offset * size) as GLintptr,
+                (dst_offset * size) as GLintptr,
+                (len * size) as GLsizeiptr,
+            );
+        }
+    }
+
+    pub fn read(&self) -> Vec<T> {
+        self.read_range(0..self.len)
+    }
+
+    pub fn read_range(&self, range: Range<usize>) -> Vec<T> {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Read past the end of the buffer"
+        );
+        let mut out = vec![T::zeroed(); range.len()];
+        unsafe {
+            gl::GetNamedBufferSubData(
+                self.id,
+                (range.start * mem::size_of::<T>()) as GLintptr,
+                mem::size_of_val(out.as_slice()) as GLsizeiptr,
+                out.as_mut_ptr().cast(),
+            );
+        }
+        out
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(self.target.gl_enum(), self.id) }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindBuffer(self.target.gl_enum(), 0) }
+    }
+
+    /// Binds the whole buffer to `layout(binding = index)` of the uniform or
+    /// storage blocks, depending on the target.
+    pub fn bind_base(&self, index: GLuint) {
+        bind_indexed::<T>(self.target, self.id, index, None);
+    }
+
+    /// Binds the elements in `range` to `layout(binding = index)`. The
+    /// start has to meet the target's offset alignment, e.g. 256 bytes.
+    pub fn bind_range(&self, index: GLuint, range: Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Bind past the end of the buffer"
+        );
+        bind_indexed::<T>(self.target, self.id, index, Some(range));
+    }
+}
+
+impl<T: IndexType> Buffer<T> {
+    /// The `type` argument of `glDrawElements`.
+    pub fn index_type(&self) -> GLenum {
+        T::GL_TYPE
+    }
+}
+
+impl<T: Pod> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
+/// A persistently mapped, coherent buffer split into `regions` of `len`
+/// elements, for data rewritten every frame. Each frame writes the next
+/// region while the GPU may still read the previous ones; a fence per region
+/// keeps the CPU from overwriting data in flight.
+///
+/// Per frame: `next_region` to wait for and fill a region, issue the draws
+/// reading it at `offset`, then `fence`.
+pub struct StreamBuffer<T: Pod> {
+    id: GLuint,
+    target: BufferTarget,
+    len: usize,
+    ptr: *mut T,
+    current: usize,
+    fences: Vec<GLsync>,
+}
+
+impl<T: Pod> StreamBuffer<T> {
+    /// Three regions are usually enough to never wait.
+    pub fn new(target: BufferTarget, len: usize, regions: usize) -> StreamBuffer<T> {
+        assert!(regions > 0, "Stream buffer without regions");
+        let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+        let data = vec![T::zeroed(); len * regions];
+        let mut id = 0;
+        let ptr = unsafe {
+            gl::CreateBuffers(1, &mut id);
+            gl::NamedBufferStorage(
+                id,
+                mem::size_of_val(data.as_slice()) as GLsizeiptr,
+                data.as_ptr().cast(),
+                flags,
+            );
+            gl::MapNamedBufferRange(
+                id,
+                0,
+                mem::size_of_val(data.as_slice()) as GLsizeiptr,
+                flags,
+            )
+        };
+        assert!(!ptr.is_null(), "Failed to map stream buffer");
+
+        StreamBuffer {
+            id,
+            target,
+            len,
+            ptr: ptr.cast(),
+            current: regions - 1,
+            fences: vec![ptr::null(); regions],
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Elements per region.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn regions(&self) -> usize {
+        self.fences.len()
+    }
+
+    /// Element offset of the current region into the whole buffer.
+    pub fn offset(&self) -> usize {
+        self.current * self.len
+    }
+
+    /// Elements of the current region in the whole buffer.
+    pub fn region(&self) -> Range<usize> {
+        self.offset()..self.offset() + self.len
+    }
+
+    /// Moves to the next region, waiting until the GPU is done with it.
+    pub fn next_region(&mut self) -> &mut [T] {
+        self.current = (self.current + 1) % self.fences.len();
+        let fence = mem::replace(&mut self.fences[self.current], ptr::null());
+        if !fence.is_null() {
+            unsafe {
+                while gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, 1_000_000)
+                    == gl::TIMEOUT_EXPIRED
+                {}
+                gl::DeleteSync(fence);
+            }
+        }
+        // SAFETY: the mapping covers every region and stays valid until drop,
+        // and the fence wait means the GPU no longer reads this region
+        unsafe { slice::from_raw_parts_mut(self.ptr.add(self.offset()), self.len) }
+    }
+
+    /// Marks the current region as in use by the commands issued so far.
+    pub fn fence(&mut self) {
+        unsafe {
+            let fence = &mut self.fences[self.current];
+            if !fence.is_null() {
+                gl::DeleteSync(*fence);
+            }
+            *fence = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(self.target.gl_enum(), self.id) }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindBuffer(self.target.gl_enum(), 0) }
+    }
+
+    /// Binds every region to `layout(binding = index)`, see
+    /// `Buffer::bind_base`.
+    pub fn bind_base(&self, index: GLuint) {
+        bind_indexed::<T>(self.target, self.id, index, None);
+    }
+
+    /// Binds the elements in `range` of the whole buffer, usually `region()`,
+    /// to `layout(binding = index)`. Regions of a uniform stream buffer have
+    /// to be sized to the offset alignment.
+    pub fn bind_range(&self, index: GLuint, range: Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len * self.regions(),
+            "Bind past the end of the stream buffer"
+        );
+        bind_indexed::<T>(self.target, self.id, index, Some(range));
+    }
+}
+
+/// `glBindBufferBase`, or `glBindBufferRange` for the elements in `range`.
+fn bind_indexed<T>(target: BufferTarget, id: GLuint, index: GLuint, range: Option<Range<usize>>) {
+    let alignment = match target {
+        BufferTarget::Uniform => gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT,
+        BufferTarget::ShaderStorage => gl::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT,
+        _ => panic!("Only uniform and storage buffers have indexed bindings"),
+    };
+    let Some(range) = range else {
+        unsafe { gl::BindBufferBase(target.gl_enum(), index, id) };
+        return;
+    };
+    let size = mem::size_of::<T>();
+    let mut align = 1;
+    unsafe { gl::GetIntegerv(alignment, &mut align) };
+    assert!(
+        (range.start * size).is_multiple_of(align.max(1) as usize),
+        "Buffer range offset not aligned to {} bytes",
+        align
+    );
+    unsafe {
+        gl::BindBufferRange(
+            target.gl_enum(),
+            index,
+            id,
+            (range.start * size) as GLintptr,
+            (range.len() * size) as GLsizeiptr,
+        );
+    }
+}
+
+impl<T: Pod> Drop for StreamBuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            for &fence in &self.fences {
+                if !fence.is_null() {
+                    gl::DeleteSync(fence);
+                }
+            }
+            gl::UnmapNamedBuffer(self.id);
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    mem,
+    ops::{BitOr, Deref, DerefMut},
+};
+
+use bytemuck::Pod;
+use gl::types::*;
+
+use super::buffer::Buffer;
+use super::preprocessor::{Defines, Preprocessor};
+use super::shader_error::{ShaderError, ShaderStage};
+use super::shader_program::ShaderProgram;
+
+/// A program with a single compute stage. Derefs to `ShaderProgram` for
+/// uniforms, reflection and hot reloading.
+pub struct ComputeProgram {
+    program: ShaderProgram,
+}
+
+impl ComputeProgram {
+    pub fn new(compute_shader_path: &str) -> Result<ComputeProgram, ShaderError> {
+        Self::with_defines(
+            compute_shader_path,
+            &Preprocessor::default(),
+            &Defines::default(),
+        )
+    }
+
+    pub fn with_defines(
+        compute_shader_path: &str,
+        preprocessor: &Preprocessor,
+        defines: &Defines,
+    ) -> Result<ComputeProgram, ShaderError> {
+        let program = ShaderProgram::builder()
+            .stage(ShaderStage::Compute, compute_shader_path)
+            .preprocessor(preprocessor.clone())
+            .defines(defines.clone())
+            .build()?;
+        Ok(ComputeProgram { program })
+    }
+
+    pub fn from_source(compute_shader_src: &str) -> Result<ComputeProgram, ShaderError> {
+        let program = ShaderProgram::builder()
+            .stage_source(ShaderStage::Compute, compute_shader_src)
+            .build()?;
+        Ok(ComputeProgram { program })
+    }
+
+    /// The `local_size_x/y/z` of the shader. Stale after a reload until the
+    /// next dispatch or `apply_reload`, like `ShaderProgram::id`.
+    pub fn work_group_size(&self) -> [u32; 3] {
+        let mut size = [0; 3];
+        unsafe {
+            gl::GetProgramiv(
+                self.program.id(),
+                gl::COMPUTE_WORK_GROUP_SIZE,
+                size.as_mut_ptr(),
+            );
+        }
+        size.map(|s| s as u32)
+    }
+
+    /// Binds the program and runs `x * y * z` work groups.
+    pub fn dispatch(&mut self, x: u32, y: u32, z: u32) {
+        self.program.bind();
+        unsafe { gl::DispatchCompute(x, y, z) }
+    }
+
+    /// Runs enough work groups to cover `invocations`, e.g. one per
+    /// particle. The shader has to skip the excess invocations.
+    pub fn dispatch_for(&mut self, invocations: [u32; 3]) {
+        self.program.apply_reload();
+        let [x, y, z] = work_group_count(invocations, self.work_group_size());
+        self.dispatch(x, y, z);
+    }
+
+    /// Runs the work group counts stored as three `u32`s at byte `offset` of
+    /// `buffer`, e.g. an `SSBO` written by a culling pass. Needs a
+    /// `Barrier::COMMAND` after the pass that wrote them.
+    pub fn dispatch_indirect<T: Pod>(&mut self, buffer: &Buffer<T>, offset: usize) {
+        assert!(
+            offset.is_multiple_of(4) && offset + 3 * 4 <= buffer.len() * mem::size_of::<T>(),
+            "Indirect dispatch arguments outside the buffer"
+        );
+        self.program.bind();
+        unsafe {
+            gl::BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, buffer.id());
+            gl::DispatchComputeIndirect(offset as GLintptr);
+            gl::BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, 0);
+        }
+    }
+}
+
+impl Deref for ComputeProgram {
+    type Target = ShaderProgram;
+
+    fn deref(&self) -> &ShaderProgram {
+        &self.program
+    }
+}
+
+impl DerefMut for ComputeProgram {
+    fn deref_mut(&mut self) -> &mut ShaderProgram {
+        &mut self.program
+    }
+}
+
+/// Work groups of `size` needed to cover `invocations` along each axis,
+/// rounded up.
+pub fn work_group_count(invocations: [u32; 3], size: [u32; 3]) -> [u32; 3] {
+    std::array::from_fn(|i| invocations[i].div_ceil(size[i].max(1)))
+}
+
+/// Which later reads `memory_barrier` makes shader writes visible to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Barrier(GLbitfield);
+
+impl Barrier {
+    /// Storage buffer reads and writes in later shaders.
+    pub const SHADER_STORAGE: Barrier = Barrier(gl::SHADER_STORAGE_BARRIER_BIT);
+    /// Vertex attributes sourced from the written buffers.
+    pub const VERTEX_ATTRIB_ARRAY: Barrier = Barrier(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    /// Indices sourced from the written buffers.
+    pub const ELEMENT_ARRAY: Barrier = Barrier(gl::ELEMENT_ARRAY_BARRIER_BIT);
+    pub const UNIFORM: Barrier = Barrier(gl::UNIFORM_BARRIER_BIT);
+    /// Indirect draw and dispatch arguments.
+    pub const COMMAND: Barrier = Barrier(gl::COMMAND_BARRIER_BIT);
+    /// Read-back and copies, e.g. `Buffer::read`.
+    pub const BUFFER_UPDATE: Barrier = Barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    pub const TEXTURE_FETCH: Barrier = Barrier(gl::TEXTURE_FETCH_BARRIER_BIT);
+    pub const SHADER_IMAGE_ACCESS: Barrier = Barrier(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    pub const ALL: Barrier = Barrier(gl::ALL_BARRIER_BITS);
+
+    pub fn bits(&self) -> GLbitfield {
+        self.0
+    }
+}
+
+impl BitOr for Barrier {
+    type Output = Barrier;
+
+    fn bitor(self, rhs: Barrier) -> Barrier {
+        Barrier(self.0 | rhs.0)
+    }
+}
+
+/// Orders earlier shader writes before the reads in `barrier`, e.g.
+/// `Barrier::SHADER_STORAGE | Barrier::VERTEX_ATTRIB_ARRAY` between a
+/// particle update and drawing the particles.
+pub fn memory_barrier(barrier: Barrier) {
+    unsafe { gl::MemoryBarrier(barrier.bits()) }
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::shader_error::ShaderError;
+
+/// `#define`s injected after `#version`, e.g. `TEXTURED` or `NUM_LIGHTS=4`.
+/// Kept sorted so equal sets compare and hash equal, which makes them usable
+/// as cache keys.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Defines {
+    values: BTreeMap<String, String>,
+}
+
+impl Defines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `NAME` or `NAME=VALUE`, replacing an earlier value.
+    pub fn insert(&mut self, define: &str) {
+        let (name, value) = define.split_once('=').unwrap_or((define, ""));
+        self.values
+            .insert(name.trim().to_string(), value.trim().to_string());
+    }
+
+    pub fn with(mut self, define: &str) -> Self {
+        self.insert(define);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// `(name, value)` pairs, with an empty value for flags.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Defines {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut defines = Defines::new();
+        iter.into_iter().for_each(|define| defines.insert(define));
+        defines
+    }
+}
+
+/// Preprocessed shader source and where each of its lines came from.
+#[derive(Clone, Debug)]
+pub struct Source {
+    text: String,
+    names: Vec<String>,
+    files: Vec<PathBuf>,
+    /// Per output line, an index into `names` and the line there. `None` for
+    /// injected lines.
+    lines: Vec<Option<(usize, u32)>>,
+}
+
+impl Source {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Every file read, i.e. the shader and its includes.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// The file name and line of the 1-based output `line`.
+    pub fn locate(&self, line: u32) -> Option<(&str, u32)> {
+        let (name, line) = (*self.lines.get(line.checked_sub(1)? as usize)?)?;
+        Some((&self.names[name], line))
+    }
+
+    fn push(&mut self, line: &str, origin: Option<(usize, u32)>) {
+        self.text.push_str(line);
+        self.text.push('\n');
+        self.lines.push(origin);
+    }
+}
+
+/// Expands `#include "file"` and injects `Defines`. Includes are resolved
+/// relative to the including file, then against each include directory in
+/// order. A root file without `#version` gets the default version, and
+/// `#version` lines of included files are dropped. `#pragma once` is
+/// honoured. Conditionals are left to the driver, so an include inside
+/// `#if 0` still has to exist.
+#[derive(Clone, Debug)]
+pub struct Preprocessor {
+    include_dirs: Vec<PathBuf>,
+    default_version: String,
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `#version` of root files without one, `460 core` unless changed.
+    pub fn default_version(mut self, version: &str) -> Self {
+        self.default_version = version.to_string();
+        self
+    }
+
+    pub fn version(&self) -> &str {
+        &self.default_version
+    }
+
+    pub fn include_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.include_dirs.push(dir.into());
+        self
+    }
+
+    pub fn include_dirs(&self) -> &[PathBuf] {
+        &self.include_dirs
+    }
+
+    pub fn process(&self, path: &Path, defines: &Defines) -> Result<Source, ShaderError> {
+        self.process_tracked(path, defines).0
+    }
+
+    /// `process`, also returning every file read or looked for. They are
+    /// known even when it fails, e.g. on an include that doesn't exist yet,
+    /// so hot reloading can watch them.
+    pub(crate) fn process_tracked(
+        &self,
+        path: &Path,
+        defines: &Defines,
+    ) -> (Result<Source, ShaderError>, Vec<PathBuf>) {
+        let mut expansion = Expansion::new(self);
+        expansion.source.files.push(path.to_path_buf());
+        let result = read(path).and_then(|src| {
+            expansion.stack.push(canonical(path));
+            expansion.expand(
+                &src,
+                path.display().to_string(),
+                path.parent(),
+                Some(defines),
+            )
+        });
+        expansion.finish(result)
+    }
+
+    /// Like `process` for a source without a file, e.g. one embedded in the
+    /// binary. `name` stands in for the path in error messages.
+    pub fn process_str(
+        &self,
+        src: &str,
+        name: &str,
+        defines: &Defines,
+    ) -> Result<Source, ShaderError> {
+        self.process_str_tracked(src, name, defines).0
+    }
+
+    /// `process_str`, also returning the files looked for like
+    /// `process_tracked`.
+    pub(crate) fn process_str_tracked(
+        &self,
+        src: &str,
+        name: &str,
+        defines: &Defines,
+    ) -> (Result<Source, ShaderError>, Vec<PathBuf>) {
+        let mut expansion = Expansion::new(self);
+        let result = expansion.expand(src, name.to_string(), None, Some(defines));
+        expansion.finish(result)
+    }
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Preprocessor {
+            include_dirs: Vec::new(),
+            default_version: "460 core".to_string(),
+        }
+    }
+}
+
+struct Expansion<'a> {
+    preprocessor: &'a Preprocessor,
+    source: Source,
+    /// Files currently being expanded, to catch include cycles.
+    stack: Vec<PathBuf>,
+    once: HashSet<PathBuf>,
+    /// Where an include that couldn't be found was first looked for.
+    missing: Option<PathBuf>,
+}
+
+impl<'a> Expansion<'a> {
+    fn new(preprocessor: &'a Preprocessor) -> Self {
+        Expansion {
+            preprocessor,
+            source: Source {
+                text: String::new(),
+                names: Vec::new(),
+                files: Vec::new(),
+                lines: Vec::new(),
+            },
+            stack: Vec::new(),
+            once: HashSet::new(),
+            missing: None,
+        }
+    }
+
+    fn finish(
+        self,
+        result: Result<(), ShaderError>,
+    ) -> (Result<Source, ShaderError>, Vec<PathBuf>) {
+        let mut files = self.source.files.clone();
+        files.extend(self.missing);
+        (result.map(|_| self.source), files)
+    }
+
+    /// `defines` is only given for the root file.
+    fn expand(
+        &mut self,
+        src: &str,
+        name: String,
+        dir: Option<&Path>,
+        defines: Option<&Defines>,
+    ) -> Result<(), ShaderError> {
+        let file = self.source.names.len();
+        self.source.names.push(name.clone());
+
+        let has_version = src.lines().any(|line| directive(line).0 == "version");
+        if let (false, Some(defines)) = (has_version, defines) {
+            let version = format!("#version {}", self.preprocessor.default_version);
+            self.source.push(&version, None);
+            self.inject(defines);
+        }
+
+        for (i, line) in src.lines().enumerate() {
+            let line_no = i as u32 + 1;
+            let error = |message: String| ShaderError::Preprocess {
+                file: name.clone(),
+                line: line_no,
+                message,
+            };
+            match directive(line) {
+                ("version", _) => {
+                    if let Some(defines) = defines {
+                        self.source.push(line, Some((file, line_no)));
+                        self.inject(defines);
+                    }
+                }
+                ("pragma", "once") => {
+                    if let Some(path) = self.stack.last() {
+                        self.once.insert(path.clone());
+                    }
+                }
+                ("include", arg) => {
+                    let include = arg
+                        .strip_prefix('"')
+                        .and_then(|arg| arg.strip_suffix('"'))
+                        .or_else(|| arg.strip_prefix('<')?.strip_suffix('>'))
+                        .ok_or_else(|| error(format!("Malformed #include {}", arg)))?;
+                    let Some(path) = self.resolve(include, dir) else {
+                        self.missing = dir
+                            .or(self.preprocessor.include_dirs.first().map(PathBuf::as_path))
+                            .map(|dir| dir.join(include));
+                        return Err(error(format!("Cannot find include \"{}\"", include)));
+                    };
+                    let key = canonical(&path);
+                    if self.once.contains(&key) {
+                        continue;
+                    }
+                    if self.stack.contains(&key) {
+                        return Err(error(format!("Include cycle through \"{}\"", include)));
+                    }
+
+                    let src = read(&path)?;
+                    self.source.files.push(path.clone());
+                    self.stack.push(key);
+                    self.expand(&src, path.display().to_string(), path.parent(), None)?;
+                    self.stack.pop();
+                }
+                _ => self.source.push(line, Some((file, line_no))),
+            }
+        }
+        Ok(())
+    }
+
+    fn inject(&mut self, defines: &Defines) {
+        for (name, value) in defines.iter() {
+            self.source
+                .push(&format!("#define {} {}", name, value), None);
+        }
+    }
+
+    fn resolve(&self, include: &str, dir: Option<&Path>) -> Option<PathBuf> {
+        dir.into_iter()
+            .chain(self.preprocessor.include_dirs.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(include))
+            .find(|path| path.is_file())
+    }
+}
+
+/// Splits `# keyword args` into `(keyword, args)`, or `("", "")` for a line
+/// that isn't a directive.
+fn directive(line: &str) -> (&str, &str) {
+    let Some(rest) = line.trim_start().strip_prefix('#') else {
+        return ("", "");
+    };
+    let rest = rest.trim();
+    let (keyword, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    (keyword, args.trim())
+}
+
+fn read(path: &Path) -> Result<String, ShaderError> {
+    fs::read_to_string(path).map_err(|source| ShaderError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
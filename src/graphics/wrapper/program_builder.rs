@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+
+use super::preprocessor::{Defines, Preprocessor};
+use super::shader_error::{ShaderError, ShaderStage};
+use super::shader_program::{ShaderProgram, StageSource};
+
+/// Collects the stages of a `ShaderProgram`, any combination of files and
+/// embedded sources. Programs with at least one file are watched like the
+/// ones from `ShaderProgram::new`, unless they are separable.
+///
+/// ```no_run
+/// # use doom_engine::graphics::wrapper::*;
+/// # fn main() -> Result<(), ShaderError> {
+/// let normals = ShaderProgram::builder()
+///     .vertex("resources/shaders/normals.vert")
+///     .geometry("resources/shaders/normals.geom")
+///     .fragment("resources/shaders/basic.frag")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ShaderProgramBuilder {
+    stages: Vec<(ShaderStage, StageSource)>,
+    preprocessor: Preprocessor,
+    defines: Defines,
+    separable: bool,
+}
+
+impl ShaderProgramBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a stage loaded from `path`. Each stage can be given once.
+    pub fn stage<P: Into<PathBuf>>(self, stage: ShaderStage, path: P) -> Self {
+        self.with_stage(stage, StageSource::File(path.into()))
+    }
+
+    /// Adds a stage from an embedded source. Each stage can be given once.
+    pub fn stage_source(self, stage: ShaderStage, src: &str) -> Self {
+        self.with_stage(stage, StageSource::Str(src.to_string()))
+    }
+
+    pub fn vertex<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stage(ShaderStage::Vertex, path)
+    }
+
+    pub fn tess_control<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stage(ShaderStage::TessControl, path)
+    }
+
+    pub fn tess_evaluation<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stage(ShaderStage::TessEvaluation, path)
+    }
+
+    pub fn geometry<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stage(ShaderStage::Geometry, path)
+    }
+
+    pub fn fragment<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stage(ShaderStage::Fragment, path)
+    }
+
+    pub fn preprocessor(mut self, preprocessor: Preprocessor) -> Self {
+        self.preprocessor = preprocessor;
+        self
+    }
+
+    /// Defines injected into every stage.
+    pub fn defines(mut self, defines: Defines) -> Self {
+        self.defines = defines;
+        self
+    }
+
+    /// Links with `GL_PROGRAM_SEPARABLE`, so the program can supply some of
+    /// the stages of a `ProgramPipeline`. Separable programs aren't hot
+    /// reloaded, as the pipelines using them would keep the old program.
+    pub fn separable(mut self, separable: bool) -> Self {
+        self.separable = separable;
+        self
+    }
+
+    pub fn build(&self) -> Result<ShaderProgram, ShaderError> {
+        if self.stages.is_empty() {
+            return Err(ShaderError::NoStages);
+        }
+        ShaderProgram::from_stages(
+            self.stages.clone(),
+            self.preprocessor.clone(),
+            self.defines.clone(),
+            self.separable,
+        )
+    }
+
+    fn with_stage(mut self, stage: ShaderStage, src: StageSource) -> Self {
+        assert!(
+            self.stages.iter().all(|(s, _)| *s != stage),
+            "The {} stage was given twice",
+            stage
+        );
+        self.stages.push((stage, src));
+        self
+    }
+}
@@ -0,0 +1,98 @@
+use gl::types::*;
+
+use super::shader_error::ShaderStage;
+use super::shader_program::ShaderProgram;
+
+// Program Pipeline Object
+//
+// Mixes the stages of separable programs, e.g. one vertex program with
+// several fragment programs. The pipeline stores program names, which is why
+// separable programs aren't hot reloaded.
+pub struct ProgramPipeline {
+    id: GLuint,
+}
+
+impl ProgramPipeline {
+    pub fn new() -> ProgramPipeline {
+        let mut id = 0;
+        unsafe {
+            gl::CreateProgramPipelines(1, &mut id);
+        }
+        ProgramPipeline { id }
+    }
+
+    /// Uses `program` for `stages`. The program must have been built with
+    /// `separable(true)` and contain those stages.
+    pub fn use_stages(&mut self, stages: &[ShaderStage], program: &ShaderProgram) {
+        let mut separable = 0;
+        unsafe {
+            gl::GetProgramiv(program.id(), gl::PROGRAM_SEPARABLE, &mut separable);
+        }
+        assert!(separable != 0, "Pipeline stages need a separable program");
+
+        let bits = stages.iter().fold(0, |bits, stage| bits | stage.bit());
+        unsafe {
+            gl::UseProgramStages(self.id, bits, program.id());
+        }
+    }
+
+    /// Removes the program from `stages`.
+    pub fn clear_stages(&mut self, stages: &[ShaderStage]) {
+        let bits = stages.iter().fold(0, |bits, stage| bits | stage.bit());
+        unsafe {
+            gl::UseProgramStages(self.id, bits, 0);
+        }
+    }
+
+    /// Makes raw `glUniform*` calls go to `program` while the pipeline is
+    /// bound. `ShaderProgram::set_uniform` doesn't need it.
+    pub fn set_active_program(&mut self, program: &ShaderProgram) {
+        unsafe {
+            gl::ActiveShaderProgram(self.id, program.id());
+        }
+    }
+
+    /// Checks that the stages fit together, returning the info log if not.
+    pub fn validate(&self) -> Result<(), String> {
+        unsafe {
+            gl::ValidateProgramPipeline(self.id);
+            let mut status = 0;
+            gl::GetProgramPipelineiv(self.id, gl::VALIDATE_STATUS, &mut status);
+            if status != 0 {
+                return Ok(());
+            }
+
+            let mut len = 0;
+            gl::GetProgramPipelineiv(self.id, gl::INFO_LOG_LENGTH, &mut len);
+            let mut buf = vec![0u8; len.max(1) as usize];
+            let mut written = 0;
+            gl::GetProgramPipelineInfoLog(self.id, len, &mut written, buf.as_mut_ptr().cast());
+            buf.truncate(written as usize);
+            Err(String::from_utf8_lossy(&buf).into_owned())
+        }
+    }
+
+    /// Binds the pipeline. A program bound with `ShaderProgram::bind` takes
+    /// precedence, so unbind that first.
+    pub fn bind(&self) {
+        unsafe { gl::BindProgramPipeline(self.id) }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindProgramPipeline(0) }
+    }
+}
+
+impl Default for ProgramPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ProgramPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgramPipelines(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+
+use gl::types::*;
+
+use super::ubo::block_binding;
+
+/// An active uniform. Arrays are reported once, under their name without the
+/// `[0]` suffix.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UniformInfo {
+    name: String,
+    location: GLint,
+    gl_type: GLenum,
+    size: usize,
+    block_index: Option<GLuint>,
+    unit: Option<GLuint>,
+}
+
+impl UniformInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// -1 for block members.
+    pub fn location(&self) -> GLint {
+        self.location
+    }
+
+    pub fn gl_type(&self) -> GLenum {
+        self.gl_type
+    }
+
+    /// Array length, 1 for non-arrays.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The uniform block it belongs to, if any.
+    pub fn block_index(&self) -> Option<GLuint> {
+        self.block_index
+    }
+
+    /// First texture unit of a sampler, see `Reflection::assign_units`.
+    /// Array elements take the following ones.
+    pub fn unit(&self) -> Option<GLuint> {
+        self.unit
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AttributeInfo {
+    name: String,
+    location: GLint,
+    gl_type: GLenum,
+    size: usize,
+}
+
+impl AttributeInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// -1 for built-ins like `gl_VertexID`.
+    pub fn location(&self) -> GLint {
+        self.location
+    }
+
+    pub fn gl_type(&self) -> GLenum {
+        self.gl_type
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BlockInfo {
+    name: String,
+    index: GLuint,
+    binding: GLuint,
+    data_size: usize,
+}
+
+impl BlockInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn index(&self) -> GLuint {
+        self.index
+    }
+
+    pub fn binding(&self) -> GLuint {
+        self.binding
+    }
+
+    /// Size of the block's buffer storage in bytes.
+    pub fn data_size(&self) -> usize {
+        self.data_size
+    }
+}
+
+/// Active uniforms, attributes and uniform blocks of a linked program.
+#[derive(Clone, Debug, Default)]
+pub struct Reflection {
+    uniforms: HashMap<String, UniformInfo>,
+    attributes: Vec<AttributeInfo>,
+    blocks: Vec<BlockInfo>,
+}
+
+impl Reflection {
+    pub(crate) fn new(program: GLuint) -> Self {
+        unsafe {
+            let uniforms = active(program, gl::ACTIVE_UNIFORMS, gl::ACTIVE_UNIFORM_MAX_LENGTH)
+                .into_iter()
+                .map(|(index, name, gl_type, size)| {
+                    let mut block_index = -1;
+                    gl::GetActiveUniformsiv(
+                        program,
+                        1,
+                        &index,
+                        gl::UNIFORM_BLOCK_INDEX,
+                        &mut block_index,
+                    );
+                    let info = UniformInfo {
+                        location: location(&name, |c| gl::GetUniformLocation(program, c)),
+                        name: name.strip_suffix("[0]").unwrap_or(&name).to_string(),
+                        gl_type,
+                        size,
+                        block_index: (block_index >= 0).then_some(block_index as GLuint),
+                        unit: None,
+                    };
+                    (info.name.clone(), info)
+                })
+                .collect();
+
+            let mut attributes: Vec<_> = active(
+                program,
+                gl::ACTIVE_ATTRIBUTES,
+                gl::ACTIVE_ATTRIBUTE_MAX_LENGTH,
+            )
+            .into_iter()
+            .map(|(_, name, gl_type, size)| AttributeInfo {
+                location: location(&name, |c| gl::GetAttribLocation(program, c)),
+                name,
+                gl_type,
+                size,
+            })
+            .collect();
+            attributes.sort_by_key(|attribute| attribute.location);
+
+            let mut count = 0;
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_BLOCKS, &mut count);
+            let blocks = (0..count as GLuint)
+                .map(|index| {
+                    let param = |pname| {
+                        let mut value = 0;
+                        gl::GetActiveUniformBlockiv(program, index, pname, &mut value);
+                        value
+                    };
+                    let mut buf = vec![0u8; param(gl::UNIFORM_BLOCK_NAME_LENGTH).max(1) as usize];
+                    let mut len = 0;
+                    gl::GetActiveUniformBlockName(
+                        program,
+                        index,
+                        buf.len() as GLsizei,
+                        &mut len,
+                        buf.as_mut_ptr().cast(),
+                    );
+                    buf.truncate(len as usize);
+                    BlockInfo {
+                        name: String::from_utf8_lossy(&buf).into_owned(),
+                        index,
+                        binding: param(gl::UNIFORM_BLOCK_BINDING) as GLuint,
+                        data_size: param(gl::UNIFORM_BLOCK_DATA_SIZE) as usize,
+                    }
+                })
+                .collect();
+
+            Reflection {
+                uniforms,
+                attributes,
+                blocks,
+            }
+        }
+    }
+
+    /// Binds every block to its shared binding point, see `block_binding`.
+    pub(crate) fn bind_blocks(&mut self, program: GLuint) {
+        for block in &mut self.blocks {
+            block.binding = block_binding(&block.name);
+            unsafe { gl::UniformBlockBinding(program, block.index, block.binding) };
+        }
+    }
+
+    /// Gives every sampler its own texture units, in location order, and
+    /// points the sampler uniforms at them. Textures for different samplers
+    /// of the program then never share a unit.
+    ///
+    /// A sampler with an explicit `layout(binding = N)` keeps units `N` on,
+    /// and the others are placed around them. As a binding of 0 can't be told
+    /// from none, such samplers are reassigned like the rest. Image uniforms
+    /// keep the image unit the shader gives them.
+    pub(crate) fn assign_units(&mut self, program: GLuint) {
+        let mut samplers: Vec<_> = self
+            .uniforms
+            .values_mut()
+            .filter(|info| is_sampler(info.gl_type) && info.location >= 0)
+            .collect();
+        samplers.sort_by_key(|info| info.location);
+
+        let mut explicit = Vec::new();
+        samplers.retain_mut(|info| {
+            let mut binding = 0;
+            unsafe { gl::GetUniformiv(program, info.location, &mut binding) };
+            if binding <= 0 {
+                return true;
+            }
+            info.unit = Some(binding as GLuint);
+            explicit.push(binding..binding + info.size as GLint);
+            false
+        });
+
+        let mut next = 0;
+        for info in samplers {
+            let size = info.size as GLint;
+            while let Some(taken) = explicit
+                .iter()
+                .find(|taken| taken.start < next + size && next < taken.end)
+            {
+                next = taken.end;
+            }
+            let units: Vec<GLint> = (next..next + size).collect();
+            unsafe {
+                gl::ProgramUniform1iv(
+                    program,
+                    info.location,
+                    units.len() as GLsizei,
+                    units.as_ptr(),
+                );
+            }
+            info.unit = Some(next as GLuint);
+            next += size;
+        }
+
+        let used = explicit
+            .iter()
+            .map(|taken| taken.end)
+            .fold(next, GLint::max);
+        let mut max = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_COMBINED_TEXTURE_IMAGE_UNITS, &mut max) };
+        if used > max {
+            eprintln!(
+                "Program {} has samplers for {} texture units, only {} are available",
+                program, used, max
+            );
+        }
+    }
+
+    /// Looks `name` up, also accepting an element of an array like
+    /// `lights[2]`. Returns the uniform and the index of the element.
+    pub fn uniform(&self, name: &str) -> Option<(&UniformInfo, usize)> {
+        if let Some(info) = self.uniforms.get(name) {
+            return Some((info, 0));
+        }
+        let (base, index) = name.strip_suffix(']')?.rsplit_once('[')?;
+        let index = index.parse().ok()?;
+        let info = self.uniforms.get(base)?;
+        (index < info.size).then_some((info, index))
+    }
+
+    pub fn uniforms(&self) -> impl Iterator<Item = &UniformInfo> {
+        self.uniforms.values()
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&AttributeInfo> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Sorted by location.
+    pub fn attributes(&self) -> &[AttributeInfo] {
+        &self.attributes
+    }
+
+    pub fn block(&self, name: &str) -> Option<&BlockInfo> {
+        self.blocks.iter().find(|block| block.name == name)
+    }
+
+    pub fn blocks(&self) -> &[BlockInfo] {
+        &self.blocks
+    }
+}
+
+/// GLSL name of a uniform or attribute type, for messages.
+pub fn type_name(gl_type: GLenum) -> String {
+    match gl_type {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::BOOL => "bool",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+        _ => return format!("0x{:04X}", gl_type),
+    }
+    .to_string()
+}
+
+/// Whether `gl_type` is a sampler type, i.e. set with a texture unit.
+pub fn is_sampler(gl_type: GLenum) -> bool {
+    matches!(
+        gl_type,
+        gl::SAMPLER_1D
+            | gl::SAMPLER_2D
+            | gl::SAMPLER_3D
+            | gl::SAMPLER_CUBE
+            | gl::SAMPLER_1D_SHADOW
+            | gl::SAMPLER_2D_SHADOW
+            | gl::SAMPLER_1D_ARRAY
+            | gl::SAMPLER_2D_ARRAY
+            | gl::SAMPLER_2D_ARRAY_SHADOW
+            | gl::SAMPLER_CUBE_SHADOW
+            | gl::SAMPLER_CUBE_MAP_ARRAY
+            | gl::SAMPLER_2D_MULTISAMPLE
+            | gl::SAMPLER_2D_RECT
+            | gl::SAMPLER_BUFFER
+            | gl::INT_SAMPLER_2D
+            | gl::INT_SAMPLER_3D
+            | gl::INT_SAMPLER_2D_ARRAY
+            | gl::UNSIGNED_INT_SAMPLER_2D
+            | gl::UNSIGNED_INT_SAMPLER_3D
+            | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY
+    )
+}
+
+/// Whether `gl_type` is an image type, i.e. set with an image unit for
+/// `glBindImageTexture`.
+pub fn is_image(gl_type: GLenum) -> bool {
+    matches!(
+        gl_type,
+        gl::IMAGE_1D
+            | gl::IMAGE_2D
+            | gl::IMAGE_3D
+            | gl::IMAGE_CUBE
+            | gl::IMAGE_BUFFER
+            | gl::IMAGE_2D_ARRAY
+            | gl::INT_IMAGE_2D
+            | gl::INT_IMAGE_3D
+            | gl::UNSIGNED_INT_IMAGE_2D
+            | gl::UNSIGNED_INT_IMAGE_3D
+    )
+}
+
+/// `(index, name, type, size)` of the active uniforms or attributes.
+unsafe fn active(
+    program: GLuint,
+    count_pname: GLenum,
+    length_pname: GLenum,
+) -> Vec<(GLuint, String, GLenum, usize)> {
+    let (mut count, mut max_len) = (0, 0);
+    gl::GetProgramiv(program, count_pname, &mut count);
+    gl::GetProgramiv(program, length_pname, &mut max_len);
+
+    (0..count as GLuint)
+        .map(|index| {
+            let mut buf = vec![0u8; max_len.max(1) as usize];
+            let (mut len, mut size, mut gl_type) = (0, 0, 0);
+            let get = if count_pname == gl::ACTIVE_UNIFORMS {
+                gl::GetActiveUniform
+            } else {
+                gl::GetActiveAttrib
+            };
+            get(
+                program,
+                index,
+                buf.len() as GLsizei,
+                &mut len,
+                &mut size,
+                &mut gl_type,
+                buf.as_mut_ptr().cast(),
+            );
+            buf.truncate(len as usize);
+            let name = String::from_utf8_lossy(&buf).into_owned();
+            (index, name, gl_type, size as usize)
+        })
+        .collect()
+}
+
+fn location(name: &str, get: impl Fn(*const GLchar) -> GLint) -> GLint {
+    let c_str = std::ffi::CString::new(name).unwrap();
+    get(c_str.as_ptr())
+}
@@ -0,0 +1,175 @@
+use std::{error::Error, fmt, io, path::PathBuf};
+
+use gl::types::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShaderStage {
+    Vertex,
+    TessControl,
+    TessEvaluation,
+    Geometry,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    pub fn gl_enum(&self) -> GLenum {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::TessControl => gl::TESS_CONTROL_SHADER,
+            ShaderStage::TessEvaluation => gl::TESS_EVALUATION_SHADER,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+            ShaderStage::Compute => gl::COMPUTE_SHADER,
+        }
+    }
+
+    /// The stage's bit for `glUseProgramStages`.
+    pub fn bit(&self) -> GLbitfield {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER_BIT,
+            ShaderStage::TessControl => gl::TESS_CONTROL_SHADER_BIT,
+            ShaderStage::TessEvaluation => gl::TESS_EVALUATION_SHADER_BIT,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER_BIT,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER_BIT,
+            ShaderStage::Compute => gl::COMPUTE_SHADER_BIT,
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::TessControl => "tessellation control",
+            ShaderStage::TessEvaluation => "tessellation evaluation",
+            ShaderStage::Geometry => "geometry",
+            ShaderStage::Fragment => "fragment",
+            ShaderStage::Compute => "compute",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    /// The shader file couldn't be read.
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A bad `#include`, `file` being the including file.
+    Preprocess {
+        file: String,
+        line: u32,
+        message: String,
+    },
+    /// The source contains a nul byte and can't be passed to the driver.
+    InvalidSource {
+        stage: ShaderStage,
+        path: Option<PathBuf>,
+    },
+    /// The driver info log, with locations rewritten as `path:line` of the
+    /// original, unpreprocessed files.
+    Compile {
+        stage: ShaderStage,
+        path: Option<PathBuf>,
+        log: String,
+    },
+    Link {
+        log: String,
+    },
+    /// A `ShaderProgramBuilder` was built without any stage.
+    NoStages,
+}
+
+impl ShaderError {
+    /// The driver info log, empty for I/O errors.
+    pub fn log(&self) -> &str {
+        match self {
+            ShaderError::Compile { log, .. } | ShaderError::Link { log } => log,
+            _ => "",
+        }
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let describe = |stage: &ShaderStage, path: &Option<PathBuf>| match path {
+            Some(path) => format!("{} shader {}", stage, path.display()),
+            None => format!("{} shader", stage),
+        };
+        match self {
+            ShaderError::Io { path, source } => {
+                write!(f, "Failed to read shader {}: {}", path.display(), source)
+            }
+            ShaderError::Preprocess {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            ShaderError::InvalidSource { stage, path } => {
+                write!(f, "Nul byte in {}", describe(stage, path))
+            }
+            ShaderError::Compile { stage, path, log } => {
+                write!(f, "Failed to compile {}:\n{}", describe(stage, path), log)
+            }
+            ShaderError::Link { log } => write!(f, "Failed to link shader program:\n{}", log),
+            ShaderError::NoStages => write!(f, "Shader program without stages"),
+        }
+    }
+}
+
+impl Error for ShaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ShaderError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Rewrites the locations in a driver info log using `locate(source, line)`.
+/// Understands `0:12(5): error` (Mesa), `0(12) : error` (NVIDIA) and
+/// `ERROR: 0:12: ` (AMD, Intel). Lines `locate` returns `None` for are kept
+/// as they are.
+pub fn map_log(log: &str, locate: impl Fn(u32, u32) -> Option<String>) -> String {
+    log.lines()
+        .map(|line| {
+            parse_location(line)
+                .and_then(|(prefix, source, line_no, rest)| {
+                    locate(source, line_no).map(|loc| format!("{}{}{}", prefix, loc, rest))
+                })
+                .unwrap_or_else(|| line.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a log line into `(prefix, source, line, rest)`.
+fn parse_location(line: &str) -> Option<(&str, u32, u32, &str)> {
+    let start = ["ERROR: ", "WARNING: "]
+        .iter()
+        .find(|p| line.starts_with(*p))
+        .map_or(0, |p| p.len());
+    let (prefix, rest) = line.split_at(start);
+
+    let number = |s: &str| -> Option<(u32, usize)> {
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        Some((s[..end].parse().ok()?, end))
+    };
+
+    let (source, end) = number(rest)?;
+    let rest = &rest[end..];
+    let (line_no, rest) = match rest.strip_prefix(':') {
+        Some(rest) => {
+            let (line_no, end) = number(rest)?;
+            (line_no, &rest[end..])
+        }
+        None => {
+            let rest = rest.strip_prefix('(')?;
+            let (line_no, end) = number(rest)?;
+            (line_no, rest[end..].strip_prefix(')')?)
+        }
+    };
+    Some((prefix, source, line_no, rest))
+}
@@ -1,141 +1,606 @@
-use std::{collections::HashMap, ffi::CString, fs::File, io::Read, ptr};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    ffi::CString,
+    fs,
+    path::{Path, PathBuf},
+    ptr,
+    rc::{Rc, Weak},
+    time::SystemTime,
+};
 
 use gl::types::*;
 
-use crate::maths::{Matrix, Vector};
+use crate::maths::{Mat3, Mat4, Vec3, Vec4};
 
+use super::bindless::{bindless_available, set_handle_uniform};
+use super::preprocessor::{Defines, Preprocessor, Source};
+use super::program_builder::ShaderProgramBuilder;
+use super::reflection::{type_name, Reflection};
+use super::shader_error::{map_log, ShaderError, ShaderStage};
+use super::state;
+use super::std140::Std140;
 use super::texture::Texture2D;
+use super::texture_set::{TextureConflict, TextureSet};
+use super::uniform::UniformValue;
+
+thread_local! {
+    /// Programs created from files, checked by `ShaderProgram::reload_changed`.
+    static WATCHED: RefCell<Vec<Weak<Watch>>> = const { RefCell::new(Vec::new()) };
+}
 
 pub struct ShaderProgram {
     id: GLuint,
-    location_cache: HashMap<String, GLint>,
+    reflection: Reflection,
+    /// Uniforms already warned about, so each problem is logged once.
+    warned: HashSet<String>,
+    /// Samplers last set to a bindless handle rather than their unit.
+    bindless_samplers: HashSet<String>,
+    watch: Option<Rc<Watch>>,
 }
 
 impl ShaderProgram {
-    pub fn new(vertex_shader_path: &str, fragment_shader_path: &str) -> ShaderProgram {
-        let mut vertex_shader_file =
-            File::open(vertex_shader_path).expect("Failed to open vertex shader file");
-        let mut fragment_shader_file =
-            File::open(fragment_shader_path).expect("Failed to open fragment shader file");
-
-        let mut vertex_shader_src = String::new();
-        let mut fragment_shader_src = String::new();
-
-        vertex_shader_file
-            .read_to_string(&mut vertex_shader_src)
-            .expect("Failed to read vertex shader");
-        fragment_shader_file
-            .read_to_string(&mut fragment_shader_src)
-            .expect("Failed to read fragment shader");
+    /// The program is watched for changes, see `reload_changed`. For other
+    /// stage combinations see `builder`.
+    pub fn new(
+        vertex_shader_path: &str,
+        fragment_shader_path: &str,
+    ) -> Result<ShaderProgram, ShaderError> {
+        Self::builder()
+            .vertex(vertex_shader_path)
+            .fragment(fragment_shader_path)
+            .build()
+    }
 
-        unsafe {
-            let vertex_shader = gl::CreateShader(gl::VERTEX_SHADER);
-            let c_str_vert = CString::new(vertex_shader_src.as_bytes()).unwrap();
-            gl::ShaderSource(vertex_shader, 1, &c_str_vert.as_ptr(), ptr::null());
-            gl::CompileShader(vertex_shader);
-
-            let fragment_shader = gl::CreateShader(gl::FRAGMENT_SHADER);
-            let c_str_frag = CString::new(fragment_shader_src.as_bytes()).unwrap();
-            gl::ShaderSource(fragment_shader, 1, &c_str_frag.as_ptr(), ptr::null());
-            gl::CompileShader(fragment_shader);
-
-            let id = gl::CreateProgram();
-            gl::AttachShader(id, vertex_shader);
-            gl::AttachShader(id, fragment_shader);
-            gl::LinkProgram(id);
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
-
-            ShaderProgram {
-                id,
-                location_cache: HashMap::new(),
-            }
-        }
+    /// `new`, with `#include`s resolved by `preprocessor` and `defines`
+    /// injected into both stages.
+    pub fn with_defines(
+        vertex_shader_path: &str,
+        fragment_shader_path: &str,
+        preprocessor: &Preprocessor,
+        defines: &Defines,
+    ) -> Result<ShaderProgram, ShaderError> {
+        Self::builder()
+            .vertex(vertex_shader_path)
+            .fragment(fragment_shader_path)
+            .preprocessor(preprocessor.clone())
+            .defines(defines.clone())
+            .build()
     }
 
-    pub fn bind(&self) {
-        unsafe {
-            gl::UseProgram(self.id);
+    /// For shaders embedded in the binary, e.g. with `include_str!`.
+    pub fn from_sources(
+        vertex_shader_src: &str,
+        fragment_shader_src: &str,
+    ) -> Result<ShaderProgram, ShaderError> {
+        Self::builder()
+            .stage_source(ShaderStage::Vertex, vertex_shader_src)
+            .stage_source(ShaderStage::Fragment, fragment_shader_src)
+            .build()
+    }
+
+    pub fn builder() -> ShaderProgramBuilder {
+        ShaderProgramBuilder::new()
+    }
+
+    /// Builds the stages, watching the program if any of them is a file and
+    /// it isn't separable.
+    pub(crate) fn from_stages(
+        stages: Vec<(ShaderStage, StageSource)>,
+        preprocessor: Preprocessor,
+        defines: Defines,
+        separable: bool,
+    ) -> Result<ShaderProgram, ShaderError> {
+        let watch = Watch {
+            stages,
+            preprocessor,
+            defines,
+            separable,
+            modified: RefCell::new(Vec::new()),
+            replacement: Cell::new(None),
+        };
+        let id = watch.build()?;
+
+        let has_files = watch
+            .stages
+            .iter()
+            .any(|(_, src)| matches!(src, StageSource::File(_)));
+        if !has_files || separable {
+            return Ok(ShaderProgram::from_id(id, None));
         }
+        let watch = Rc::new(watch);
+        WATCHED.with(|watched| watched.borrow_mut().push(Rc::downgrade(&watch)));
+        Ok(ShaderProgram::from_id(id, Some(watch)))
     }
 
-    pub fn unbind(&self) {
-        unsafe {
-            gl::UseProgram(0);
+    fn from_id(id: GLuint, watch: Option<Rc<Watch>>) -> ShaderProgram {
+        ShaderProgram {
+            id,
+            reflection: reflect(id),
+            warned: HashSet::new(),
+            bindless_samplers: HashSet::new(),
+            watch,
         }
     }
 
-    fn get_location(&mut self, name: &str) -> GLint {
-        match self.location_cache.get(&name.to_string()) {
-            Some(id) => *id,
-            None => {
-                let name_cstring = CString::new(name).unwrap();
-                let loc = unsafe { gl::GetUniformLocation(self.id, name_cstring.as_ptr()) };
-                if loc < 0 {
-                    panic!("Cannot locate uniform: {}", name);
-                } else {
-                    self.location_cache.insert(name.to_string(), loc);
+    /// Recompiles every live program whose files changed since the last
+    /// call. Failures are logged, returned, and leave the old program in
+    /// place; on success the new program is swapped in by the next `bind`,
+    /// `apply_reload` or uniform write. Called once per frame by
+    /// `Window::update`.
+    pub fn reload_changed() -> Vec<ShaderError> {
+        let mut errors = Vec::new();
+        WATCHED.with(|watched| {
+            let mut watched = watched.borrow_mut();
+            watched.retain(|watch| watch.strong_count() > 0);
+            for watch in watched.iter().filter_map(Weak::upgrade) {
+                if !watch.changed() {
+                    continue;
+                }
+                match watch.build() {
+                    Ok(id) => {
+                        if let Some(stale) = watch.replacement.replace(Some(id)) {
+                            unsafe { gl::DeleteProgram(stale) };
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        errors.push(err);
+                    }
                 }
-                loc
             }
-        }
+        });
+        errors
     }
 
-    pub fn uniform_3fv(&mut self, name: &str, v: &Vector) {
-        assert_eq!(v.len(), 3);
-        unsafe {
-            gl::Uniform3fv(self.get_location(name), 1, v.as_ptr());
+    pub fn bind(&mut self) {
+        self.apply_reload();
+        state::use_program(self.id);
+    }
+
+    /// Swaps in the program rebuilt by `reload_changed`, if there is one,
+    /// without binding it. Returns whether it did. Uniforms have their
+    /// default values in the new program.
+    pub fn apply_reload(&mut self) -> bool {
+        let Some(id) = self
+            .watch
+            .as_ref()
+            .and_then(|watch| watch.replacement.take())
+        else {
+            return false;
+        };
+        unsafe { gl::DeleteProgram(self.id) };
+        state::deleted_program(self.id);
+        self.id = id;
+        self.reflection = reflect(id);
+        self.warned.clear();
+        self.bindless_samplers.clear();
+        true
+    }
+
+    pub fn unbind(&self) {
+        state::use_program(0);
+    }
+
+    /// The GL program name. It changes when a reload is swapped in, so it
+    /// is stale after `reload_changed` until the next `bind` or
+    /// `apply_reload`.
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Active uniforms, attributes and blocks. Like `id`, stale after
+    /// `reload_changed` until the next `bind` or `apply_reload`.
+    pub fn reflection(&self) -> &Reflection {
+        &self.reflection
+    }
+
+    /// Whether the active uniform block `name` has the size of `T`'s std140
+    /// layout, i.e. whether a `UBO<T>` fits it.
+    pub fn block_matches<T: Std140>(&self, name: &str) -> bool {
+        self.reflection
+            .block(name)
+            .is_some_and(|block| block.data_size() == T::SIZE)
+    }
+
+    /// Sets a uniform of the program, bound or not. Uniforms the driver optimized
+    /// out and values that don't match the reflected type are skipped with a
+    /// warning, logged once per uniform.
+    pub fn set_uniform<T: UniformValue + ?Sized>(&mut self, name: &str, value: &T) {
+        self.apply_reload();
+        if let Some(loc) = self.get_location(name, value) {
+            value.upload(self.id, loc);
         }
     }
 
+    /// The location of `name` from the reflection, `None` if the value
+    /// can't be written to it.
+    fn get_location<T: UniformValue + ?Sized>(&mut self, name: &str, value: &T) -> Option<GLint> {
+        let location = match self.reflection.uniform(name) {
+            None => Err("is not active".to_string()),
+            Some((info, _)) if info.block_index().is_some() => {
+                Err("is in a uniform block".to_string())
+            }
+            Some((info, _)) if !value.accepts(info.gl_type()) => Err(format!(
+                "is a {}, which the value can't be assigned to",
+                type_name(info.gl_type())
+            )),
+            Some((info, index)) if index + value.count() > info.size() => Err(format!(
+                "has {} elements, {} written from {}",
+                info.size(),
+                value.count(),
+                index
+            )),
+            // Elements of an array of basic types have consecutive locations
+            Some((info, index)) => Ok(info.location() + index as GLint),
+        };
+        location
+            .map_err(|problem| {
+                if self.warned.insert(name.to_string()) {
+                    eprintln!("Uniform {} {}, ignoring it", name, problem);
+                }
+            })
+            .ok()
+    }
+
+    pub fn uniform_3fv<V: Into<Vec3>>(&mut self, name: &str, v: V) {
+        self.set_uniform(name, &v.into());
+    }
+
     pub fn uniform_4f(&mut self, name: &str, v1: f32, v2: f32, v3: f32, v4: f32) {
-        unsafe {
-            gl::Uniform4f(self.get_location(name), v1, v2, v3, v4);
-        }
+        self.set_uniform(name, &Vec4::from([v1, v2, v3, v4]));
     }
 
-    pub fn uniform_4fv(&mut self, name: &str, v: &Vector) {
-        assert_eq!(v.len(), 4);
-        unsafe {
-            gl::Uniform4fv(self.get_location(name), 1, v.as_ptr());
+    pub fn uniform_4fv<V: Into<Vec4>>(&mut self, name: &str, v: V) {
+        self.set_uniform(name, &v.into());
+    }
+
+    pub fn uniform_matrix_4fv<M: Into<Mat4>>(&mut self, name: &str, m: M) {
+        self.set_uniform(name, &m.into());
+    }
+
+    pub fn uniform_matrix_3fv<M: Into<Mat3>>(&mut self, name: &str, m: M) {
+        self.set_uniform(name, &m.into());
+    }
+
+    pub fn uniform_2dtex(&mut self, name: &str, tex: &Texture2D) {
+        self.set_texture(name, tex);
+    }
+
+    /// Binds `texture` to the unit of sampler `name`, which also accepts an
+    /// array element like `shadow_maps[1]`. Every sampler gets its own units
+    /// when the program is linked, so textures set for different samplers
+    /// don't replace each other.
+    pub fn set_texture(&mut self, name: &str, texture: &Texture2D) {
+        if let Some((location, unit)) = self.sampler_unit(name) {
+            self.bind_sampler(name, location, unit, texture, false);
         }
     }
 
-    pub fn uniform_matrix_4fv(&mut self, name: &str, m: &Matrix) {
-        assert!(m.is_square());
-        assert_eq!(m.rows(), 4);
-        unsafe {
-            //Matrix is a row-major matrix type, therefore we need to use
-            //transpose: gl::TRUE since OpenGL uses column-major matrices
-            gl::UniformMatrix4fv(self.get_location(name), 1, gl::TRUE, m.as_ptr());
+    /// Binds every texture of the set, with bindless handles if the set asks
+    /// for them and the driver has them. Nothing is bound if two textures
+    /// would end up on the same unit.
+    pub fn bind_textures(&mut self, textures: &TextureSet) -> Result<(), TextureConflict> {
+        let mut samplers: Vec<(&str, GLint, GLuint, &Texture2D)> = Vec::new();
+        for (name, texture) in textures.iter() {
+            let Some((location, unit)) = self.sampler_unit(name) else {
+                continue;
+            };
+            if let Some((other, .., bound)) = samplers
+                .iter()
+                .find(|(_, _, u, bound)| *u == unit && bound.id() != texture.id())
+            {
+                return Err(TextureConflict::new(
+                    other,
+                    name,
+                    unit,
+                    (bound.id(), texture.id()),
+                ));
+            }
+            samplers.push((name, location, unit, texture));
+        }
+
+        let bindless = textures.is_bindless() && bindless_available();
+        for (name, location, unit, texture) in samplers {
+            self.bind_sampler(name, location, unit, texture, bindless);
         }
+        Ok(())
     }
 
-    pub fn uniform_matrix_3fv(&mut self, name: &str, m: &Matrix) {
-        assert!(m.is_square());
-        assert_eq!(m.rows(), 3);
-        unsafe {
-            //Matrix is a row-major matrix type, therefore we need to use
-            //transpose: gl::TRUE since OpenGL uses column-major matrices
-            gl::UniformMatrix3fv(self.get_location(name), 1, gl::TRUE, m.as_ptr());
+    /// The location and texture unit of sampler `name`.
+    fn sampler_unit(&mut self, name: &str) -> Option<(GLint, GLuint)> {
+        self.apply_reload();
+        let found = self.reflection.uniform(name).and_then(|(info, index)| {
+            Some((
+                info.location() + index as GLint,
+                info.unit()? + index as GLuint,
+            ))
+        });
+        if found.is_none() && self.warned.insert(name.to_string()) {
+            eprintln!("Sampler {} is not active, ignoring it", name);
         }
+        found
     }
 
-    pub fn uniform_2dtex(&mut self, name: &str, tex: &Texture2D) {
-        self.get_location(name);
-        unsafe {
-            //que se joda el que hizo opengl
-            gl::ActiveTexture(gl::TEXTURE0);
-            tex.bind();
+    fn bind_sampler(
+        &mut self,
+        name: &str,
+        location: GLint,
+        unit: GLuint,
+        texture: &Texture2D,
+        bindless: bool,
+    ) {
+        // Only ask for a handle when it will be used, as making one resident
+        // freezes the texture's sampling parameters
+        if bindless {
+            if let Some(handle) = texture.bindless_handle() {
+                set_handle_uniform(self.id, location, handle);
+                self.bindless_samplers.insert(name.to_string());
+                return;
+            }
         }
+        // Point the sampler back at its unit after a handle
+        if self.bindless_samplers.remove(name) {
+            unsafe { gl::ProgramUniform1i(self.id, location, unit as GLint) };
+        }
+        texture.bind_unit(unit);
     }
 }
 
 impl Drop for ShaderProgram {
     fn drop(&mut self) {
-        self.unbind();
+        state::deleted_program(self.id);
         unsafe {
             gl::DeleteProgram(self.id);
+            if let Some(id) = self
+                .watch
+                .as_ref()
+                .and_then(|watch| watch.replacement.take())
+            {
+                gl::DeleteProgram(id);
+            }
         }
     }
 }
+
+/// Permutations of one shader pair, built on first use and cached by their
+/// define set.
+pub struct ShaderVariants {
+    vertex_shader_path: String,
+    fragment_shader_path: String,
+    preprocessor: Preprocessor,
+    programs: HashMap<Defines, ShaderProgram>,
+}
+
+impl ShaderVariants {
+    pub fn new(
+        vertex_shader_path: &str,
+        fragment_shader_path: &str,
+        preprocessor: Preprocessor,
+    ) -> ShaderVariants {
+        ShaderVariants {
+            vertex_shader_path: vertex_shader_path.to_string(),
+            fragment_shader_path: fragment_shader_path.to_string(),
+            preprocessor,
+            programs: HashMap::new(),
+        }
+    }
+
+    /// The program for `defines`, building it if it isn't cached yet.
+    pub fn get(&mut self, defines: &Defines) -> Result<&mut ShaderProgram, ShaderError> {
+        if !self.programs.contains_key(defines) {
+            let program = ShaderProgram::with_defines(
+                &self.vertex_shader_path,
+                &self.fragment_shader_path,
+                &self.preprocessor,
+                defines,
+            )?;
+            self.programs.insert(defines.clone(), program);
+        }
+        Ok(self.programs.get_mut(defines).unwrap())
+    }
+
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+}
+
+/// Where the source of a stage comes from.
+#[derive(Clone, Debug)]
+pub(crate) enum StageSource {
+    File(PathBuf),
+    Str(String),
+}
+
+/// How to rebuild a watched program and the last seen modification time of
+/// every file it read, includes too.
+struct Watch {
+    stages: Vec<(ShaderStage, StageSource)>,
+    preprocessor: Preprocessor,
+    defines: Defines,
+    separable: bool,
+    modified: RefCell<Vec<(PathBuf, Option<SystemTime>)>>,
+    /// Rebuilt program waiting to be swapped in.
+    replacement: Cell<Option<GLuint>>,
+}
+
+impl Watch {
+    /// Whether any file changed since the last call. A file that can't be
+    /// read, e.g. mid-save, counts as unchanged until it reappears.
+    fn changed(&self) -> bool {
+        let mut changed = false;
+        for (path, last) in self.modified.borrow_mut().iter_mut() {
+            let now = modified(path);
+            if now.is_some() && now != *last {
+                *last = now;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Preprocesses and links the stages, recording the files read, and
+    /// those looked for if preprocessing fails, so creating a missing
+    /// include triggers the next reload.
+    fn build(&self) -> Result<GLuint, ShaderError> {
+        let mut files = Vec::new();
+        let mut sources = Vec::new();
+        let mut failure = None;
+        for (stage, src) in &self.stages {
+            let (source, read) = match src {
+                StageSource::File(path) => self.preprocessor.process_tracked(path, &self.defines),
+                StageSource::Str(src) => {
+                    self.preprocessor
+                        .process_str_tracked(src, &stage.to_string(), &self.defines)
+                }
+            };
+            files.extend(read);
+            match source {
+                Ok(source) => sources.push(source),
+                Err(err) => {
+                    failure.get_or_insert(err);
+                }
+            }
+        }
+
+        files.sort();
+        files.dedup();
+        *self.modified.borrow_mut() = files
+            .into_iter()
+            .map(|path| {
+                let time = modified(&path);
+                (path, time)
+            })
+            .collect();
+        if let Some(err) = failure {
+            return Err(err);
+        }
+
+        let stages: Vec<_> = self
+            .stages
+            .iter()
+            .zip(&sources)
+            .map(|((stage, stage_src), src)| {
+                let path = match stage_src {
+                    StageSource::File(path) => Some(path.as_path()),
+                    StageSource::Str(_) => None,
+                };
+                (*stage, src, path)
+            })
+            .collect();
+        link(&stages, self.separable)
+    }
+}
+
+fn reflect(program: GLuint) -> Reflection {
+    let mut reflection = Reflection::new(program);
+    reflection.bind_blocks(program);
+    reflection.assign_units(program);
+    reflection
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Compiles and links the stages, cleaning up the GL objects on failure.
+fn link(
+    stages: &[(ShaderStage, &Source, Option<&Path>)],
+    separable: bool,
+) -> Result<GLuint, ShaderError> {
+    let mut shaders = Vec::new();
+    for &(stage, src, path) in stages {
+        match compile(stage, src, path) {
+            Ok(shader) => shaders.push(shader),
+            Err(err) => {
+                shaders
+                    .iter()
+                    .for_each(|&shader| unsafe { gl::DeleteShader(shader) });
+                return Err(err);
+            }
+        }
+    }
+
+    unsafe {
+        let id = gl::CreateProgram();
+        if separable {
+            gl::ProgramParameteri(id, gl::PROGRAM_SEPARABLE, gl::TRUE as GLint);
+        }
+        for &shader in &shaders {
+            gl::AttachShader(id, shader);
+        }
+        gl::LinkProgram(id);
+        for &shader in &shaders {
+            gl::DetachShader(id, shader);
+            gl::DeleteShader(shader);
+        }
+
+        let mut status = 0;
+        gl::GetProgramiv(id, gl::LINK_STATUS, &mut status);
+        if status == 0 {
+            let log = program_log(id);
+            gl::DeleteProgram(id);
+            return Err(ShaderError::Link { log });
+        }
+        Ok(id)
+    }
+}
+
+/// Compiles one stage, returning the info log on failure.
+fn compile(stage: ShaderStage, src: &Source, path: Option<&Path>) -> Result<GLuint, ShaderError> {
+    let path_buf = || path.map(Path::to_path_buf);
+    let c_str = CString::new(src.text()).map_err(|_| ShaderError::InvalidSource {
+        stage,
+        path: path_buf(),
+    })?;
+
+    unsafe {
+        let shader = gl::CreateShader(stage.gl_enum());
+        gl::ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null());
+        gl::CompileShader(shader);
+
+        let mut status = 0;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+        if status == 0 {
+            let log = shader_log(shader);
+            gl::DeleteShader(shader);
+            return Err(ShaderError::Compile {
+                stage,
+                path: path_buf(),
+                log: map_log(&log, |source, line| {
+                    let (name, line) = src.locate(line).filter(|_| source == 0)?;
+                    Some(format!("{}:{}", name, line))
+                }),
+            });
+        }
+        Ok(shader)
+    }
+}
+
+fn shader_log(shader: GLuint) -> String {
+    unsafe {
+        let mut len = 0;
+        gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+        let mut buf = vec![0u8; len.max(1) as usize];
+        let mut written = 0;
+        gl::GetShaderInfoLog(shader, len, &mut written, buf.as_mut_ptr().cast());
+        buf.truncate(written as usize);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+}
+
+fn program_log(program: GLuint) -> String {
+    unsafe {
+        let mut len = 0;
+        gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
+        let mut buf = vec![0u8; len.max(1) as usize];
+        let mut written = 0;
+        gl::GetProgramInfoLog(program, len, &mut written, buf.as_mut_ptr().cast());
+        buf.truncate(written as usize);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+}
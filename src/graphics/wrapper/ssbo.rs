@@ -0,0 +1,42 @@
+use std::ops::{Deref, DerefMut};
+
+use bytemuck::Pod;
+use gl::types::*;
+
+use super::buffer::{Buffer, BufferTarget};
+
+// Shader Storage Buffer Object
+//
+// `T` has to match the block's std430 layout, e.g. a `#[repr(C)]` struct
+// padded by hand where GLSL aligns a vec3 to 16 bytes. Derefs to `Buffer`
+// for updates, read-back and `bind_base`.
+pub struct SSBO<T: Pod> {
+    buffer: Buffer<T>,
+}
+
+impl<T: Pod> SSBO<T> {
+    pub fn new(usage: GLenum, data: &[T]) -> SSBO<T> {
+        SSBO {
+            buffer: Buffer::new(BufferTarget::ShaderStorage, usage, data),
+        }
+    }
+
+    /// `len` zeroed elements, e.g. for a compute shader to fill.
+    pub fn zeroed(usage: GLenum, len: usize) -> SSBO<T> {
+        Self::new(usage, &vec![T::zeroed(); len])
+    }
+}
+
+impl<T: Pod> Deref for SSBO<T> {
+    type Target = Buffer<T>;
+
+    fn deref(&self) -> &Buffer<T> {
+        &self.buffer
+    }
+}
+
+impl<T: Pod> DerefMut for SSBO<T> {
+    fn deref_mut(&mut self) -> &mut Buffer<T> {
+        &mut self.buffer
+    }
+}
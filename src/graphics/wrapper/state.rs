@@ -0,0 +1,120 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use gl::types::*;
+
+thread_local! {
+    /// What the wrappers last bound on this thread's context.
+    static STATE: RefCell<BoundState> = RefCell::new(BoundState::default());
+}
+
+/// Binds issued to GL and binds skipped because the object was already
+/// bound, since the last `reset_counters`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct StateCounters {
+    pub program_binds: u64,
+    pub program_skipped: u64,
+    pub vertex_array_binds: u64,
+    pub vertex_array_skipped: u64,
+    pub texture_binds: u64,
+    pub texture_skipped: u64,
+}
+
+/// `None` when unknown, so the next bind is always issued.
+#[derive(Default)]
+struct BoundState {
+    program: Option<GLuint>,
+    vertex_array: Option<GLuint>,
+    /// Texture of each unit.
+    textures: HashMap<GLuint, GLuint>,
+    counters: StateCounters,
+}
+
+pub(crate) fn use_program(id: GLuint) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.program == Some(id) {
+            state.counters.program_skipped += 1;
+            return;
+        }
+        state.program = Some(id);
+        state.counters.program_binds += 1;
+        unsafe { gl::UseProgram(id) }
+    })
+}
+
+pub(crate) fn bind_vertex_array(id: GLuint) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.vertex_array == Some(id) {
+            state.counters.vertex_array_skipped += 1;
+            return;
+        }
+        state.vertex_array = Some(id);
+        state.counters.vertex_array_binds += 1;
+        unsafe { gl::BindVertexArray(id) }
+    })
+}
+
+/// Binds `id` to texture `unit`, whatever its target.
+pub(crate) fn bind_texture(unit: GLuint, id: GLuint) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.textures.get(&unit) == Some(&id) {
+            state.counters.texture_skipped += 1;
+            return;
+        }
+        state.textures.insert(unit, id);
+        state.counters.texture_binds += 1;
+        unsafe { gl::BindTextureUnit(unit, id) }
+    })
+}
+
+/// A deleted program stays in use until another one is, but its name may be
+/// reused afterwards, so forget it.
+pub(crate) fn deleted_program(id: GLuint) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.program == Some(id) {
+            state.program = None;
+        }
+    })
+}
+
+/// Deleting a VAO unbinds it.
+pub(crate) fn deleted_vertex_array(id: GLuint) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.vertex_array == Some(id) {
+            state.vertex_array = Some(0);
+        }
+    })
+}
+
+/// Deleting a texture unbinds it from every unit.
+pub(crate) fn deleted_texture(id: GLuint) {
+    STATE.with(|state| {
+        state
+            .borrow_mut()
+            .textures
+            .retain(|_, texture| *texture != id)
+    })
+}
+
+/// Forgets what is bound, for after code outside the wrappers changed it,
+/// e.g. the UI renderer. Called by `Window::end_ui`.
+pub fn invalidate_state() {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.program = None;
+        state.vertex_array = None;
+        state.textures.clear();
+    })
+}
+
+pub fn state_counters() -> StateCounters {
+    STATE.with(|state| state.borrow().counters)
+}
+
+pub fn reset_state_counters() {
+    STATE.with(|state| state.borrow_mut().counters = StateCounters::default())
+}
@@ -0,0 +1,182 @@
+use crate::maths::{Mat2, Mat3, Mat4, MatrixN, Vec2, Vec3, Vec4};
+
+/// A type with a std140 representation, as used by `UBO`. Structs implement
+/// it through the `std140!` macro, which computes the member offsets from
+/// these constants, so the layout is checked at compile time rather than
+/// relying on `#[repr(C)]` and manual padding.
+pub trait Std140 {
+    /// Base alignment in bytes.
+    const ALIGN: usize;
+    /// Size in bytes. As an array element it's padded to a multiple of
+    /// `ALIGN` rounded up to 16.
+    const SIZE: usize;
+
+    /// Writes the `SIZE` bytes of the value into `out`.
+    fn write_std140(&self, out: &mut [u8]);
+
+    /// The value laid out in a buffer of `SIZE` bytes.
+    fn to_std140(&self) -> Vec<u8> {
+        let mut out = vec![0; Self::SIZE];
+        self.write_std140(&mut out);
+        out
+    }
+}
+
+/// `offset` rounded up to a multiple of `align`.
+pub const fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+/// Array elements and structs are aligned to at least a vec4.
+pub const fn align_to_vec4(align: usize) -> usize {
+    align_to(align, 16)
+}
+
+macro_rules! impl_std140_scalar {
+    ( $( $t:ty ),* ) => { $(
+        impl Std140 for $t {
+            const ALIGN: usize = 4;
+            const SIZE: usize = 4;
+
+            fn write_std140(&self, out: &mut [u8]) {
+                out[..4].copy_from_slice(&self.to_ne_bytes());
+            }
+        }
+    )* };
+}
+
+impl_std140_scalar!(f32, i32, u32);
+
+impl Std140 for bool {
+    const ALIGN: usize = 4;
+    const SIZE: usize = 4;
+
+    fn write_std140(&self, out: &mut [u8]) {
+        (*self as u32).write_std140(out);
+    }
+}
+
+macro_rules! impl_std140_vector {
+    ( $( $t:ty, $n:expr, $align:expr );* ) => { $(
+        impl Std140 for $t {
+            const ALIGN: usize = $align;
+            const SIZE: usize = 4 * $n;
+
+            fn write_std140(&self, out: &mut [u8]) {
+                for i in 0..$n {
+                    self[i].write_std140(&mut out[4 * i..]);
+                }
+            }
+        }
+    )* };
+}
+
+// vec3 is aligned like a vec4 but only 12 bytes, so a scalar can follow it
+impl_std140_vector!(Vec2, 2, 8; Vec3, 3, 16; Vec4, 4, 16);
+
+/// Matrices are stored as arrays of columns, so `MatrixN`'s rows are
+/// transposed on the way out and GLSL's default `column_major` applies.
+macro_rules! impl_std140_matrix {
+    ( $( $t:ty, $n:expr );* ) => { $(
+        impl Std140 for $t {
+            const ALIGN: usize = 16;
+            const SIZE: usize = 16 * $n;
+
+            fn write_std140(&self, out: &mut [u8]) {
+                write_columns(self, out);
+            }
+        }
+    )* };
+}
+
+impl_std140_matrix!(Mat2, 2; Mat3, 3; Mat4, 4);
+
+fn write_columns<const N: usize>(m: &MatrixN<N>, out: &mut [u8]) {
+    for j in 0..N {
+        for i in 0..N {
+            m[i][j].write_std140(&mut out[16 * j + 4 * i..]);
+        }
+    }
+}
+
+impl<T: Std140, const N: usize> Std140 for [T; N] {
+    const ALIGN: usize = align_to_vec4(T::ALIGN);
+    const SIZE: usize = N * align_to(T::SIZE, Self::ALIGN);
+
+    fn write_std140(&self, out: &mut [u8]) {
+        let stride = align_to(T::SIZE, Self::ALIGN);
+        for (i, element) in self.iter().enumerate() {
+            element.write_std140(&mut out[i * stride..]);
+        }
+    }
+}
+
+/// Declares a struct and implements `Std140` for it. Fields can be any
+/// `Std140` type, including arrays and other `std140!` structs.
+///
+/// ```
+/// use doom_engine::graphics::wrapper::Std140;
+/// use doom_engine::maths::{Mat4, Vec3};
+/// use doom_engine::std140;
+///
+/// std140! {
+///     pub struct CameraBlock {
+///         pub proj: Mat4,
+///         pub view: Mat4,
+///         pub view_pos: Vec3,
+///     }
+/// }
+///
+/// assert_eq!(CameraBlock::SIZE, 144);
+/// ```
+#[macro_export]
+macro_rules! std140 {
+    (
+        $( #[$meta:meta] )*
+        $vis:vis struct $name:ident {
+            $( $field_vis:vis $field:ident : $t:ty ),* $(,)?
+        }
+    ) => {
+        $( #[$meta] )*
+        $vis struct $name {
+            $( $field_vis $field: $t ),*
+        }
+
+        impl $crate::graphics::wrapper::Std140 for $name {
+            // Structs are aligned to at least a vec4
+            const ALIGN: usize = {
+                let mut align = 16;
+                $(
+                    let field = <$t as $crate::graphics::wrapper::Std140>::ALIGN;
+                    if field > align {
+                        align = field;
+                    }
+                )*
+                align
+            };
+            const SIZE: usize = {
+                let mut end = 0;
+                $(
+                    end = $crate::graphics::wrapper::align_to(
+                        end,
+                        <$t as $crate::graphics::wrapper::Std140>::ALIGN,
+                    ) + <$t as $crate::graphics::wrapper::Std140>::SIZE;
+                )*
+                $crate::graphics::wrapper::align_to(end, Self::ALIGN)
+            };
+
+            fn write_std140(&self, out: &mut [u8]) {
+                let mut end = 0;
+                $(
+                    let offset = $crate::graphics::wrapper::align_to(
+                        end,
+                        <$t as $crate::graphics::wrapper::Std140>::ALIGN,
+                    );
+                    $crate::graphics::wrapper::Std140::write_std140(&self.$field, &mut out[offset..]);
+                    end = offset + <$t as $crate::graphics::wrapper::Std140>::SIZE;
+                )*
+                let _ = end;
+            }
+        }
+    };
+}
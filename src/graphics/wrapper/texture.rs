@@ -1,73 +1,143 @@
+use std::{cell::Cell, mem};
+
+use bytemuck::Pod;
 use gl::types::*;
-use image::GenericImageView;
+
+use super::bindless;
+use super::state;
+use super::texture_builder::{PixelFormat, Texture2DBuilder, TextureError};
 
 #[derive(Debug)]
 pub struct Texture2D {
     id: GLuint,
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    levels: u32,
+    /// Bindless handle, made resident on first use.
+    handle: Cell<Option<GLuint64>>,
 }
 
 impl Texture2D {
-    pub fn new(img_path: &str) -> Texture2D {
-        let img = image::open(img_path).unwrap().flipv();
-        let (width, height) = img.dimensions();
-        let data = img.to_rgba8().into_raw();
+    /// An image file with the default options, see `builder` for others.
+    pub fn new(img_path: &str) -> Result<Texture2D, TextureError> {
+        Self::builder().path(img_path).build()
+    }
+
+    pub fn builder() -> Texture2DBuilder {
+        Texture2DBuilder::new()
+    }
+
+    /// Wraps immutable storage allocated by the builder.
+    pub(crate) fn from_storage(
+        id: GLuint,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        levels: u32,
+    ) -> Texture2D {
+        Texture2D {
+            id,
+            width,
+            height,
+            format,
+            levels,
+            handle: Cell::new(None),
+        }
+    }
 
-        let mut id = 0;
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.levels
+    }
+
+    /// Overwrites a `width` x `height` region of the base level at `(x, y)`,
+    /// counted from the bottom left, with pixels in the texture's format,
+    /// bottom row first. The mip levels are regenerated.
+    pub fn update_region<T: Pod>(&self, x: u32, y: u32, width: u32, height: u32, data: &[T]) {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "Region {}x{} at ({}, {}) is outside the {}x{} texture",
+            width,
+            height,
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        assert_eq!(
+            mem::size_of_val(data),
+            width as usize * height as usize * self.format.bytes_per_pixel(),
+            "Region data doesn't match its size and the texture format"
+        );
         unsafe {
-            gl::GenTextures(1, &mut id);
-            gl::BindTexture(gl::TEXTURE_2D, id);
-            gl::TexStorage2D(gl::TEXTURE_2D, 1, gl::RGBA8, width as _, height as _);
-            gl::TexSubImage2D(
-                gl::TEXTURE_2D,
-                0,
+            // Rows of R8 pixels aren't 4-byte aligned
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TextureSubImage2D(
+                self.id,
                 0,
-                0,
-                width as _,
-                height as _,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                data.as_ptr() as *const GLvoid,
+                x as GLint,
+                y as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                self.format.gl_format(),
+                self.format.gl_type(),
+                data.as_ptr().cast(),
             );
-            gl::GenerateMipmap(gl::TEXTURE_2D);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
-            // gl::TexImage2D(
-            //     gl::TEXTURE_2D,
-            //     0,
-            //     gl::RGBA as i32,
-            //     width as i32,
-            //     height as i32,
-            //     0,
-            //     gl::RGBA,
-            //     gl::UNSIGNED_BYTE,
-            //     data.as_ptr() as *const GLvoid,
-            // );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            if self.levels > 1 {
+                gl::GenerateTextureMipmap(self.id);
+            }
         }
+    }
 
-        Texture2D { id }
+    /// The resident bindless handle, `None` without
+    /// `GL_ARB_bindless_texture`. Sampling parameters are frozen once it
+    /// exists.
+    pub fn bindless_handle(&self) -> Option<GLuint64> {
+        if self.handle.get().is_none() {
+            self.handle.set(bindless::texture_handle(self.id));
+        }
+        self.handle.get()
     }
 
+    /// Binds to texture unit 0.
     pub fn bind(&self) {
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, self.id);
-        }
+        self.bind_unit(0);
+    }
+
+    pub fn bind_unit(&self, unit: GLuint) {
+        state::bind_texture(unit, self.id);
     }
 
     pub fn unbind(&self) {
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, 0);
-        }
+        state::bind_texture(0, 0);
     }
 }
 
 impl Drop for Texture2D {
     fn drop(&mut self) {
-        self.unbind();
+        if let Some(handle) = self.handle.get() {
+            bindless::release_handle(handle);
+        }
         unsafe {
             gl::DeleteTextures(1, &self.id);
         }
+        state::deleted_texture(self.id);
     }
 }
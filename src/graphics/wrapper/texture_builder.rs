@@ -0,0 +1,443 @@
+use std::{error::Error, fmt, fs, io, path::PathBuf};
+
+use bytemuck::Pod;
+use gl::types::*;
+
+use super::texture::Texture2D;
+
+// Core in 4.6, the bindings are generated for 4.5
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+/// Layout of raw pixels and of the texture storing them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PixelFormat {
+    Rgba8,
+    R8,
+    /// Two half floats per pixel, given as their `u16` bits.
+    Rg16F,
+    Rgba32F,
+}
+
+impl PixelFormat {
+    /// Only `Rgba8` has an sRGB variant.
+    pub fn internal_format(&self, srgb: bool) -> GLenum {
+        match self {
+            PixelFormat::Rgba8 if srgb => gl::SRGB8_ALPHA8,
+            PixelFormat::Rgba8 => gl::RGBA8,
+            PixelFormat::R8 => gl::R8,
+            PixelFormat::Rg16F => gl::RG16F,
+            PixelFormat::Rgba32F => gl::RGBA32F,
+        }
+    }
+
+    /// The `format` of `glTextureSubImage2D`.
+    pub fn gl_format(&self) -> GLenum {
+        match self {
+            PixelFormat::Rgba8 | PixelFormat::Rgba32F => gl::RGBA,
+            PixelFormat::R8 => gl::RED,
+            PixelFormat::Rg16F => gl::RG,
+        }
+    }
+
+    /// The `type` of `glTextureSubImage2D`.
+    pub fn gl_type(&self) -> GLenum {
+        match self {
+            PixelFormat::Rgba8 | PixelFormat::R8 => gl::UNSIGNED_BYTE,
+            PixelFormat::Rg16F => gl::HALF_FLOAT,
+            PixelFormat::Rgba32F => gl::FLOAT,
+        }
+    }
+
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Rgba8 | PixelFormat::Rg16F => 4,
+            PixelFormat::R8 => 1,
+            PixelFormat::Rgba32F => 16,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+impl Wrap {
+    pub fn gl_enum(&self) -> GLenum {
+        match self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Wrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+impl Filter {
+    pub fn gl_enum(&self) -> GLenum {
+        match self {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }
+    }
+
+    /// The minification filter picking texels with `self` and blending mip
+    /// levels with `mipmap`.
+    pub fn with_mipmap(&self, mipmap: Filter) -> GLenum {
+        match (self, mipmap) {
+            (Filter::Nearest, Filter::Nearest) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Nearest, Filter::Linear) => gl::NEAREST_MIPMAP_LINEAR,
+            (Filter::Linear, Filter::Nearest) => gl::LINEAR_MIPMAP_NEAREST,
+            (Filter::Linear, Filter::Linear) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
+/// Number of levels of a full mip chain, down to 1x1.
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    /// The image file couldn't be read.
+    Io { path: PathBuf, source: io::Error },
+    /// The file or bytes aren't an image `image` can decode. `name` is the
+    /// path, or `<memory>`.
+    Decode {
+        name: String,
+        source: image::ImageError,
+    },
+    /// Raw pixels that don't match their size and format, or options that
+    /// don't apply to them.
+    InvalidPixels { message: String },
+    /// The builder was given no path, bytes or pixels.
+    NoSource,
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Io { path, source } => {
+                write!(f, "Failed to read texture {}: {}", path.display(), source)
+            }
+            TextureError::Decode { name, source } => {
+                write!(f, "Failed to decode texture {}: {}", name, source)
+            }
+            TextureError::InvalidPixels { message } => write!(f, "Invalid texture: {}", message),
+            TextureError::NoSource => write!(f, "Texture without a source"),
+        }
+    }
+}
+
+impl Error for TextureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TextureError::Io { source, .. } => Some(source),
+            TextureError::Decode { source, .. } => Some(source),
+            TextureError::InvalidPixels { .. } | TextureError::NoSource => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum TextureSource {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+    Pixels {
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        data: Vec<u8>,
+    },
+}
+
+/// Pixels ready for upload, rows from bottom to top like GL expects.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextureData {
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    data: Vec<u8>,
+}
+
+impl TextureData {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Options of a `Texture2D`. Defaults to repeating, trilinear filtering, a
+/// full mip chain, linear RGBA8 and flipping images so their first row ends
+/// up at the top.
+///
+/// ```no_run
+/// # use doom_engine::graphics::wrapper::*;
+/// # fn main() -> Result<(), TextureError> {
+/// let wall = Texture2D::builder()
+///     .path("resources/textures/cat.jpg")
+///     .filter(Filter::Nearest)
+///     .srgb(true)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Texture2DBuilder {
+    source: Option<TextureSource>,
+    wrap: (Wrap, Wrap),
+    min_filter: Filter,
+    mag_filter: Filter,
+    mipmap_filter: Filter,
+    anisotropy: f32,
+    mip_levels: Option<u32>,
+    srgb: bool,
+    flip: bool,
+}
+
+impl Default for Texture2DBuilder {
+    fn default() -> Self {
+        Texture2DBuilder {
+            source: None,
+            wrap: (Wrap::Repeat, Wrap::Repeat),
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_filter: Filter::Linear,
+            anisotropy: 1.,
+            mip_levels: None,
+            srgb: false,
+            flip: true,
+        }
+    }
+}
+
+impl Texture2DBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An image file in any format `image` decodes.
+    pub fn path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.source = Some(TextureSource::Path(path.into()));
+        self
+    }
+
+    /// An encoded image in memory, e.g. from `include_bytes!`.
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.source = Some(TextureSource::Bytes(bytes.to_vec()));
+        self
+    }
+
+    /// Raw pixels, rows from top to bottom unless `flip_vertically(false)`.
+    pub fn pixels<T: Pod>(
+        mut self,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        data: &[T],
+    ) -> Self {
+        self.source = Some(TextureSource::Pixels {
+            width,
+            height,
+            format,
+            data: bytemuck::cast_slice(data).to_vec(),
+        });
+        self
+    }
+
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = (wrap, wrap);
+        self
+    }
+
+    /// Horizontal and vertical wrapping.
+    pub fn wrap_st(mut self, s: Wrap, t: Wrap) -> Self {
+        self.wrap = (s, t);
+        self
+    }
+
+    /// The minification, magnification and mipmap filters at once.
+    /// `Filter::Nearest` gives blocky, pixel-art texels.
+    pub fn filter(self, filter: Filter) -> Self {
+        self.min_filter(filter)
+            .mag_filter(filter)
+            .mipmap_filter(filter)
+    }
+
+    pub fn min_filter(mut self, filter: Filter) -> Self {
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn mag_filter(mut self, filter: Filter) -> Self {
+        self.mag_filter = filter;
+        self
+    }
+
+    /// How mip levels are blended, unused without mips.
+    pub fn mipmap_filter(mut self, filter: Filter) -> Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    /// Maximum anisotropic filtering samples, clamped to what the driver
+    /// supports. 1 disables it.
+    pub fn anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    /// Number of mip levels, 1 for none. Clamped to a full chain, which is
+    /// the default.
+    pub fn mip_levels(mut self, levels: u32) -> Self {
+        self.mip_levels = Some(levels.max(1));
+        self
+    }
+
+    /// Stores RGBA8 colors as sRGB, so sampling returns linear values.
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Whether to reverse the rows, as images are stored top row first and
+    /// GL textures bottom row first.
+    pub fn flip_vertically(mut self, flip: bool) -> Self {
+        self.flip = flip;
+        self
+    }
+
+    /// Reads, decodes and flips the source without touching GL.
+    pub fn load(&self) -> Result<TextureData, TextureError> {
+        let decode = |bytes: &[u8], name: String| {
+            let img = image::load_from_memory(bytes)
+                .map_err(|source| TextureError::Decode { name, source })?
+                .into_rgba8();
+            Ok(TextureData {
+                width: img.width(),
+                height: img.height(),
+                format: PixelFormat::Rgba8,
+                data: img.into_raw(),
+            })
+        };
+        let invalid = |message: String| Err(TextureError::InvalidPixels { message });
+
+        let Some(source) = &self.source else {
+            return Err(TextureError::NoSource);
+        };
+        let mut texture = match source {
+            TextureSource::Path(path) => {
+                let bytes = fs::read(path).map_err(|source| TextureError::Io {
+                    path: path.clone(),
+                    source,
+                })?;
+                decode(&bytes, path.display().to_string())?
+            }
+            TextureSource::Bytes(bytes) => decode(bytes, "<memory>".to_string())?,
+            TextureSource::Pixels {
+                width,
+                height,
+                format,
+                data,
+            } => {
+                let expected = *width as usize * *height as usize * format.bytes_per_pixel();
+                if data.len() != expected {
+                    return invalid(format!(
+                        "{}x{} {:?} pixels take {} bytes, got {}",
+                        width,
+                        height,
+                        format,
+                        expected,
+                        data.len()
+                    ));
+                }
+                TextureData {
+                    width: *width,
+                    height: *height,
+                    format: *format,
+                    data: data.clone(),
+                }
+            }
+        };
+
+        if texture.width == 0 || texture.height == 0 {
+            return invalid("The texture is empty".to_string());
+        }
+        if self.srgb && texture.format != PixelFormat::Rgba8 {
+            return invalid(format!("{:?} has no sRGB format", texture.format));
+        }
+        if self.flip {
+            let row = texture.width as usize * texture.format.bytes_per_pixel();
+            texture.data = texture
+                .data
+                .chunks_exact(row)
+                .rev()
+                .flatten()
+                .copied()
+                .collect();
+        }
+        Ok(texture)
+    }
+
+    pub fn build(&self) -> Result<Texture2D, TextureError> {
+        let data = self.load()?;
+        let full_chain = mip_level_count(data.width, data.height);
+        let levels = self.mip_levels.unwrap_or(full_chain).min(full_chain);
+        let min_filter = if levels > 1 {
+            self.min_filter.with_mipmap(self.mipmap_filter)
+        } else {
+            self.min_filter.gl_enum()
+        };
+
+        let mut id = 0;
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+            gl::TextureStorage2D(
+                id,
+                levels as GLsizei,
+                data.format.internal_format(self.srgb),
+                data.width as GLsizei,
+                data.height as GLsizei,
+            );
+            gl::TextureParameteri(id, gl::TEXTURE_WRAP_S, self.wrap.0.gl_enum() as GLint);
+            gl::TextureParameteri(id, gl::TEXTURE_WRAP_T, self.wrap.1.gl_enum() as GLint);
+            gl::TextureParameteri(id, gl::TEXTURE_MIN_FILTER, min_filter as GLint);
+            gl::TextureParameteri(
+                id,
+                gl::TEXTURE_MAG_FILTER,
+                self.mag_filter.gl_enum() as GLint,
+            );
+            if self.anisotropy > 1. {
+                let mut max = 1.;
+                gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+                gl::TextureParameterf(id, TEXTURE_MAX_ANISOTROPY, self.anisotropy.min(max));
+            }
+        }
+
+        let texture = Texture2D::from_storage(id, data.width, data.height, data.format, levels);
+        texture.update_region(0, 0, data.width, data.height, &data.data);
+        Ok(texture)
+    }
+}
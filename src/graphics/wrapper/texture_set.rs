@@ -0,0 +1,140 @@
+use std::{error::Error, fmt};
+
+use gl::types::*;
+
+use super::texture::Texture2D;
+
+/// The textures of a draw by sampler name, e.g. a material's diffuse, normal
+/// and specular maps. Bound with `ShaderProgram::bind_textures`.
+#[derive(Clone, Debug, Default)]
+pub struct TextureSet<'a> {
+    textures: Vec<(String, &'a Texture2D)>,
+    bindless: bool,
+}
+
+impl<'a> TextureSet<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Samples `texture` through `sampler`, replacing an earlier texture.
+    pub fn with(mut self, sampler: &str, texture: &'a Texture2D) -> Self {
+        self.insert(sampler, texture);
+        self
+    }
+
+    pub fn insert(&mut self, sampler: &str, texture: &'a Texture2D) {
+        match self.textures.iter_mut().find(|(name, _)| name == sampler) {
+            Some(entry) => entry.1 = texture,
+            None => self.textures.push((sampler.to_string(), texture)),
+        }
+    }
+
+    /// Uses bindless handles instead of texture units when
+    /// `GL_ARB_bindless_texture` is available.
+    pub fn bindless(mut self, bindless: bool) -> Self {
+        self.bindless = bindless;
+        self
+    }
+
+    pub fn is_bindless(&self) -> bool {
+        self.bindless
+    }
+
+    pub fn get(&self, sampler: &str) -> Option<&'a Texture2D> {
+        self.iter()
+            .find(|(name, _)| *name == sampler)
+            .map(|(_, texture)| texture)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &'a Texture2D)> {
+        self.textures
+            .iter()
+            .map(|(name, texture)| (name.as_str(), *texture))
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    /// Both sets together, e.g. a material and the scene's shadow map.
+    /// Fails if they give a sampler different textures.
+    pub fn merge(&self, other: &TextureSet<'a>) -> Result<TextureSet<'a>, TextureConflict> {
+        let mut merged = self.clone();
+        for (sampler, texture) in other.iter() {
+            match self.get(sampler) {
+                Some(existing) if existing.id() != texture.id() => {
+                    return Err(TextureConflict {
+                        first: sampler.to_string(),
+                        second: sampler.to_string(),
+                        unit: None,
+                        textures: (existing.id(), texture.id()),
+                    })
+                }
+                _ => merged.insert(sampler, texture),
+            }
+        }
+        merged.bindless = self.bindless || other.bindless;
+        Ok(merged)
+    }
+}
+
+/// Two different textures for one sampler, or for samplers sharing a unit
+/// like `maps` and `maps[0]`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TextureConflict {
+    first: String,
+    second: String,
+    unit: Option<GLuint>,
+    textures: (GLuint, GLuint),
+}
+
+impl TextureConflict {
+    pub(crate) fn new(first: &str, second: &str, unit: GLuint, textures: (GLuint, GLuint)) -> Self {
+        TextureConflict {
+            first: first.to_string(),
+            second: second.to_string(),
+            unit: Some(unit),
+            textures,
+        }
+    }
+
+    /// The samplers given the textures, the same one for a merge conflict.
+    pub fn samplers(&self) -> (&str, &str) {
+        (&self.first, &self.second)
+    }
+
+    /// The shared texture unit, `None` for a merge conflict.
+    pub fn unit(&self) -> Option<GLuint> {
+        self.unit
+    }
+
+    /// The GL names of the two textures.
+    pub fn textures(&self) -> (GLuint, GLuint) {
+        self.textures
+    }
+}
+
+impl fmt::Display for TextureConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (a, b) = self.textures;
+        match self.unit {
+            Some(unit) => write!(
+                f,
+                "Samplers {} and {} share texture unit {} but were given textures {} and {}",
+                self.first, self.second, unit, a, b
+            ),
+            None => write!(
+                f,
+                "Sampler {} was given both texture {} and {}",
+                self.first, a, b
+            ),
+        }
+    }
+}
+
+impl Error for TextureConflict {}
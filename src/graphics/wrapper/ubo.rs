@@ -0,0 +1,72 @@
+use std::{cell::RefCell, collections::HashMap, marker::PhantomData};
+
+use gl::types::*;
+
+use super::buffer::{Buffer, BufferTarget};
+use super::std140::Std140;
+
+thread_local! {
+    /// Binding point of every named uniform block, shared by all programs.
+    static BLOCK_BINDINGS: RefCell<HashMap<String, GLuint>> = RefCell::new(HashMap::new());
+}
+
+/// The binding point of uniform blocks called `name`, assigning the next free
+/// one on first use. `ShaderProgram`s bind all their active blocks to these
+/// when linked, overriding any `layout(binding = N)`, so a `UBO` and the
+/// programs reading it can be created in any order.
+pub fn block_binding(name: &str) -> GLuint {
+    BLOCK_BINDINGS.with(|bindings| {
+        let mut bindings = bindings.borrow_mut();
+        let next = bindings.len() as GLuint;
+        *bindings.entry(name.to_string()).or_insert(next)
+    })
+}
+
+// Uniform Buffer Object
+pub struct UBO<T: Std140> {
+    buffer: Buffer<u8>,
+    binding: GLuint,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Std140> UBO<T> {
+    /// Storage for the uniform block `block_name`, bound to its shared
+    /// binding point.
+    pub fn new(block_name: &str, usage: GLenum, value: &T) -> UBO<T> {
+        let binding = block_binding(block_name);
+        let buffer = Buffer::new(BufferTarget::Uniform, usage, &value.to_std140());
+        buffer.bind_base(binding);
+        UBO {
+            buffer,
+            binding,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.buffer.id()
+    }
+
+    pub fn binding(&self) -> GLuint {
+        self.binding
+    }
+
+    /// Uploads the whole block, e.g. once per frame.
+    pub fn update(&self, value: &T) {
+        self.buffer.sub_data(0, &value.to_std140());
+    }
+
+    /// Binds the buffer to its binding point again, e.g. after something
+    /// else used the point.
+    pub fn bind_base(&self) {
+        self.buffer.bind_base(self.binding);
+    }
+
+    pub fn bind(&self) {
+        self.buffer.bind();
+    }
+
+    pub fn unbind(&self) {
+        self.buffer.unbind();
+    }
+}
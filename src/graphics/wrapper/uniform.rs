@@ -0,0 +1,181 @@
+use gl::types::*;
+
+use crate::maths::{Mat2, Mat3, Mat4, Vec2, Vec3, Vec4};
+
+use super::reflection::{is_image, is_sampler};
+
+/// A value `ShaderProgram::set_uniform` can upload.
+pub trait UniformValue {
+    /// Whether the value can be assigned to a uniform of type `gl_type`.
+    fn accepts(&self, gl_type: GLenum) -> bool;
+
+    /// Number of array elements written.
+    fn count(&self) -> usize;
+
+    /// Uploads to `location` of `program`, bound or not.
+    fn upload(&self, program: GLuint, location: GLint);
+}
+
+/// A type that can be uploaded on its own or as an array.
+pub trait UniformElement: Sized {
+    fn accepts(gl_type: GLenum) -> bool;
+
+    fn upload_array(values: &[Self], program: GLuint, location: GLint);
+}
+
+macro_rules! impl_uniform_element {
+    ( $t:ty, |$gl_type:ident| $accepts:expr, |$values:ident, $program:ident, $location:ident| $upload:expr ) => {
+        impl UniformElement for $t {
+            fn accepts($gl_type: GLenum) -> bool {
+                $accepts
+            }
+
+            fn upload_array($values: &[Self], $program: GLuint, $location: GLint) {
+                unsafe { $upload }
+            }
+        }
+
+        impl UniformValue for $t {
+            fn accepts(&self, gl_type: GLenum) -> bool {
+                <$t as UniformElement>::accepts(gl_type)
+            }
+
+            fn count(&self) -> usize {
+                1
+            }
+
+            fn upload(&self, program: GLuint, location: GLint) {
+                <$t>::upload_array(std::slice::from_ref(self), program, location)
+            }
+        }
+    };
+}
+
+/// Flattens vectors or matrices for the `*v` uploads.
+fn flatten<'a, T: 'a>(values: &'a [T], f: impl Fn(&'a T) -> &'a [f32]) -> Vec<f32> {
+    values.iter().flat_map(f).copied().collect()
+}
+
+impl_uniform_element!(f32, |t| t == gl::FLOAT, |v, program, loc| {
+    gl::ProgramUniform1fv(program, loc, v.len() as GLsizei, v.as_ptr())
+});
+
+// Samplers and images are set to their texture or image unit
+impl_uniform_element!(
+    i32,
+    |t| t == gl::INT || t == gl::BOOL || is_sampler(t) || is_image(t),
+    |v, program, loc| gl::ProgramUniform1iv(program, loc, v.len() as GLsizei, v.as_ptr())
+);
+
+impl_uniform_element!(
+    u32,
+    |t| t == gl::UNSIGNED_INT || t == gl::BOOL,
+    |v, program, loc| gl::ProgramUniform1uiv(program, loc, v.len() as GLsizei, v.as_ptr())
+);
+
+impl_uniform_element!(bool, |t| t == gl::BOOL, |v, program, loc| {
+    let ints: Vec<GLint> = v.iter().map(|&b| b as GLint).collect();
+    gl::ProgramUniform1iv(program, loc, ints.len() as GLsizei, ints.as_ptr())
+});
+
+impl_uniform_element!(Vec2, |t| t == gl::FLOAT_VEC2, |v, program, loc| {
+    gl::ProgramUniform2fv(
+        program,
+        loc,
+        v.len() as GLsizei,
+        flatten(v, Vec2::as_slice).as_ptr(),
+    )
+});
+
+impl_uniform_element!(Vec3, |t| t == gl::FLOAT_VEC3, |v, program, loc| {
+    gl::ProgramUniform3fv(
+        program,
+        loc,
+        v.len() as GLsizei,
+        flatten(v, Vec3::as_slice).as_ptr(),
+    )
+});
+
+impl_uniform_element!(Vec4, |t| t == gl::FLOAT_VEC4, |v, program, loc| {
+    gl::ProgramUniform4fv(
+        program,
+        loc,
+        v.len() as GLsizei,
+        flatten(v, Vec4::as_slice).as_ptr(),
+    )
+});
+
+//Matrices are row-major, therefore we need to use transpose: gl::TRUE
+//since OpenGL uses column-major matrices
+
+impl_uniform_element!(Mat2, |t| t == gl::FLOAT_MAT2, |v, program, loc| {
+    gl::ProgramUniformMatrix2fv(
+        program,
+        loc,
+        v.len() as GLsizei,
+        gl::TRUE,
+        flatten(v, Mat2::as_slice).as_ptr(),
+    )
+});
+
+impl_uniform_element!(Mat3, |t| t == gl::FLOAT_MAT3, |v, program, loc| {
+    gl::ProgramUniformMatrix3fv(
+        program,
+        loc,
+        v.len() as GLsizei,
+        gl::TRUE,
+        flatten(v, Mat3::as_slice).as_ptr(),
+    )
+});
+
+impl_uniform_element!(Mat4, |t| t == gl::FLOAT_MAT4, |v, program, loc| {
+    gl::ProgramUniformMatrix4fv(
+        program,
+        loc,
+        v.len() as GLsizei,
+        gl::TRUE,
+        flatten(v, Mat4::as_slice).as_ptr(),
+    )
+});
+
+impl<T: UniformElement> UniformValue for [T] {
+    fn accepts(&self, gl_type: GLenum) -> bool {
+        T::accepts(gl_type)
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn upload(&self, program: GLuint, location: GLint) {
+        T::upload_array(self, program, location)
+    }
+}
+
+impl<T: UniformElement, const N: usize> UniformValue for [T; N] {
+    fn accepts(&self, gl_type: GLenum) -> bool {
+        T::accepts(gl_type)
+    }
+
+    fn count(&self) -> usize {
+        N
+    }
+
+    fn upload(&self, program: GLuint, location: GLint) {
+        T::upload_array(self, program, location)
+    }
+}
+
+impl<T: UniformElement> UniformValue for Vec<T> {
+    fn accepts(&self, gl_type: GLenum) -> bool {
+        T::accepts(gl_type)
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn upload(&self, program: GLuint, location: GLint) {
+        T::upload_array(self, program, location)
+    }
+}
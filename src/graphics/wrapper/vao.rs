@@ -1,5 +1,12 @@
+use std::mem;
+
+use bytemuck::Pod;
 use gl::types::*;
 
+use super::buffer::{Buffer, IndexType, StreamBuffer};
+use super::state;
+use super::vertex_layout::{Vertex, VertexLayout};
+
 // Vertex Array Object
 pub struct VAO {
     id: GLuint,
@@ -9,19 +16,69 @@ impl VAO {
     pub fn new() -> VAO {
         let mut id = 0;
         unsafe {
-            gl::GenVertexArrays(1, &mut id);
-            gl::BindVertexArray(id);
+            gl::CreateVertexArrays(1, &mut id);
         }
         VAO { id }
     }
 
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
     pub fn bind(&self) {
-        unsafe { gl::BindVertexArray(self.id) }
+        state::bind_vertex_array(self.id)
     }
 
     pub fn unbind(&self) {
-        unsafe { gl::BindVertexArray(0) }
+        state::bind_vertex_array(0)
+    }
+
+    /// Reads the attributes of `layout` from `buffer`.
+    pub fn attach<T: Pod>(&self, buffer: &Buffer<T>, layout: &VertexLayout) {
+        self.attach_range(buffer, 0, layout);
+    }
+
+    /// `attach`, with vertex 0 being element `offset` of the buffer.
+    pub fn attach_range<T: Pod>(&self, buffer: &Buffer<T>, offset: usize, layout: &VertexLayout) {
+        assert!(offset <= buffer.len(), "Attach past the end of the buffer");
+        check_stride::<T>(layout);
+        layout.apply_to(self.id, buffer.id(), offset * mem::size_of::<T>());
     }
+
+    /// Reads the attributes of `layout` from a stream buffer, with vertex 0
+    /// being element `offset` of the whole buffer. Attach again with
+    /// `buffer.offset()` after each `next_region`.
+    pub fn attach_stream<T: Pod>(
+        &self,
+        buffer: &StreamBuffer<T>,
+        offset: usize,
+        layout: &VertexLayout,
+    ) {
+        assert!(
+            offset <= buffer.len() * buffer.regions(),
+            "Attach past the end of the stream buffer"
+        );
+        check_stride::<T>(layout);
+        layout.apply_to(self.id, buffer.id(), offset * mem::size_of::<T>());
+    }
+
+    /// `attach` with the layout of the vertex type.
+    pub fn attach_vertices<V: Vertex>(&self, buffer: &Buffer<V>) {
+        self.attach(buffer, &V::layout());
+    }
+
+    /// Uses `buffer` for indexed draws.
+    pub fn set_index_buffer<I: IndexType>(&self, buffer: &Buffer<I>) {
+        unsafe { gl::VertexArrayElementBuffer(self.id, buffer.id()) }
+    }
+}
+
+fn check_stride<T>(layout: &VertexLayout) {
+    assert_eq!(
+        layout.stride(),
+        mem::size_of::<T>(),
+        "Vertex layout stride doesn't match the buffer's element size"
+    );
 }
 
 impl Default for VAO {
@@ -32,9 +89,9 @@ impl Default for VAO {
 
 impl Drop for VAO {
     fn drop(&mut self) {
-        self.unbind();
         unsafe {
             gl::DeleteVertexArrays(1, &self.id);
         }
+        state::deleted_vertex_array(self.id);
     }
 }
@@ -0,0 +1,433 @@
+use std::fmt::Write;
+
+use bytemuck::{Pod, Zeroable};
+use gl::types::*;
+
+use crate::maths::{Mat2, Mat3, Mat4, Vec2, Vec3, Vec4};
+
+use super::reflection::type_name;
+use super::shader_program::ShaderProgram;
+
+/// How the shader reads an attribute.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AttribKind {
+    /// Floats, read as `float`/`vecN`.
+    Float,
+    /// Integers mapped to [0, 1] or [-1, 1], read as `float`/`vecN`.
+    Normalized,
+    /// Integers read as `int`/`uint` and their vectors.
+    Integer,
+}
+
+/// Component count and type of one attribute. Matrices take one location
+/// per row.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AttribFormat {
+    components: GLint,
+    gl_type: GLenum,
+    kind: AttribKind,
+    rows: GLuint,
+}
+
+impl AttribFormat {
+    pub const fn new(components: GLint, gl_type: GLenum, kind: AttribKind) -> Self {
+        AttribFormat {
+            components,
+            gl_type,
+            kind,
+            rows: 1,
+        }
+    }
+
+    pub const fn matrix(n: GLint) -> Self {
+        AttribFormat {
+            components: n,
+            gl_type: gl::FLOAT,
+            kind: AttribKind::Float,
+            rows: n as GLuint,
+        }
+    }
+
+    pub fn components(&self) -> GLint {
+        self.components
+    }
+
+    pub fn gl_type(&self) -> GLenum {
+        self.gl_type
+    }
+
+    pub fn kind(&self) -> AttribKind {
+        self.kind
+    }
+
+    /// Number of locations taken.
+    pub fn locations(&self) -> GLuint {
+        self.rows
+    }
+
+    /// Size in bytes.
+    pub fn size(&self) -> usize {
+        let component = match self.gl_type {
+            gl::BYTE | gl::UNSIGNED_BYTE => 1,
+            gl::SHORT | gl::UNSIGNED_SHORT | gl::HALF_FLOAT => 2,
+            gl::DOUBLE => 8,
+            _ => 4,
+        };
+        component * self.components as usize * self.rows as usize
+    }
+
+    /// The GLSL type reading it, as reported by reflection.
+    pub fn glsl_type(&self) -> GLenum {
+        let n = self.components as usize - 1;
+        if self.rows > 1 {
+            return [gl::FLOAT, gl::FLOAT_MAT2, gl::FLOAT_MAT3, gl::FLOAT_MAT4][n];
+        }
+        let signed = matches!(self.gl_type, gl::BYTE | gl::SHORT | gl::INT);
+        match (self.kind, signed) {
+            (AttribKind::Integer, true) => [gl::INT, gl::INT_VEC2, gl::INT_VEC3, gl::INT_VEC4][n],
+            (AttribKind::Integer, false) => [
+                gl::UNSIGNED_INT,
+                gl::UNSIGNED_INT_VEC2,
+                gl::UNSIGNED_INT_VEC3,
+                gl::UNSIGNED_INT_VEC4,
+            ][n],
+            _ => [gl::FLOAT, gl::FLOAT_VEC2, gl::FLOAT_VEC3, gl::FLOAT_VEC4][n],
+        }
+    }
+}
+
+/// A type that can be a vertex attribute, i.e. a field of a `vertex!`
+/// struct.
+pub trait AttribType: Pod {
+    const FORMAT: AttribFormat;
+}
+
+macro_rules! impl_attrib_type {
+    ( $( $t:ty => $components:expr, $gl_type:expr, $kind:ident );* $(;)? ) => { $(
+        impl AttribType for $t {
+            const FORMAT: AttribFormat = AttribFormat::new($components, $gl_type, AttribKind::$kind);
+        }
+    )* };
+}
+
+impl_attrib_type!(
+    f32 => 1, gl::FLOAT, Float;
+    [f32; 2] => 2, gl::FLOAT, Float;
+    [f32; 3] => 3, gl::FLOAT, Float;
+    [f32; 4] => 4, gl::FLOAT, Float;
+    Vec2 => 2, gl::FLOAT, Float;
+    Vec3 => 3, gl::FLOAT, Float;
+    Vec4 => 4, gl::FLOAT, Float;
+    i32 => 1, gl::INT, Integer;
+    [i32; 2] => 2, gl::INT, Integer;
+    [i32; 3] => 3, gl::INT, Integer;
+    [i32; 4] => 4, gl::INT, Integer;
+    u32 => 1, gl::UNSIGNED_INT, Integer;
+    [u32; 2] => 2, gl::UNSIGNED_INT, Integer;
+    [u32; 3] => 3, gl::UNSIGNED_INT, Integer;
+    [u32; 4] => 4, gl::UNSIGNED_INT, Integer;
+    [u8; 4] => 4, gl::UNSIGNED_BYTE, Integer;
+    [u16; 2] => 2, gl::UNSIGNED_SHORT, Integer;
+    [u16; 4] => 4, gl::UNSIGNED_SHORT, Integer;
+    Normalized<[u8; 4]> => 4, gl::UNSIGNED_BYTE, Normalized;
+    Normalized<[i8; 4]> => 4, gl::BYTE, Normalized;
+    Normalized<[u16; 2]> => 2, gl::UNSIGNED_SHORT, Normalized;
+    Normalized<[i16; 2]> => 2, gl::SHORT, Normalized;
+    Normalized<[i16; 4]> => 4, gl::SHORT, Normalized;
+);
+
+// `MatrixN` is row-major, so the shader receives the transpose: store
+// `m.transpose()` or multiply `v * m` in GLSL.
+impl AttribType for Mat2 {
+    const FORMAT: AttribFormat = AttribFormat::matrix(2);
+}
+
+impl AttribType for Mat3 {
+    const FORMAT: AttribFormat = AttribFormat::matrix(3);
+}
+
+impl AttribType for Mat4 {
+    const FORMAT: AttribFormat = AttribFormat::matrix(4);
+}
+
+/// Integer components read as normalized floats, e.g. `Normalized<[u8; 4]>`
+/// for an RGBA8 color.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(transparent)]
+pub struct Normalized<T>(pub T);
+
+// SAFETY: a transparent wrapper around a `Pod` type
+unsafe impl<T: Zeroable> Zeroable for Normalized<T> {}
+unsafe impl<T: Pod> Pod for Normalized<T> {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VertexAttribute {
+    name: String,
+    location: GLuint,
+    format: AttribFormat,
+    offset: usize,
+}
+
+impl VertexAttribute {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// First location, matrices take the following ones too.
+    pub fn location(&self) -> GLuint {
+        self.location
+    }
+
+    pub fn format(&self) -> AttribFormat {
+        self.format
+    }
+
+    /// Offset in bytes from the start of a vertex.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// The attributes of one vertex buffer. Built by hand for raw float buffers,
+/// or from a `vertex!` struct with `V::layout()`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VertexLayout {
+    attributes: Vec<VertexAttribute>,
+    stride: usize,
+    divisor: GLuint,
+}
+
+impl VertexLayout {
+    /// `stride` is the size of a vertex in bytes.
+    pub fn new(stride: usize) -> Self {
+        VertexLayout {
+            attributes: Vec::new(),
+            stride,
+            divisor: 0,
+        }
+    }
+
+    pub fn attribute(
+        mut self,
+        name: &str,
+        location: GLuint,
+        format: AttribFormat,
+        offset: usize,
+    ) -> Self {
+        assert!(
+            offset + format.size() <= self.stride,
+            "Attribute {} doesn't fit in a stride of {}",
+            name,
+            self.stride
+        );
+        self.attributes.push(VertexAttribute {
+            name: name.to_string(),
+            location,
+            format,
+            offset,
+        });
+        self
+    }
+
+    /// Advances the attributes once per `divisor` instances instead of once
+    /// per vertex.
+    pub fn instanced(mut self, divisor: GLuint) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
+    /// Shifts every location by `location`, e.g. to put per-instance
+    /// attributes after the per-vertex ones.
+    pub fn starting_at(mut self, location: GLuint) -> Self {
+        for attribute in &mut self.attributes {
+            attribute.location += location;
+        }
+        self
+    }
+
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// 0 for per-vertex attributes.
+    pub fn divisor(&self) -> GLuint {
+        self.divisor
+    }
+
+    /// One past the last location used.
+    pub fn end_location(&self) -> GLuint {
+        self.attributes
+            .iter()
+            .map(|attribute| attribute.location + attribute.format.rows)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Sets up and enables the attributes of `vao`, reading from `buffer`.
+    /// The buffer goes in the binding index of the first location, so
+    /// layouts with distinct locations can share a VAO.
+    /// Reads the attributes from `buffer`, starting `offset` bytes in.
+    pub(crate) fn apply_to(&self, vao: GLuint, buffer: GLuint, offset: usize) {
+        let Some(binding) = self.attributes.iter().map(|a| a.location).min() else {
+            return;
+        };
+        unsafe {
+            gl::VertexArrayVertexBuffer(
+                vao,
+                binding,
+                buffer,
+                offset as GLintptr,
+                self.stride as GLsizei,
+            );
+            gl::VertexArrayBindingDivisor(vao, binding, self.divisor);
+        }
+        for attribute in &self.attributes {
+            let format = attribute.format;
+            let row_size = format.size() / format.rows as usize;
+            for row in 0..format.rows {
+                let index = attribute.location + row;
+                let offset = (attribute.offset + row as usize * row_size) as GLuint;
+                unsafe {
+                    if format.kind == AttribKind::Integer {
+                        gl::VertexArrayAttribIFormat(
+                            vao,
+                            index,
+                            format.components,
+                            format.gl_type,
+                            offset,
+                        );
+                    } else {
+                        gl::VertexArrayAttribFormat(
+                            vao,
+                            index,
+                            format.components,
+                            format.gl_type,
+                            (format.kind == AttribKind::Normalized) as GLboolean,
+                            offset,
+                        );
+                    }
+                    gl::VertexArrayAttribBinding(vao, index, binding);
+                    gl::EnableVertexArrayAttrib(vao, index);
+                }
+            }
+        }
+    }
+
+    /// Checks the active attributes of `program` against this layout, see
+    /// `check_layouts`.
+    pub fn check(&self, program: &ShaderProgram) -> Result<(), String> {
+        check_layouts(program, &[self])
+    }
+}
+
+/// Checks that every active attribute of `program` is fed by one of
+/// `layouts` with a matching GLSL type. Layout attributes the program doesn't
+/// use are fine, the compiler drops unused inputs.
+pub fn check_layouts(program: &ShaderProgram, layouts: &[&VertexLayout]) -> Result<(), String> {
+    let mut errors = String::new();
+    for active in program.reflection().attributes() {
+        // Built-ins like gl_VertexID
+        if active.location() < 0 {
+            continue;
+        }
+        let location = active.location() as GLuint;
+        let found = layouts
+            .iter()
+            .flat_map(|layout| layout.attributes())
+            .find(|attribute| {
+                (attribute.location..attribute.location + attribute.format.rows).contains(&location)
+            });
+        match found {
+            None => writeln!(
+                errors,
+                "{} at location {} isn't in the vertex layout",
+                active.name(),
+                location
+            ),
+            Some(attribute) if attribute.format.glsl_type() != active.gl_type() => writeln!(
+                errors,
+                "{} is a {} in the shader but {} is a {} in the vertex layout",
+                active.name(),
+                type_name(active.gl_type()),
+                attribute.name,
+                type_name(attribute.format.glsl_type())
+            ),
+            Some(_) => Ok(()),
+        }
+        .unwrap();
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.trim_end().to_string())
+    }
+}
+
+/// A `#[repr(C)]` struct used as the vertex type of a buffer, usually
+/// declared with `vertex!`.
+pub trait Vertex: Pod {
+    fn layout() -> VertexLayout;
+}
+
+/// Declares a `#[repr(C)]` struct and implements `Vertex` for it. Fields
+/// can be any `AttribType` and get consecutive locations starting at 0, in
+/// order. The struct has to derive `Pod`, which also rules out padding.
+///
+/// ```
+/// use bytemuck::{Pod, Zeroable};
+/// use doom_engine::graphics::wrapper::{Normalized, Vertex};
+/// use doom_engine::maths::{Vec2, Vec3};
+/// use doom_engine::vertex;
+///
+/// vertex! {
+///     #[derive(Clone, Copy, Pod, Zeroable)]
+///     pub struct SpriteVertex {
+///         pub pos: Vec3,
+///         pub tex: Vec2,
+///         pub color: Normalized<[u8; 4]>,
+///     }
+/// }
+///
+/// let layout = SpriteVertex::layout();
+/// assert_eq!(layout.stride(), 24);
+/// assert_eq!(layout.attributes()[2].offset(), 20);
+/// ```
+#[macro_export]
+macro_rules! vertex {
+    (
+        $( #[$meta:meta] )*
+        $vis:vis struct $name:ident {
+            $( $field_vis:vis $field:ident : $t:ty ),* $(,)?
+        }
+    ) => {
+        $( #[$meta] )*
+        #[repr(C)]
+        $vis struct $name {
+            $( $field_vis $field: $t ),*
+        }
+
+        impl $crate::graphics::wrapper::Vertex for $name {
+            fn layout() -> $crate::graphics::wrapper::VertexLayout {
+                let layout = $crate::graphics::wrapper::VertexLayout::new(::std::mem::size_of::<$name>());
+                let location = 0;
+                $(
+                    let format = <$t as $crate::graphics::wrapper::AttribType>::FORMAT;
+                    let layout = layout.attribute(
+                        stringify!($field),
+                        location,
+                        format,
+                        ::std::mem::offset_of!($name, $field),
+                    );
+                    let location = location + format.locations();
+                )*
+                let _ = location;
+                layout
+            }
+        }
+    };
+}
@@ -1,116 +1,106 @@
 use doom_engine::graphics::mesh::Cube;
 use doom_engine::graphics::{wrapper::*, Window};
 use doom_engine::maths::*;
-use doom_engine::vector;
+use doom_engine::{vector_n, vertex};
 use egui::{Align2, RichText};
 use egui_glfw::egui;
-use gl::types::*;
 use std::error::Error;
-use std::{mem, os::raw::c_void, ptr};
 
 static WIDTH: u32 = 1920;
 
 static HEIGHT: u32 = 1080;
 
+vertex! {
+    #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+    struct CubeVertex {
+        pos: Vec3,
+        tex: Vec2,
+        normal: Vec3,
+    }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let mut window = Window::new(WIDTH, HEIGHT, "Doom Engine");
 
     let mut shader_program = ShaderProgram::new(
         "resources/shaders/texture.vert",
         "resources/shaders/texture.frag",
-    );
+    )?;
 
-    let texture_gato = Texture2D::new("resources/textures/cat.jpg");
-    let texture_gatorrito = Texture2D::new("resources/textures/gatorrito.jpg");
-    let texture_pog = Texture2D::new("resources/textures/pog.jpg");
+    let texture_gato = Texture2D::new("resources/textures/cat.jpg")?;
+    let texture_gatorrito = Texture2D::new("resources/textures/gatorrito.jpg")?;
+    let texture_pog = Texture2D::new("resources/textures/pog.jpg")?;
     let textures = [&texture_gato, &texture_gatorrito, &texture_pog];
     let mut main_texture = 0;
 
     // All Buffer Objects are binded and the data is stored on creation
     let _vao = VAO::new();
-    let _vbo: VBO = BO::new(
+    let cube_vertices: Vec<f32> = vec![
+        -0.5, -0.5, -0.5, 0.0, 1.0, 0.0, 0.0, -1.0, //
+        0.5, -0.5, -0.5, 1.0, 0.0, 0.0, 0.0, -1.0, //
+        0.5, 0.5, -0.5, 1.0, 1.0, 0.0, 0.0, -1.0, //
+        0.5, 0.5, -0.5, 1.0, 1.0, 0.0, 0.0, -1.0, //
+        -0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 0.0, -1.0, //
+        -0.5, -0.5, -0.5, 0.0, 0.0, 0.0, 0.0, -1.0, //
+        //
+        -0.5, -0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, //
+        0.5, -0.5, 0.5, 1.0, 0.0, 0.0, 0.0, 1.0, //
+        0.5, 0.5, 0.5, 1.0, 1.0, 0.0, 0.0, 1.0, //
+        0.5, 0.5, 0.5, 1.0, 1.0, 0.0, 0.0, 1.0, //
+        -0.5, 0.5, 0.5, 0.0, 1.0, 0.0, 0.0, 1.0, //
+        -0.5, -0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, //
+        //
+        -0.5, 0.5, 0.5, 1.0, 0.0, -1.0, 0.0, 0.0, //
+        -0.5, 0.5, -0.5, 1.0, 1.0, -1.0, 0.0, 0.0, //
+        -0.5, -0.5, -0.5, 0.0, 1.0, -1.0, 0.0, 0.0, //
+        -0.5, -0.5, -0.5, 0.0, 1.0, -1.0, 0.0, 0.0, //
+        -0.5, -0.5, 0.5, 0.0, 0.0, -1.0, 0.0, 0.0, //
+        -0.5, 0.5, 0.5, 1.0, 0.0, -1.0, 0.0, 0.0, //
+        //
+        0.5, 0.5, 0.5, 1.0, 0.0, 1.0, 0.0, 0.0, //
+        0.5, 0.5, -0.5, 1.0, 1.0, 1.0, 0.0, 0.0, //
+        0.5, -0.5, -0.5, 0.0, 1.0, 1.0, 0.0, 0.0, //
+        0.5, -0.5, -0.5, 0.0, 1.0, 1.0, 0.0, 0.0, //
+        0.5, -0.5, 0.5, 0.0, 0.0, 1.0, 0.0, 0.0, //
+        0.5, 0.5, 0.5, 1.0, 0.0, 1.0, 0.0, 0.0, //
+        //
+        -0.5, -0.5, -0.5, 0.0, 1.0, 0.0, -1.0, 0.0, //
+        0.5, -0.5, -0.5, 1.0, 1.0, 0.0, -1.0, 0.0, //
+        0.5, -0.5, 0.5, 1.0, 0.0, 0.0, -1.0, 0.0, //
+        0.5, -0.5, 0.5, 1.0, 0.0, 0.0, -1.0, 0.0, //
+        -0.5, -0.5, 0.5, 0.0, 0.0, 0.0, -1.0, 0.0, //
+        -0.5, -0.5, -0.5, 0.0, 1.0, 0.0, -1.0, 0.0, //
+        //
+        -0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 1.0, 0.0, //
+        0.5, 0.5, -0.5, 1.0, 1.0, 0.0, 1.0, 0.0, //
+        0.5, 0.5, 0.5, 1.0, 0.0, 0.0, 1.0, 0.0, //
+        0.5, 0.5, 0.5, 1.0, 0.0, 0.0, 1.0, 0.0, //
+        -0.5, 0.5, 0.5, 0.0, 0.0, 0.0, 1.0, 0.0, //
+        -0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 1.0, 0.0,
+    ];
+    let _vbo: Buffer<CubeVertex> = Buffer::new(
+        BufferTarget::Array,
         gl::STATIC_DRAW,
-        vec![
-            -0.5, -0.5, -0.5, 0.0, 1.0, 0.0, 0.0, -1.0, //
-            0.5, -0.5, -0.5, 1.0, 0.0, 0.0, 0.0, -1.0, //
-            0.5, 0.5, -0.5, 1.0, 1.0, 0.0, 0.0, -1.0, //
-            0.5, 0.5, -0.5, 1.0, 1.0, 0.0, 0.0, -1.0, //
-            -0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 0.0, -1.0, //
-            -0.5, -0.5, -0.5, 0.0, 0.0, 0.0, 0.0, -1.0, //
-            //
-            -0.5, -0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, //
-            0.5, -0.5, 0.5, 1.0, 0.0, 0.0, 0.0, 1.0, //
-            0.5, 0.5, 0.5, 1.0, 1.0, 0.0, 0.0, 1.0, //
-            0.5, 0.5, 0.5, 1.0, 1.0, 0.0, 0.0, 1.0, //
-            -0.5, 0.5, 0.5, 0.0, 1.0, 0.0, 0.0, 1.0, //
-            -0.5, -0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0, //
-            //
-            -0.5, 0.5, 0.5, 1.0, 0.0, -1.0, 0.0, 0.0, //
-            -0.5, 0.5, -0.5, 1.0, 1.0, -1.0, 0.0, 0.0, //
-            -0.5, -0.5, -0.5, 0.0, 1.0, -1.0, 0.0, 0.0, //
-            -0.5, -0.5, -0.5, 0.0, 1.0, -1.0, 0.0, 0.0, //
-            -0.5, -0.5, 0.5, 0.0, 0.0, -1.0, 0.0, 0.0, //
-            -0.5, 0.5, 0.5, 1.0, 0.0, -1.0, 0.0, 0.0, //
-            //
-            0.5, 0.5, 0.5, 1.0, 0.0, 1.0, 0.0, 0.0, //
-            0.5, 0.5, -0.5, 1.0, 1.0, 1.0, 0.0, 0.0, //
-            0.5, -0.5, -0.5, 0.0, 1.0, 1.0, 0.0, 0.0, //
-            0.5, -0.5, -0.5, 0.0, 1.0, 1.0, 0.0, 0.0, //
-            0.5, -0.5, 0.5, 0.0, 0.0, 1.0, 0.0, 0.0, //
-            0.5, 0.5, 0.5, 1.0, 0.0, 1.0, 0.0, 0.0, //
-            //
-            -0.5, -0.5, -0.5, 0.0, 1.0, 0.0, -1.0, 0.0, //
-            0.5, -0.5, -0.5, 1.0, 1.0, 0.0, -1.0, 0.0, //
-            0.5, -0.5, 0.5, 1.0, 0.0, 0.0, -1.0, 0.0, //
-            0.5, -0.5, 0.5, 1.0, 0.0, 0.0, -1.0, 0.0, //
-            -0.5, -0.5, 0.5, 0.0, 0.0, 0.0, -1.0, 0.0, //
-            -0.5, -0.5, -0.5, 0.0, 1.0, 0.0, -1.0, 0.0, //
-            //
-            -0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 1.0, 0.0, //
-            0.5, 0.5, -0.5, 1.0, 1.0, 0.0, 1.0, 0.0, //
-            0.5, 0.5, 0.5, 1.0, 0.0, 0.0, 1.0, 0.0, //
-            0.5, 0.5, 0.5, 1.0, 0.0, 0.0, 1.0, 0.0, //
-            -0.5, 0.5, 0.5, 0.0, 0.0, 0.0, 1.0, 0.0, //
-            -0.5, 0.5, -0.5, 0.0, 1.0, 0.0, 1.0, 0.0,
-        ],
+        bytemuck::cast_slice(&cube_vertices),
     );
+    _vao.attach_vertices(&_vbo);
+    if let Err(e) = CubeVertex::layout().check(&shader_program) {
+        eprintln!("{}", e);
+    }
     let cube_pos = [
-        vector!(2.0, 5.0, -15.0),
-        vector!(-1.5, -2.2, -2.5),
-        vector!(-3.8, -2.0, -12.3),
-        vector!(2.4, -0.4, -3.5),
-        vector!(-1.7, 3.0, -7.5),
-        vector!(1.3, -2.0, -2.5),
-        vector!(1.5, 2.0, -2.5),
-        vector!(1.5, 0.2, -1.5),
-        vector!(-1.3, 1.0, -1.5),
+        vector_n!(2.0, 5.0, -15.0),
+        vector_n!(-1.5, -2.2, -2.5),
+        vector_n!(-3.8, -2.0, -12.3),
+        vector_n!(2.4, -0.4, -3.5),
+        vector_n!(-1.7, 3.0, -7.5),
+        vector_n!(1.3, -2.0, -2.5),
+        vector_n!(1.5, 2.0, -2.5),
+        vector_n!(1.5, 0.2, -1.5),
+        vector_n!(-1.3, 1.0, -1.5),
     ];
 
-    let stride = 8 * mem::size_of::<GLfloat>() as GLsizei;
-
-    let _pos_attrib = VertexAttrib::new(0, 3, gl::FLOAT, gl::FALSE, stride, ptr::null());
-    let _tex_attrib = VertexAttrib::new(
-        1,
-        2,
-        gl::FLOAT,
-        gl::FALSE,
-        stride,
-        (3 * mem::size_of::<GLfloat>()) as *const c_void,
-    );
-    let normal_attrib = VertexAttrib::new(
-        2,
-        3,
-        gl::FLOAT,
-        gl::FALSE,
-        stride,
-        (5 * mem::size_of::<GLfloat>()) as *const c_void,
-    );
-
-    _vbo.unbind();
     _vao.unbind();
-    _pos_attrib.disable();
-    _tex_attrib.disable();
-    normal_attrib.disable();
+    _vbo.unbind();
     texture_gato.unbind();
     texture_pog.unbind();
     texture_gatorrito.unbind();
@@ -119,23 +109,23 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut light_shader = ShaderProgram::new(
         "resources/shaders/light.vert",
         "resources/shaders/light.frag",
-    );
+    )?;
 
     let mut light = Cube::new(
-        Some((
-            Matrix::translation(vector![2.5, 1.0, 2.0]),
-            Matrix::identity(4),
-            Matrix::scaling(vector![0.2, 0.2, 0.2]),
+        Some(Transform::new(
+            vector_n![2.5, 1.0, 2.0],
+            Quaternion::identity(),
+            vector_n![0.2, 0.2, 0.2],
         )),
         None,
         None,
     );
 
     let mut light2 = Cube::new(
-        Some((
-            Matrix::translation(vector![-2.5, 1.0, 2.0]),
-            Matrix::identity(4),
-            Matrix::scaling(vector![0.2, 0.2, 0.2]),
+        Some(Transform::new(
+            vector_n![-2.5, 1.0, 2.0],
+            Quaternion::identity(),
+            vector_n![0.2, 0.2, 0.2],
         )),
         None,
         None,
@@ -151,19 +141,17 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
 
         shader_program.bind();
-        //_pos_attrib.enable();
-        //_tex_attrib.enable();
         _vao.bind();
-        shader_program.uniform_matrix_4fv("proj", &window.camera_handle().proj());
-        shader_program.uniform_matrix_4fv("view", &window.camera_handle().view());
-        shader_program.uniform_3fv("color", &vector![1.0, 1.0, 1.0]);
-        shader_program.uniform_3fv("light_color", &vector![1.0, 1.0, 1.0]);
-        shader_program.uniform_3fv("light_pos", &light.pos());
-        shader_program.uniform_3fv("light_pos2", &light2.pos());
-        shader_program.uniform_3fv("view_pos", &window.camera_handle().pos());
+        shader_program.uniform_matrix_4fv("proj", window.camera_handle().proj());
+        shader_program.uniform_matrix_4fv("view", window.camera_handle().view());
+        shader_program.uniform_3fv("color", vector_n![1.0, 1.0, 1.0]);
+        shader_program.uniform_3fv("light_color", vector_n![1.0, 1.0, 1.0]);
+        shader_program.uniform_3fv("light_pos", light.pos());
+        shader_program.uniform_3fv("light_pos2", light2.pos());
+        shader_program.uniform_3fv("view_pos", window.camera_handle().pos());
 
         cube_pos.iter().for_each(|pos| {
-            let m = Matrix::translation(pos.clone())
+            let m = Mat4::translation(*pos)
                      //* Matrix::translation((
                      //    translate.0.parse::<f32>().unwrap_or(0.0),
                      //    translate.1.parse::<f32>().unwrap_or(0.0),
@@ -175,20 +163,18 @@ fn main() -> Result<(), Box<dyn Error>> {
                      //    scale.2.parse::<f32>().unwrap_or(1.0),
                      //))
                      ;
-            shader_program.uniform_matrix_4fv("model", &m);
-            shader_program.uniform_matrix_3fv("normal", &m.to_normal());
+            shader_program.uniform_matrix_4fv("model", m);
+            shader_program.uniform_matrix_3fv("normal", m.to_normal());
 
             shader_program.uniform_2dtex("tex", textures[main_texture]);
             unsafe { gl::DrawArrays(gl::TRIANGLES, 0, 36) }
         });
 
         _vao.unbind();
-        _pos_attrib.disable();
-        _tex_attrib.disable();
 
         println!("Draw cube");
         light.draw(window.camera_handle(), &mut light_shader);
-        light2.set_pos(vector!(-light.pos()[0], light.pos()[1], light.pos()[2]));
+        light2.set_pos(vector_n!(-light.pos()[0], light.pos()[1], light.pos()[2]));
         light2.draw(window.camera_handle(), &mut light_shader);
 
         window.begin_ui();
@@ -1,5 +1,22 @@
+pub mod batch;
+pub mod fixed;
+pub mod geometry;
+pub mod interpolation;
 pub mod matrix;
+pub mod matrix_n;
+pub mod noise;
+pub mod quaternion;
+pub mod random;
+pub mod spline;
+pub mod transform;
 pub mod vector;
+pub mod vector_n;
 
+pub use interpolation::*;
 pub use matrix::*;
+pub use matrix_n::*;
+pub use quaternion::*;
+pub use spline::*;
+pub use transform::*;
 pub use vector::*;
+pub use vector_n::*;
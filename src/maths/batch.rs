@@ -0,0 +1,191 @@
+// Bulk transforms over vertex data, in place and without allocating.
+//
+// Streams are interleaved `f32` buffers like the ones uploaded to a vertex
+// `Buffer<f32>`: `stride` floats per vertex with the attribute starting
+// `offset` floats in.
+// On x86_64 the kernels use SSE, which is part of the baseline target, so no
+// runtime detection is needed. Other targets fall back to scalar code.
+
+use super::geometry::Aabb;
+use super::{Mat3, Mat4, Vec3};
+
+impl Mat4 {
+    /// `transform_point` on every point. The bottom row is ignored, so this
+    /// is for affine matrices.
+    pub fn transform_points(&self, points: &mut [Vec3]) {
+        let kernel = Kernel::new(self);
+        for p in points {
+            kernel.apply(p.as_mut_slice(), 1.);
+        }
+    }
+
+    /// `transform_vector` on every vector.
+    pub fn transform_vectors(&self, vectors: &mut [Vec3]) {
+        let kernel = Kernel::new(self);
+        for v in vectors {
+            kernel.apply(v.as_mut_slice(), 0.);
+        }
+    }
+
+    /// `transform_points` over the 3 floats at `offset` in each vertex.
+    pub fn transform_points_strided(&self, data: &mut [f32], stride: usize, offset: usize) {
+        let kernel = Kernel::new(self);
+        for_each_attribute(data, stride, offset, |v| kernel.apply(v, 1.));
+    }
+
+    /// `transform_vectors` over the 3 floats at `offset` in each vertex.
+    pub fn transform_vectors_strided(&self, data: &mut [f32], stride: usize, offset: usize) {
+        let kernel = Kernel::new(self);
+        for_each_attribute(data, stride, offset, |v| kernel.apply(v, 0.));
+    }
+}
+
+impl Mat3 {
+    /// Multiplies every normal by `self` and renormalizes. `self` is a
+    /// normal matrix, e.g. from `Mat4::to_normal`.
+    pub fn transform_normals(&self, normals: &mut [Vec3]) {
+        let kernel = Kernel::from_mat3(self);
+        for n in normals {
+            kernel.apply_normal(n.as_mut_slice());
+        }
+    }
+
+    /// `transform_normals` over the 3 floats at `offset` in each vertex.
+    pub fn transform_normals_strided(&self, data: &mut [f32], stride: usize, offset: usize) {
+        let kernel = Kernel::from_mat3(self);
+        for_each_attribute(data, stride, offset, |n| kernel.apply_normal(n));
+    }
+}
+
+impl Aabb {
+    /// Bounds of the 3 floats at `offset` in each vertex, `None` for an
+    /// empty buffer.
+    pub fn from_strided(data: &[f32], stride: usize, offset: usize) -> Option<Self> {
+        check_layout(data.len(), stride, offset);
+        let mut vertices = data.chunks_exact(stride).map(|v| &v[offset..offset + 3]);
+        let first = vertices.next()?;
+        let (min, max) = bounds(first, vertices);
+        Some(Aabb::new(Vec3::from(min), Vec3::from(max)))
+    }
+}
+
+fn check_layout(len: usize, stride: usize, offset: usize) {
+    assert!(
+        offset + 3 <= stride,
+        "Attribute at {} doesn't fit in a stride of {}",
+        offset,
+        stride
+    );
+    assert_eq!(len % stride, 0, "Buffer isn't a whole number of vertices");
+}
+
+fn for_each_attribute(
+    data: &mut [f32],
+    stride: usize,
+    offset: usize,
+    mut f: impl FnMut(&mut [f32]),
+) {
+    check_layout(data.len(), stride, offset);
+    for vertex in data.chunks_exact_mut(stride) {
+        f(&mut vertex[offset..offset + 3]);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// Matrix columns ready for `column * x + column * y + ...` evaluation.
+struct Kernel {
+    #[cfg(target_arch = "x86_64")]
+    cols: [__m128; 4],
+    #[cfg(not(target_arch = "x86_64"))]
+    cols: [[f32; 4]; 4],
+}
+
+impl Kernel {
+    fn new(m: &Mat4) -> Self {
+        let cols: [[f32; 4]; 4] = std::array::from_fn(|j| *m.col(j).as_array());
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: SSE is always available on x86_64, the loads are unaligned
+        let cols = unsafe { cols.map(|c| _mm_loadu_ps(c.as_ptr())) };
+        Self { cols }
+    }
+
+    fn from_mat3(m: &Mat3) -> Self {
+        Self::new(&Mat4::from(std::array::from_fn(|i| {
+            std::array::from_fn(|j| if i < 3 && j < 3 { m[i][j] } else { 0. })
+        })))
+    }
+
+    /// `v = M * (v, w)`, dropping the result's 4th component.
+    #[cfg(target_arch = "x86_64")]
+    fn apply(&self, v: &mut [f32], w: f32) {
+        let mut out = [0.; 4];
+        // SAFETY: SSE is always available on x86_64, `out` has 4 floats
+        unsafe {
+            let r = _mm_add_ps(
+                _mm_add_ps(
+                    _mm_mul_ps(self.cols[0], _mm_set1_ps(v[0])),
+                    _mm_mul_ps(self.cols[1], _mm_set1_ps(v[1])),
+                ),
+                _mm_add_ps(
+                    _mm_mul_ps(self.cols[2], _mm_set1_ps(v[2])),
+                    _mm_mul_ps(self.cols[3], _mm_set1_ps(w)),
+                ),
+            );
+            _mm_storeu_ps(out.as_mut_ptr(), r);
+        }
+        v.copy_from_slice(&out[..3]);
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    fn apply(&self, v: &mut [f32], w: f32) {
+        let c = &self.cols;
+        let (x, y, z) = (v[0], v[1], v[2]);
+        for i in 0..3 {
+            v[i] = c[0][i] * x + c[1][i] * y + c[2][i] * z + c[3][i] * w;
+        }
+    }
+
+    fn apply_normal(&self, n: &mut [f32]) {
+        self.apply(n, 0.);
+        let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        if len > 0. {
+            n.iter_mut().for_each(|x| *x /= len);
+        }
+    }
+}
+
+/// Component-wise min and max of `first` and `rest`.
+#[cfg(target_arch = "x86_64")]
+fn bounds<'a>(first: &[f32], rest: impl Iterator<Item = &'a [f32]>) -> ([f32; 3], [f32; 3]) {
+    // SAFETY: SSE is always available on x86_64, the stores write 4 floats
+    // into 4 float arrays
+    unsafe {
+        let load = |v: &[f32]| _mm_set_ps(0., v[2], v[1], v[0]);
+        let mut min = load(first);
+        let mut max = min;
+        for v in rest {
+            let v = load(v);
+            min = _mm_min_ps(min, v);
+            max = _mm_max_ps(max, v);
+        }
+        let (mut lo, mut hi) = ([0.; 4], [0.; 4]);
+        _mm_storeu_ps(lo.as_mut_ptr(), min);
+        _mm_storeu_ps(hi.as_mut_ptr(), max);
+        ([lo[0], lo[1], lo[2]], [hi[0], hi[1], hi[2]])
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn bounds<'a>(first: &[f32], rest: impl Iterator<Item = &'a [f32]>) -> ([f32; 3], [f32; 3]) {
+    let mut min = [first[0], first[1], first[2]];
+    let mut max = min;
+    for v in rest {
+        for i in 0..3 {
+            min[i] = min[i].min(v[i]);
+            max[i] = max[i].max(v[i]);
+        }
+    }
+    (min, max)
+}
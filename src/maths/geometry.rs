@@ -0,0 +1,19 @@
+pub mod aabb;
+pub mod frustum;
+pub mod plane;
+pub mod polygon;
+pub mod ray;
+pub mod segment;
+pub mod sphere;
+pub mod triangle;
+pub mod triangulate;
+
+pub use aabb::*;
+pub use frustum::*;
+pub use plane::*;
+pub use polygon::*;
+pub use ray::*;
+pub use segment::*;
+pub use sphere::*;
+pub use triangle::*;
+pub use triangulate::*;
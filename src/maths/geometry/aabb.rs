@@ -0,0 +1,86 @@
+use crate::maths::{Mat4, Vec3};
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center(center: Vec3, half_extents: Vec3) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    /// `None` for an empty iterator.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, p| aabb.expand(p)))
+    }
+
+    pub fn min(&self) -> Vec3 {
+        self.min
+    }
+
+    pub fn max(&self) -> Vec3 {
+        self.max
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Smallest box containing `self` and `p`.
+    pub fn expand(&self, p: &Vec3) -> Self {
+        Self::new(self.min.zip_map(p, f32::min), self.max.zip_map(p, f32::max))
+    }
+
+    /// Smallest box containing both boxes.
+    pub fn merge(&self, other: &Self) -> Self {
+        Self::new(
+            self.min.zip_map(&other.min, f32::min),
+            self.max.zip_map(&other.max, f32::max),
+        )
+    }
+
+    pub fn contains(&self, p: &Vec3) -> bool {
+        (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    pub fn closest_point(&self, p: &Vec3) -> Vec3 {
+        Vec3::from(std::array::from_fn(|i| {
+            p[i].clamp(self.min[i], self.max[i])
+        }))
+    }
+
+    /// Box around the transformed box. Accepts a `Mat4` or a 4x4 `Matrix`.
+    pub fn transform<M: Into<Mat4>>(&self, m: M) -> Self {
+        // Arvo's method: each output axis is the translation plus the
+        // extremes of each matrix entry times the input range
+        let m = m.into();
+        let mut min = m.col(3).truncate();
+        let mut max = min;
+        for i in 0..3 {
+            for j in 0..3 {
+                let a = m[i][j] * self.min[j];
+                let b = m[i][j] * self.max[j];
+                min[i] += a.min(b);
+                max[i] += a.max(b);
+            }
+        }
+        Self::new(min, max)
+    }
+}
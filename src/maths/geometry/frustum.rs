@@ -0,0 +1,82 @@
+use super::{Aabb, Plane, Sphere};
+use crate::maths::{DepthRange, Mat4, Vec3, Vec4};
+
+/// Planes with normals pointing inside, in the order left, right, bottom,
+/// top, near, far. Infinite projections have no far plane.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Frustum {
+    planes: [Plane; 6],
+    len: usize,
+}
+
+impl Frustum {
+    pub fn new(planes: [Plane; 6]) -> Self {
+        Self { planes, len: 6 }
+    }
+
+    /// Extracts the planes from a `proj * view` matrix (Gribb & Hartmann),
+    /// with `depth` the projection's depth convention. With a projection
+    /// alone the planes are in view space.
+    pub fn from_matrix<M: Into<Mat4>>(m: M, depth: DepthRange) -> Self {
+        let m = m.into();
+        let (r0, r1, r2, r3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        // Clip space depth runs from near * w to far * w
+        let (near, far) = depth.near_far();
+        let sign = if far > near { 1. } else { -1. };
+        let far_plane = (r3 * far - r2) * sign;
+        let mut frustum = Self::new([
+            Plane::from(r3 + r0),
+            Plane::from(r3 - r0),
+            Plane::from(r3 + r1),
+            Plane::from(r3 - r1),
+            Plane::from((r2 - r3 * near) * sign),
+            Plane::from(far_plane),
+        ]);
+        if is_degenerate(&far_plane) {
+            frustum.len = 5;
+        }
+        frustum
+    }
+
+    pub fn planes(&self) -> &[Plane] {
+        &self.planes[..self.len]
+    }
+
+    /// `None` for an infinite projection.
+    pub fn far(&self) -> Option<&Plane> {
+        self.planes().get(5)
+    }
+
+    pub fn contains(&self, p: &Vec3) -> bool {
+        self.planes().iter().all(|plane| plane.distance(p) >= 0.)
+    }
+
+    /// Conservative: may report spheres near the frustum's corners as
+    /// intersecting.
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.planes()
+            .iter()
+            .all(|plane| plane.distance(&sphere.center()) >= -sphere.radius())
+    }
+
+    /// Conservative like `intersects_sphere`. Tests the box corner furthest
+    /// along each plane normal.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes().iter().all(|plane| {
+            let n = plane.normal();
+            let corner = Vec3::from(std::array::from_fn(|i| {
+                if n[i] >= 0. {
+                    aabb.max()[i]
+                } else {
+                    aabb.min()[i]
+                }
+            }));
+            plane.distance(&corner) >= 0.
+        })
+    }
+}
+
+/// The far plane of an infinite projection, `(0, 0, 0, d)` up to rounding.
+fn is_degenerate(plane: &Vec4) -> bool {
+    plane.truncate().abs() <= 1e-6 * plane[3].abs()
+}
@@ -0,0 +1,64 @@
+use crate::maths::{Vec3, Vec4};
+
+/// Points `p` with `normal * p + d == 0`. The normal points to the positive
+/// side.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Plane {
+    normal: Vec3,
+    d: f32,
+}
+
+impl Plane {
+    /// `normal` gets normalized.
+    pub fn new(normal: Vec3, d: f32) -> Self {
+        let len = normal.abs();
+        Self {
+            normal: normal / len,
+            d: d / len,
+        }
+    }
+
+    pub fn from_point_normal(point: &Vec3, normal: &Vec3) -> Self {
+        let normal = normal.unit();
+        Self {
+            normal,
+            d: -(normal * point),
+        }
+    }
+
+    /// Counter-clockwise points give a normal facing the viewer.
+    pub fn from_points(a: &Vec3, b: &Vec3, c: &Vec3) -> Self {
+        Self::from_point_normal(a, &(b - a).cross(&(c - a)))
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    pub fn d(&self) -> f32 {
+        self.d
+    }
+
+    /// Signed distance, positive on the side the normal points to.
+    pub fn distance(&self, p: &Vec3) -> f32 {
+        self.normal * p + self.d
+    }
+
+    pub fn closest_point(&self, p: &Vec3) -> Vec3 {
+        p - self.normal * self.distance(p)
+    }
+
+    pub fn flip(&self) -> Self {
+        Self {
+            normal: -self.normal,
+            d: -self.d,
+        }
+    }
+}
+
+impl From<Vec4> for Plane {
+    /// `(a, b, c, d)` coefficients of `ax + by + cz + d = 0`.
+    fn from(v: Vec4) -> Self {
+        Self::new(v.truncate(), v[3])
+    }
+}
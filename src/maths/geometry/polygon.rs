@@ -0,0 +1,143 @@
+use super::segment::orient;
+use super::Segment2;
+use crate::maths::Vec2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Simple 2D polygon. The last vertex connects back to the first.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Polygon2 {
+    vertices: Vec<Vec2>,
+}
+
+impl Polygon2 {
+    pub fn new(vertices: Vec<Vec2>) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Segment2> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| Segment2::new(self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Positive for counter-clockwise vertices.
+    pub fn signed_area(&self) -> f32 {
+        let n = self.vertices.len();
+        let origin = match self.vertices.first() {
+            Some(v) => v,
+            None => return 0.,
+        };
+        // Fan from the first vertex keeps the terms small for maps far from
+        // the origin
+        let twice: f64 = (1..n.saturating_sub(1))
+            .map(|i| orient(origin, &self.vertices[i], &self.vertices[i + 1]))
+            .sum();
+        (twice / 2.) as f32
+    }
+
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    /// `None` for polygons with zero area.
+    pub fn winding(&self) -> Option<Winding> {
+        let area = self.signed_area();
+        if area > 0. {
+            Some(Winding::CounterClockwise)
+        } else if area < 0. {
+            Some(Winding::Clockwise)
+        } else {
+            None
+        }
+    }
+
+    pub fn reverse(&mut self) {
+        self.vertices.reverse();
+    }
+
+    /// Reverses the vertices if needed. Degenerate polygons are left alone.
+    pub fn set_winding(&mut self, winding: Winding) {
+        if self.winding().is_some_and(|w| w != winding) {
+            self.reverse();
+        }
+    }
+
+    pub fn is_convex(&self) -> bool {
+        let n = self.vertices.len();
+        let mut sign = 0.;
+        for i in 0..n {
+            let o = orient(
+                &self.vertices[i],
+                &self.vertices[(i + 1) % n],
+                &self.vertices[(i + 2) % n],
+            );
+            if o != 0. {
+                if o * sign < 0. {
+                    return false;
+                }
+                sign = o;
+            }
+        }
+        true
+    }
+
+    /// Winding number test, exact in the same way as `Segment2::side`.
+    /// Points on the boundary are inside.
+    pub fn contains(&self, p: &Vec2) -> bool {
+        let mut winding = 0;
+        for edge in self.edges() {
+            let (a, b) = (edge.start(), edge.end());
+            let o = orient(&a, &b, p);
+            if o == 0.
+                && a[0].min(b[0]) <= p[0]
+                && p[0] <= a[0].max(b[0])
+                && a[1].min(b[1]) <= p[1]
+                && p[1] <= a[1].max(b[1])
+            {
+                return true;
+            }
+            if a[1] <= p[1] {
+                if b[1] > p[1] && o > 0. {
+                    winding += 1;
+                }
+            } else if b[1] <= p[1] && o < 0. {
+                winding -= 1;
+            }
+        }
+        winding != 0
+    }
+
+    /// Closest point on the boundary.
+    pub fn closest_point(&self, p: &Vec2) -> Option<Vec2> {
+        self.edges()
+            .map(|edge| edge.closest_point(p))
+            .min_by(|a, b| (p - a).abs().total_cmp(&(p - b).abs()))
+    }
+
+    /// Distance to the boundary, `None` for an empty polygon.
+    pub fn distance(&self, p: &Vec2) -> Option<f32> {
+        self.closest_point(p).map(|c| (p - c).abs())
+    }
+}
+
+impl From<Vec<Vec2>> for Polygon2 {
+    fn from(vertices: Vec<Vec2>) -> Self {
+        Self::new(vertices)
+    }
+}
@@ -0,0 +1,109 @@
+use super::{Aabb, Plane, Sphere, Triangle};
+use crate::maths::Vec3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    origin: Vec3,
+    direction: Vec3,
+}
+
+impl Ray {
+    /// `direction` gets normalized, so hit distances are in world units.
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.unit(),
+        }
+    }
+
+    pub fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    pub fn direction(&self) -> Vec3 {
+        self.direction
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// Closest point on the ray, never behind the origin.
+    pub fn closest_point(&self, p: &Vec3) -> Vec3 {
+        self.at(((p - self.origin) * self.direction).max(0.))
+    }
+
+    // Intersections return the distance along the ray to the first hit in
+    // front of the origin.
+
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denom = plane.normal() * self.direction;
+        if denom.abs() <= f32::EPSILON {
+            return None;
+        }
+        let t = -plane.distance(&self.origin) / denom;
+        (t >= 0.).then_some(t)
+    }
+
+    /// Slab test. Returns 0 if the origin is inside the box.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut t_min = 0_f32;
+        let mut t_max = f32::INFINITY;
+        for i in 0..3 {
+            let inv = 1. / self.direction[i];
+            let mut t0 = (aabb.min()[i] - self.origin[i]) * inv;
+            let mut t1 = (aabb.max()[i] - self.origin[i]) * inv;
+            if inv < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN (origin on a slab face with a parallel direction) keeps
+            // the current bounds
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+
+    /// Möller–Trumbore, hits both faces.
+    pub fn intersect_triangle(&self, triangle: &Triangle) -> Option<f32> {
+        let [a, b, c] = triangle.vertices();
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(&edge2);
+        let det = edge1 * p;
+        if det.abs() <= f32::EPSILON {
+            return None;
+        }
+        let inv_det = 1. / det;
+        let s = self.origin - a;
+        let u = (s * p) * inv_det;
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+        let q = s.cross(&edge1);
+        let v = (self.direction * q) * inv_det;
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+        let t = (edge2 * q) * inv_det;
+        (t >= 0.).then_some(t)
+    }
+
+    /// Returns 0 if the origin is inside the sphere.
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        let oc = self.origin - sphere.center();
+        let b = oc * self.direction;
+        let c = oc * oc - sphere.radius() * sphere.radius();
+        if c <= 0. {
+            return Some(0.);
+        }
+        let discriminant = b * b - c;
+        if b > 0. || discriminant < 0. {
+            return None;
+        }
+        Some(-b - discriminant.sqrt())
+    }
+}
@@ -0,0 +1,172 @@
+use crate::maths::Vec2;
+
+/// Which side of a directed line a point lies on. For a linedef going from
+/// `v1` to `v2` Doom's front side is `Right`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Left,
+    Right,
+    On,
+}
+
+/// Result of `Segment2::intersect`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SegmentIntersection {
+    Point(Vec2),
+    /// Collinear segments sharing more than a point.
+    Overlap(Segment2),
+}
+
+/// Twice the signed area of `(a, b, c)`, positive when counter-clockwise.
+/// Evaluated in f64, which makes the sign exact for integer coordinates up
+/// to 2^24, so map vertices are classified consistently.
+pub(super) fn orient(a: &Vec2, b: &Vec2, c: &Vec2) -> f64 {
+    let (ax, ay) = (a[0] as f64, a[1] as f64);
+    let (bx, by) = (b[0] as f64, b[1] as f64);
+    let (cx, cy) = (c[0] as f64, c[1] as f64);
+    (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+}
+
+/// 2D line segment from `start` to `end`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Segment2 {
+    start: Vec2,
+    end: Vec2,
+}
+
+impl Segment2 {
+    pub fn new(start: Vec2, end: Vec2) -> Self {
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> Vec2 {
+        self.start
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.end
+    }
+
+    /// `end - start`, not normalized.
+    pub fn direction(&self) -> Vec2 {
+        self.end - self.start
+    }
+
+    pub fn length(&self) -> f32 {
+        self.direction().abs()
+    }
+
+    pub fn reverse(&self) -> Self {
+        Self::new(self.end, self.start)
+    }
+
+    /// Side of the infinite line through the segment. Points closer to the
+    /// line than `eps` are `On`; with `eps == 0.` the test is exact (see
+    /// `orient`).
+    pub fn side(&self, p: &Vec2, eps: f32) -> Side {
+        let o = orient(&self.start, &self.end, p);
+        if o.abs() <= eps as f64 * self.length() as f64 {
+            Side::On
+        } else if o > 0. {
+            Side::Left
+        } else {
+            Side::Right
+        }
+    }
+
+    pub fn closest_point(&self, p: &Vec2) -> Vec2 {
+        let d = self.direction();
+        let len2 = d * d;
+        if len2 == 0. {
+            return self.start;
+        }
+        let t = ((p - self.start) * d / len2).clamp(0., 1.);
+        self.start + d * t
+    }
+
+    pub fn distance(&self, p: &Vec2) -> f32 {
+        (p - self.closest_point(p)).abs()
+    }
+
+    /// Endpoints within `eps` of the other segment count as touching it.
+    /// With `eps == 0.` crossing and touching are decided exactly; only the
+    /// returned point is rounded.
+    pub fn intersect(&self, other: &Self, eps: f32) -> Option<SegmentIntersection> {
+        // Degenerate segments are points
+        if self.start == self.end {
+            return (other.distance(&self.start) <= eps)
+                .then_some(SegmentIntersection::Point(self.start));
+        }
+        if other.start == other.end {
+            return (self.distance(&other.start) <= eps)
+                .then_some(SegmentIntersection::Point(other.start));
+        }
+
+        let s1 = self.side(&other.start, eps);
+        let s2 = self.side(&other.end, eps);
+        if s1 == Side::On && s2 == Side::On {
+            return self.intersect_collinear(other, eps);
+        }
+        if s1 == s2 {
+            return None;
+        }
+        let s3 = other.side(&self.start, eps);
+        let s4 = other.side(&self.end, eps);
+        if s3 == s4 {
+            return None;
+        }
+
+        // Prefer an endpoint when one is on the other segment, so shared
+        // vertices come back unrounded
+        let point = if s1 == Side::On {
+            other.start
+        } else if s2 == Side::On {
+            other.end
+        } else if s3 == Side::On {
+            self.start
+        } else if s4 == Side::On {
+            self.end
+        } else {
+            let r = self.direction();
+            let t =
+                (other.start - self.start).cross(&other.direction()) / r.cross(&other.direction());
+            self.start + r * t
+        };
+        Some(SegmentIntersection::Point(point))
+    }
+
+    pub fn intersects(&self, other: &Self, eps: f32) -> bool {
+        self.intersect(other, eps).is_some()
+    }
+
+    fn intersect_collinear(&self, other: &Self, eps: f32) -> Option<SegmentIntersection> {
+        let len = self.length();
+        let dir = self.direction() / len;
+        let t0 = (other.start - self.start) * dir;
+        let t1 = (other.end - self.start) * dir;
+        let lo = t0.min(t1).max(0.);
+        let hi = t0.max(t1).min(len);
+        if lo > hi + eps {
+            None
+        } else if hi - lo <= eps {
+            let t = (lo + hi) / 2.;
+            Some(SegmentIntersection::Point(self.point_at(t, len)))
+        } else {
+            Some(SegmentIntersection::Overlap(Self::new(
+                self.point_at(lo, len),
+                self.point_at(hi, len),
+            )))
+        }
+    }
+
+    /// Point `t` units along the segment, snapping to the endpoints.
+    fn point_at(&self, t: f32, len: f32) -> Vec2 {
+        if t <= 0. {
+            self.start
+        } else if t >= len {
+            self.end
+        } else {
+            self.start + self.direction() * (t / len)
+        }
+    }
+}
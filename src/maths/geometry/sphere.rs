@@ -0,0 +1,49 @@
+use super::Aabb;
+use crate::maths::Vec3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sphere {
+    center: Vec3,
+    radius: f32,
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        self.center
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn contains(&self, p: &Vec3) -> bool {
+        (p - self.center).abs() <= self.radius
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        (other.center - self.center).abs() <= self.radius + other.radius
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.contains(&aabb.closest_point(&self.center))
+    }
+
+    /// Closest point on the surface, or `p` itself if it is inside.
+    pub fn closest_point(&self, p: &Vec3) -> Vec3 {
+        let offset = p - self.center;
+        let dist = offset.abs();
+        if dist <= self.radius {
+            *p
+        } else {
+            self.center + offset * (self.radius / dist)
+        }
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        Aabb::from_center(self.center, Vec3::new(self.radius))
+    }
+}
@@ -0,0 +1,94 @@
+use super::{Aabb, Plane};
+use crate::maths::Vec3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Triangle {
+    vertices: [Vec3; 3],
+}
+
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Self {
+            vertices: [a, b, c],
+        }
+    }
+
+    pub fn vertices(&self) -> [Vec3; 3] {
+        self.vertices
+    }
+
+    /// Unit normal, facing the viewer for counter-clockwise vertices.
+    pub fn normal(&self) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(&(c - a)).unit()
+    }
+
+    pub fn area(&self) -> f32 {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(&(c - a)).abs() / 2.
+    }
+
+    pub fn plane(&self) -> Plane {
+        let [a, b, c] = self.vertices;
+        Plane::from_points(&a, &b, &c)
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        Aabb::from_points(&self.vertices).unwrap()
+    }
+
+    /// Barycentric coordinates `(u, v, w)` of `p` projected on the
+    /// triangle's plane, with `p = u * a + v * b + w * c`.
+    pub fn barycentric(&self, p: &Vec3) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        let (v0, v1, v2) = (b - a, c - a, p - a);
+        let d00 = v0 * v0;
+        let d01 = v0 * v1;
+        let d11 = v1 * v1;
+        let d20 = v2 * v0;
+        let d21 = v2 * v1;
+        let denom = d00 * d11 - d01 * d01;
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        Vec3::from([1. - v - w, v, w])
+    }
+
+    /// From Ericson's Real-Time Collision Detection, 5.1.5.
+    pub fn closest_point(&self, p: &Vec3) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        let ab = b - a;
+        let ac = c - a;
+        let ap = p - a;
+        let d1 = ab * ap;
+        let d2 = ac * ap;
+        if d1 <= 0. && d2 <= 0. {
+            return a;
+        }
+        let bp = p - b;
+        let d3 = ab * bp;
+        let d4 = ac * bp;
+        if d3 >= 0. && d4 <= d3 {
+            return b;
+        }
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0. && d1 >= 0. && d3 <= 0. {
+            return a + ab * (d1 / (d1 - d3));
+        }
+        let cp = p - c;
+        let d5 = ab * cp;
+        let d6 = ac * cp;
+        if d6 >= 0. && d5 <= d6 {
+            return c;
+        }
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0. && d2 >= 0. && d6 <= 0. {
+            return a + ac * (d2 / (d2 - d6));
+        }
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+        let denom = 1. / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+}
@@ -0,0 +1,589 @@
+use super::{Polygon2, Segment2, Side};
+use crate::maths::Vec2;
+
+/// Triangles covering a set of polygon loops, as indices into the loops'
+/// vertices concatenated in order.
+///
+/// Loops may be given in any winding. Nesting decides their role: a loop
+/// inside an odd number of other loops is a hole of its innermost
+/// container, so pillars inside sectors and islands inside those pillars
+/// both work. Output triangles are counter-clockwise.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Triangulation {
+    vertices: Vec<Vec2>,
+    indices: Vec<u32>,
+}
+
+impl Triangulation {
+    pub fn new(loops: &[Polygon2]) -> Self {
+        let vertices: Vec<Vec2> = loops.iter().flat_map(|l| l.vertices()).copied().collect();
+        let mut offsets = Vec::with_capacity(loops.len());
+        let mut offset = 0;
+        for l in loops {
+            offsets.push(offset);
+            offset += l.len();
+        }
+
+        // Loops with less than 3 vertices or no area bound nothing
+        let valid: Vec<usize> = (0..loops.len())
+            .filter(|&i| loops[i].len() >= 3 && loops[i].winding().is_some())
+            .collect();
+        let depth: Vec<usize> = valid
+            .iter()
+            .map(|&i| {
+                valid
+                    .iter()
+                    .filter(|&&j| j != i && inside(&loops[i], &loops[j]))
+                    .count()
+            })
+            .collect();
+
+        let mut indices = Vec::new();
+        for (k, &outer) in valid.iter().enumerate() {
+            if depth[k] % 2 == 1 {
+                continue;
+            }
+            let holes: Vec<usize> = valid
+                .iter()
+                .enumerate()
+                .filter(|&(h, &hole)| {
+                    depth[h] == depth[k] + 1 && inside(&loops[hole], &loops[outer])
+                })
+                .map(|(_, &hole)| hole)
+                .collect();
+
+            let mut earcut = Earcut::default();
+            let Some(mut start) = earcut.ring(&loops[outer], offsets[outer], true) else {
+                continue;
+            };
+            if !holes.is_empty() {
+                start =
+                    earcut.eliminate_holes(holes.iter().map(|&h| (&loops[h], offsets[h])), start);
+            }
+            earcut.triangulate(Some(start), 0);
+            indices.extend(earcut.triangles.iter().map(|&i| i as u32));
+        }
+
+        Self { vertices, indices }
+    }
+
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+
+    /// Three per triangle.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// For an index `Buffer<u32>`.
+    pub fn into_indices(self) -> Vec<u32> {
+        self.indices
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [Vec2; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| {
+            [
+                self.vertices[t[0] as usize],
+                self.vertices[t[1] as usize],
+                self.vertices[t[2] as usize],
+            ]
+        })
+    }
+}
+
+impl Polygon2 {
+    /// Indices into `vertices()`, see `Triangulation`.
+    pub fn triangulate(&self) -> Vec<u32> {
+        Triangulation::new(std::slice::from_ref(self)).into_indices()
+    }
+}
+
+/// Whether `inner` lies inside `outer`, judged from the first vertex (or
+/// edge midpoint) of `inner` that isn't on `outer`'s boundary. Loops only
+/// touching at vertices or along edges are handled that way.
+fn inside(inner: &Polygon2, outer: &Polygon2) -> bool {
+    let midpoints = inner.edges().map(|e| (e.start() + e.end()) * 0.5);
+    inner
+        .vertices()
+        .iter()
+        .copied()
+        .chain(midpoints)
+        .find(|p| !outer.edges().any(|e| on_segment(&e, p)))
+        .is_some_and(|p| outer.contains(&p))
+}
+
+fn on_segment(e: &Segment2, p: &Vec2) -> bool {
+    let (a, b) = (e.start(), e.end());
+    e.side(p, 0.) == Side::On
+        && a[0].min(b[0]) <= p[0]
+        && p[0] <= a[0].max(b[0])
+        && a[1].min(b[1]) <= p[1]
+        && p[1] <= a[1].max(b[1])
+}
+
+// Ear clipping with hole bridging, following mapbox's earcut. Rings are
+// circular doubly linked lists stored in an arena; removed nodes keep their
+// links so iteration can step off them, like the original.
+
+#[derive(Clone, Copy)]
+struct Node {
+    /// Index into the output vertices.
+    i: usize,
+    x: f64,
+    y: f64,
+    prev: usize,
+    next: usize,
+}
+
+#[derive(Default)]
+struct Earcut {
+    nodes: Vec<Node>,
+    triangles: Vec<usize>,
+}
+
+impl Earcut {
+    fn insert(&mut self, i: usize, v: &Vec2, last: Option<usize>) -> usize {
+        let p = self.nodes.len();
+        let mut node = Node {
+            i,
+            x: v[0] as f64,
+            y: v[1] as f64,
+            prev: p,
+            next: p,
+        };
+        if let Some(last) = last {
+            let next = self.nodes[last].next;
+            node.prev = last;
+            node.next = next;
+            self.nodes[next].prev = p;
+            self.nodes[last].next = p;
+        }
+        self.nodes.push(node);
+        p
+    }
+
+    fn remove(&mut self, p: usize) {
+        let Node { prev, next, .. } = self.nodes[p];
+        self.nodes[next].prev = prev;
+        self.nodes[prev].next = next;
+    }
+
+    fn next(&self, p: usize) -> usize {
+        self.nodes[p].next
+    }
+
+    fn prev(&self, p: usize) -> usize {
+        self.nodes[p].prev
+    }
+
+    /// Outer rings are stored counter-clockwise and holes clockwise, which
+    /// is what the tests below assume.
+    fn ring(&mut self, polygon: &Polygon2, offset: usize, outer: bool) -> Option<usize> {
+        let vertices = polygon.vertices();
+        let mut last = None;
+        if outer == (polygon.signed_area() > 0.) {
+            for (k, v) in vertices.iter().enumerate() {
+                last = Some(self.insert(offset + k, v, last));
+            }
+        } else {
+            for (k, v) in vertices.iter().enumerate().rev() {
+                last = Some(self.insert(offset + k, v, last));
+            }
+        }
+        let mut last = last?;
+        if self.equals(last, self.next(last)) {
+            self.remove(last);
+            last = self.next(last);
+        }
+        Some(last)
+    }
+
+    fn equals(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        a.x == b.x && a.y == b.y
+    }
+
+    /// Negative when `(p, q, r)` turns counter-clockwise, i.e. at convex
+    /// vertices of outer rings.
+    fn area(&self, p: usize, q: usize, r: usize) -> f64 {
+        let (p, q, r) = (&self.nodes[p], &self.nodes[q], &self.nodes[r]);
+        (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y)
+    }
+
+    /// Drops duplicate and collinear points between `start` and `end`.
+    fn filter(&mut self, start: usize, end: Option<usize>) -> usize {
+        let mut end = end.unwrap_or(start);
+        let mut p = start;
+        loop {
+            let mut again = false;
+            let (prev, next) = (self.prev(p), self.next(p));
+            if self.equals(p, next) || self.area(prev, p, next) == 0. {
+                self.remove(p);
+                p = prev;
+                end = prev;
+                if p == self.next(p) {
+                    break;
+                }
+                again = true;
+            } else {
+                p = next;
+            }
+            if !again && p == end {
+                break;
+            }
+        }
+        end
+    }
+
+    fn triangulate(&mut self, ear: Option<usize>, pass: u8) {
+        let Some(mut ear) = ear else { return };
+        let mut stop = ear;
+        while self.prev(ear) != self.next(ear) {
+            let (prev, next) = (self.prev(ear), self.next(ear));
+            if self.is_ear(ear) {
+                self.push_triangle(prev, ear, next);
+                self.remove(ear);
+                ear = self.next(next);
+                stop = ear;
+                continue;
+            }
+            ear = next;
+            if ear == stop {
+                match pass {
+                    // Try again without degenerate points
+                    0 => {
+                        let ear = self.filter(ear, None);
+                        self.triangulate(Some(ear), 1);
+                    }
+                    // Clip small self-intersections
+                    1 => {
+                        let ear = self.filter(ear, None);
+                        let ear = self.cure_local_intersections(ear);
+                        self.triangulate(Some(ear), 2);
+                    }
+                    // Split along a valid diagonal and start over on each half
+                    _ => self.split(ear),
+                }
+                break;
+            }
+        }
+    }
+
+    fn push_triangle(&mut self, a: usize, b: usize, c: usize) {
+        let (a, b, c) = (self.nodes[a].i, self.nodes[b].i, self.nodes[c].i);
+        self.triangles.extend([a, b, c]);
+    }
+
+    fn is_ear(&self, ear: usize) -> bool {
+        let (a, b, c) = (self.prev(ear), ear, self.next(ear));
+        if self.area(a, b, c) >= 0. {
+            return false;
+        }
+        let (na, nb, nc) = (&self.nodes[a], &self.nodes[b], &self.nodes[c]);
+        let x0 = na.x.min(nb.x).min(nc.x);
+        let y0 = na.y.min(nb.y).min(nc.y);
+        let x1 = na.x.max(nb.x).max(nc.x);
+        let y1 = na.y.max(nb.y).max(nc.y);
+        let mut p = self.next(c);
+        while p != a {
+            let np = &self.nodes[p];
+            if np.x >= x0
+                && np.x <= x1
+                && np.y >= y0
+                && np.y <= y1
+                && point_in_triangle(na, nb, nc, np.x, np.y)
+                && self.area(self.prev(p), p, self.next(p)) >= 0.
+            {
+                return false;
+            }
+            p = self.next(p);
+        }
+        true
+    }
+
+    fn cure_local_intersections(&mut self, start: usize) -> usize {
+        let mut start = start;
+        let mut p = start;
+        loop {
+            let a = self.prev(p);
+            let b = self.next(self.next(p));
+            if !self.equals(a, b)
+                && self.intersects(a, p, self.next(p), b)
+                && self.locally_inside(a, b)
+                && self.locally_inside(b, a)
+            {
+                self.push_triangle(a, p, b);
+                let next = self.next(p);
+                self.remove(p);
+                self.remove(next);
+                p = b;
+                start = b;
+            }
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+        self.filter(p, None)
+    }
+
+    fn split(&mut self, start: usize) {
+        let mut a = start;
+        loop {
+            let mut b = self.next(self.next(a));
+            while b != self.prev(a) {
+                if self.nodes[a].i != self.nodes[b].i && self.is_valid_diagonal(a, b) {
+                    let c = self.split_polygon(a, b);
+                    let a = self.filter(a, Some(self.next(a)));
+                    let c = self.filter(c, Some(self.next(c)));
+                    self.triangulate(Some(a), 0);
+                    self.triangulate(Some(c), 0);
+                    return;
+                }
+                b = self.next(b);
+            }
+            a = self.next(a);
+            if a == start {
+                break;
+            }
+        }
+    }
+
+    fn eliminate_holes<'a>(
+        &mut self,
+        holes: impl Iterator<Item = (&'a Polygon2, usize)>,
+        outer: usize,
+    ) -> usize {
+        let mut queue: Vec<usize> = holes
+            .filter_map(|(hole, offset)| {
+                let list = self.ring(hole, offset, false)?;
+                Some(self.leftmost(list))
+            })
+            .collect();
+        queue.sort_by(|&a, &b| self.nodes[a].x.total_cmp(&self.nodes[b].x));
+        queue
+            .into_iter()
+            .fold(outer, |outer, hole| self.eliminate_hole(hole, outer))
+    }
+
+    fn eliminate_hole(&mut self, hole: usize, outer: usize) -> usize {
+        let Some(bridge) = self.find_hole_bridge(hole, outer) else {
+            return outer;
+        };
+        let bridge_reverse = self.split_polygon(bridge, hole);
+        self.filter(bridge_reverse, Some(self.next(bridge_reverse)));
+        self.filter(bridge, Some(self.next(bridge)))
+    }
+
+    /// David Eberly's algorithm for finding a bridge between a hole and the
+    /// outer ring.
+    fn find_hole_bridge(&self, hole: usize, outer: usize) -> Option<usize> {
+        let (hx, hy) = (self.nodes[hole].x, self.nodes[hole].y);
+        let mut qx = f64::NEG_INFINITY;
+        let mut m = None;
+
+        // Find the segment left of the hole point closest to it on a
+        // horizontal ray
+        let mut p = outer;
+        loop {
+            let (np, nn) = (&self.nodes[p], &self.nodes[self.next(p)]);
+            if hy <= np.y && hy >= nn.y && nn.y != np.y {
+                let x = np.x + (hy - np.y) * (nn.x - np.x) / (nn.y - np.y);
+                if x <= hx && x > qx {
+                    qx = x;
+                    m = Some(if np.x < nn.x { p } else { self.next(p) });
+                    if x == hx {
+                        // The hole touches the outer segment
+                        return m;
+                    }
+                }
+            }
+            p = self.next(p);
+            if p == outer {
+                break;
+            }
+        }
+        let mut m = m?;
+
+        // Points inside the triangle (hole point, intersection, segment
+        // endpoint) could block the bridge, connect to the one with the
+        // smallest angle instead
+        let stop = m;
+        let (mx, my) = (self.nodes[m].x, self.nodes[m].y);
+        let mut tan_min = f64::INFINITY;
+        p = m;
+        loop {
+            let np = &self.nodes[p];
+            let (ax, cx) = if hy < my { (hx, qx) } else { (qx, hx) };
+            if hx >= np.x
+                && np.x >= mx
+                && hx != np.x
+                && point_in_triangle_xy(ax, hy, mx, my, cx, hy, np.x, np.y)
+            {
+                let tan = (hy - np.y).abs() / (hx - np.x);
+                if self.locally_inside(p, hole)
+                    && (tan < tan_min
+                        || (tan == tan_min
+                            && (np.x > self.nodes[m].x
+                                || (np.x == self.nodes[m].x && self.sector_contains_sector(m, p)))))
+                {
+                    m = p;
+                    tan_min = tan;
+                }
+            }
+            p = self.next(p);
+            if p == stop {
+                break;
+            }
+        }
+        Some(m)
+    }
+
+    /// Whether the sector in vertex `m` contains the sector in vertex `p`.
+    fn sector_contains_sector(&self, m: usize, p: usize) -> bool {
+        self.area(self.prev(m), m, self.prev(p)) < 0.
+            && self.area(self.next(p), m, self.next(m)) < 0.
+    }
+
+    fn leftmost(&self, start: usize) -> usize {
+        let mut p = start;
+        let mut leftmost = start;
+        loop {
+            let (np, nl) = (&self.nodes[p], &self.nodes[leftmost]);
+            if np.x < nl.x || (np.x == nl.x && np.y < nl.y) {
+                leftmost = p;
+            }
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+        leftmost
+    }
+
+    fn is_valid_diagonal(&self, a: usize, b: usize) -> bool {
+        let ib = self.nodes[b].i;
+        self.nodes[self.next(a)].i != ib
+            && self.nodes[self.prev(a)].i != ib
+            && !self.intersects_polygon(a, b)
+            && ((self.locally_inside(a, b)
+                && self.locally_inside(b, a)
+                && self.middle_inside(a, b)
+                && (self.area(self.prev(a), a, self.prev(b)) != 0.
+                    || self.area(a, self.prev(b), b) != 0.))
+                || (self.equals(a, b)
+                    && self.area(self.prev(a), a, self.next(a)) > 0.
+                    && self.area(self.prev(b), b, self.next(b)) > 0.))
+    }
+
+    fn intersects(&self, p1: usize, q1: usize, p2: usize, q2: usize) -> bool {
+        let sign = |v: f64| (v > 0.) as i8 - (v < 0.) as i8;
+        let o1 = sign(self.area(p1, q1, p2));
+        let o2 = sign(self.area(p1, q1, q2));
+        let o3 = sign(self.area(p2, q2, p1));
+        let o4 = sign(self.area(p2, q2, q1));
+        (o1 != o2 && o3 != o4)
+            || (o1 == 0 && self.on_segment(p1, p2, q1))
+            || (o2 == 0 && self.on_segment(p1, q2, q1))
+            || (o3 == 0 && self.on_segment(p2, p1, q2))
+            || (o4 == 0 && self.on_segment(p2, q1, q2))
+    }
+
+    /// For collinear `p`, `q`, `r`: whether `q` is on segment `pr`.
+    fn on_segment(&self, p: usize, q: usize, r: usize) -> bool {
+        let (p, q, r) = (&self.nodes[p], &self.nodes[q], &self.nodes[r]);
+        q.x <= p.x.max(r.x) && q.x >= p.x.min(r.x) && q.y <= p.y.max(r.y) && q.y >= p.y.min(r.y)
+    }
+
+    fn intersects_polygon(&self, a: usize, b: usize) -> bool {
+        let (ia, ib) = (self.nodes[a].i, self.nodes[b].i);
+        let mut p = a;
+        loop {
+            let next = self.next(p);
+            let (ip, inext) = (self.nodes[p].i, self.nodes[next].i);
+            if ip != ia && inext != ia && ip != ib && inext != ib && self.intersects(p, next, a, b)
+            {
+                return true;
+            }
+            p = next;
+            if p == a {
+                return false;
+            }
+        }
+    }
+
+    /// Whether the diagonal `ab` starts inside the polygon at `a`.
+    fn locally_inside(&self, a: usize, b: usize) -> bool {
+        let (prev, next) = (self.prev(a), self.next(a));
+        if self.area(prev, a, next) < 0. {
+            self.area(a, b, next) >= 0. && self.area(a, prev, b) >= 0.
+        } else {
+            self.area(a, b, prev) < 0. || self.area(a, next, b) < 0.
+        }
+    }
+
+    /// Whether the middle of the diagonal `ab` is inside the polygon.
+    fn middle_inside(&self, a: usize, b: usize) -> bool {
+        let px = (self.nodes[a].x + self.nodes[b].x) / 2.;
+        let py = (self.nodes[a].y + self.nodes[b].y) / 2.;
+        let mut inside = false;
+        let mut p = a;
+        loop {
+            let (np, nn) = (&self.nodes[p], &self.nodes[self.next(p)]);
+            if (np.y > py) != (nn.y > py)
+                && nn.y != np.y
+                && px < (nn.x - np.x) * (py - np.y) / (nn.y - np.y) + np.x
+            {
+                inside = !inside;
+            }
+            p = self.next(p);
+            if p == a {
+                return inside;
+            }
+        }
+    }
+
+    /// Links `a` to `b` with a pair of bridge edges, splitting the ring in
+    /// two. Returns the copy of `b` starting the second ring.
+    fn split_polygon(&mut self, a: usize, b: usize) -> usize {
+        let a2 = self.nodes.len();
+        let b2 = a2 + 1;
+        let (an, bp) = (self.next(a), self.prev(b));
+        let mut na2 = self.nodes[a];
+        let mut nb2 = self.nodes[b];
+
+        self.nodes[a].next = b;
+        self.nodes[b].prev = a;
+        na2.next = an;
+        self.nodes[an].prev = a2;
+        na2.prev = b2;
+        nb2.next = a2;
+        nb2.prev = bp;
+        self.nodes[bp].next = b2;
+
+        self.nodes.push(na2);
+        self.nodes.push(nb2);
+        b2
+    }
+}
+
+fn point_in_triangle(a: &Node, b: &Node, c: &Node, px: f64, py: f64) -> bool {
+    point_in_triangle_xy(a.x, a.y, b.x, b.y, c.x, c.y, px, py)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn point_in_triangle_xy(
+    ax: f64,
+    ay: f64,
+    bx: f64,
+    by: f64,
+    cx: f64,
+    cy: f64,
+    px: f64,
+    py: f64,
+) -> bool {
+    (cx - px) * (ay - py) >= (ax - px) * (cy - py)
+        && (ax - px) * (by - py) >= (bx - px) * (ay - py)
+        && (bx - px) * (cy - py) >= (cx - px) * (by - py)
+}
@@ -0,0 +1,110 @@
+use std::f32::consts::PI;
+
+/// `a` at `t = 0`, `b` at `t = 1`. `t` isn't clamped.
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Inverse of `lerp`: where `x` lies between `a` and `b`.
+pub fn inverse_lerp(a: f32, b: f32, x: f32) -> f32 {
+    (x - a) / (b - a)
+}
+
+/// Hermite step from 0 at `edge0` to 1 at `edge1`, like GLSL's.
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = inverse_lerp(edge0, edge1, x).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+/// Ken Perlin's variant of `smoothstep` with zero second derivatives at the
+/// edges.
+pub fn smootherstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = inverse_lerp(edge0, edge1, x).clamp(0., 1.);
+    t * t * t * (t * (6. * t - 15.) + 10.)
+}
+
+/// Easing curves mapping `[0, 1]` onto itself, see easings.net. Back and
+/// Elastic overshoot in between.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Easing {
+    Linear,
+    Quad,
+    Cubic,
+    Quart,
+    Quint,
+    Sine,
+    Expo,
+    Circ,
+    Back,
+    Elastic,
+    Bounce,
+}
+
+impl Easing {
+    /// Starts slow.
+    pub fn ease_in(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Easing::Linear => t,
+            Easing::Quad => t * t,
+            Easing::Cubic => t * t * t,
+            Easing::Quart => t * t * t * t,
+            Easing::Quint => t * t * t * t * t,
+            Easing::Sine => 1. - (t * PI / 2.).cos(),
+            Easing::Expo => {
+                if t == 0. {
+                    0.
+                } else {
+                    2_f32.powf(10. * t - 10.)
+                }
+            }
+            Easing::Circ => 1. - (1. - t * t).sqrt(),
+            Easing::Back => {
+                const C1: f32 = 1.70158;
+                const C3: f32 = C1 + 1.;
+                C3 * t * t * t - C1 * t * t
+            }
+            Easing::Elastic => {
+                if t == 0. || t == 1. {
+                    t
+                } else {
+                    let c4 = 2. * PI / 3.;
+                    -(2_f32.powf(10. * t - 10.)) * ((t * 10. - 10.75) * c4).sin()
+                }
+            }
+            Easing::Bounce => 1. - bounce_out(1. - t),
+        }
+    }
+
+    /// Ends slow, `ease_in` mirrored.
+    pub fn ease_out(self, t: f32) -> f32 {
+        1. - self.ease_in(1. - t)
+    }
+
+    /// `ease_in` for the first half, `ease_out` for the second.
+    pub fn ease_in_out(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        if t < 0.5 {
+            self.ease_in(2. * t) / 2.
+        } else {
+            0.5 + self.ease_out(2. * t - 1.) / 2.
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+    if t < 1. / D1 {
+        N1 * t * t
+    } else if t < 2. / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
@@ -4,7 +4,7 @@ use std::usize;
 
 use cgmath::num_traits::ToPrimitive;
 
-use super::Vector;
+use super::{lerp, DepthRange, Mat2, Mat3, Mat4, Vector};
 
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct Matrix {
@@ -142,19 +142,132 @@ impl Matrix {
         }
     }
 
+    pub fn swap_rows(&mut self, a: usize, b: usize) {
+        assert!(a < self.rows && b < self.rows);
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+
+    /// Closed form up to 4x4, LU decomposition above that.
     pub fn det(&self) -> f32 {
         assert!(self.is_square());
-        if self.rows() == 1 {
-            self[0][0]
-        } else {
-            (0..self.cols())
-                .map(|j| self[0][j] * self.cofactor(0, j))
-                .sum()
+        match self.rows() {
+            1 => self[0][0],
+            2 => Mat2::from(self).det(),
+            3 => Mat3::from(self).det(),
+            4 => Mat4::from(self).det(),
+            n => match self.lu(0.) {
+                Some((lu, _, sign)) => sign * (0..n).map(|i| lu[i][i]).product::<f32>(),
+                None => 0.,
+            },
         }
     }
 
+    /// Panics if the matrix is singular, see `try_inverse`.
     pub fn inverse(&self) -> Self {
-        self.comatrix().transpose() / self.det()
+        self.try_inverse().expect("Cannot invert a singular matrix")
+    }
+
+    /// Returns `None` for singular matrices instead of a matrix full of NaN
+    /// or inf. Closed form up to 4x4 (affine 4x4 matrices only invert their
+    /// 3x3 block), LU decomposition above that.
+    pub fn try_inverse(&self) -> Option<Self> {
+        assert!(self.is_square());
+        match self.rows() {
+            1 => {
+                let inverse = 1. / self[0][0];
+                inverse.is_finite().then(|| Self::new(1, 1, vec![inverse]))
+            }
+            2 => Mat2::from(self).try_inverse().map(Self::from),
+            3 => Mat3::from(self).try_inverse().map(Self::from),
+            4 => Mat4::from(self).try_inverse().map(Self::from),
+            n => {
+                let (lu, perm, _) = self.lu(self.singular_tolerance())?;
+                let mut inverse = Self::zeroes(n, n);
+                for j in 0..n {
+                    let e: Vec<f32> = (0..n).map(|i| if i == j { 1. } else { 0. }).collect();
+                    let col = Self::lu_solve(&lu, &perm, &e);
+                    for (i, x) in col.into_iter().enumerate() {
+                        inverse[i][j] = x;
+                    }
+                }
+                Some(inverse)
+            }
+        }
+    }
+
+    /// Inverse of a 4x4 affine matrix, i.e. one whose bottom row is
+    /// `[0, 0, 0, 1]`, such as a model or view matrix.
+    pub fn try_affine_inverse(&self) -> Option<Self> {
+        Mat4::from(self).try_affine_inverse().map(Self::from)
+    }
+
+    /// Solves `self * x = b`, `None` if the matrix is singular.
+    pub fn solve(&self, b: &Vector) -> Option<Vector> {
+        assert!(self.is_square());
+        assert_eq!(self.rows(), b.len());
+        let (lu, perm, _) = self.lu(self.singular_tolerance())?;
+        Some(Self::lu_solve(&lu, &perm, b.as_slice()).into())
+    }
+
+    fn singular_tolerance(&self) -> f32 {
+        let scale = self.data.iter().fold(0_f32, |acc, x| acc.max(x.abs()));
+        f32::EPSILON * scale * self.rows() as f32
+    }
+
+    /// LU decomposition with partial pivoting. L (unit diagonal omitted) and U
+    /// are packed in the returned matrix, along with the row permutation and
+    /// its sign. `None` if a pivot is not larger than `tolerance`.
+    fn lu(&self, tolerance: f32) -> Option<(Self, Vec<usize>, f32)> {
+        let n = self.rows();
+        let mut lu = self.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut sign = 1.;
+        for k in 0..n {
+            let p = (k..n)
+                .max_by(|&a, &b| lu[a][k].abs().total_cmp(&lu[b][k].abs()))
+                .unwrap();
+            if lu[p][k].abs() <= tolerance {
+                return None;
+            }
+            if p != k {
+                lu.swap_rows(p, k);
+                perm.swap(p, k);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                let factor = lu[i][k] / lu[k][k];
+                lu[i][k] = factor;
+                for j in k + 1..n {
+                    let u = lu[k][j];
+                    lu[i][j] -= factor * u;
+                }
+            }
+        }
+        Some((lu, perm, sign))
+    }
+
+    fn lu_solve(lu: &Self, perm: &[usize], b: &[f32]) -> Vec<f32> {
+        let n = lu.rows();
+        let mut x: Vec<f32> = perm.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            x[i] -= (0..i).map(|k| lu[i][k] * x[k]).sum::<f32>();
+        }
+        for i in (0..n).rev() {
+            x[i] = (x[i] - (i + 1..n).map(|k| lu[i][k] * x[k]).sum::<f32>()) / lu[i][i];
+        }
+        x
+    }
+
+    /// Entry-wise, `self` at `t = 0` and `other` at `t = 1`.
+    pub fn lerp(&self, other: &Matrix, t: f32) -> Self {
+        assert_eq!(self.rows(), other.rows());
+        assert_eq!(self.cols(), other.cols());
+        let data = (self.data.iter().zip(&other.data))
+            .map(|(&a, &b)| lerp(a, b, t))
+            .collect();
+        Matrix::new(self.rows(), self.cols(), data)
     }
 
     ///Takes a column matrix and turns it into a vector
@@ -188,111 +301,27 @@ impl Matrix {
     }
 
     pub fn scaling(values: Vector) -> Self {
-        assert_eq!(values.len(), 3);
-        Self {
-            data: vec![
-                values[0], 0., 0., 0., //row 1
-                0., values[1], 0., 0., //row 2
-                0., 0., values[2], 0., //row 3
-                0., 0., 0., 1., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        }
+        Mat4::scaling(values.into()).into()
     }
 
     pub fn translation(values: Vector) -> Self {
-        assert_eq!(values.len(), 3);
-        Self {
-            data: vec![
-                1., 0., 0., values[0], //row 1
-                0., 1., 0., values[1], //row 2
-                0., 0., 1., values[2], //row 3
-                0., 0., 0., 1., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        }
+        Mat4::translation(values.into()).into()
     }
 
     pub fn rotation(values: Vector) -> Self {
-        assert_eq!(values.len(), 3);
-        Self::rotation_z(values[2]) * Self::rotation_y(values[1]) * Self::rotation_x(values[0])
+        Mat4::rotation(values.into()).into()
     }
 
     pub fn rotation_x(angle: f32) -> Self {
-        Self {
-            data: vec![
-                1.,
-                0.,
-                0.,
-                0., //row 1
-                0.,
-                angle.cos(),
-                -angle.sin(),
-                0., //row 2
-                0.,
-                angle.sin(),
-                angle.cos(),
-                0., //row 3
-                0.,
-                0.,
-                0.,
-                1., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        }
+        Mat4::rotation_x(angle).into()
     }
 
     pub fn rotation_y(angle: f32) -> Self {
-        Self {
-            data: vec![
-                angle.cos(),
-                0.,
-                angle.sin(),
-                0., //row 1
-                0.,
-                1.,
-                0.,
-                0., //row 2
-                -angle.sin(),
-                0.,
-                angle.cos(),
-                0., //row 3
-                0.,
-                0.,
-                0.,
-                1., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        }
+        Mat4::rotation_y(angle).into()
     }
 
     pub fn rotation_z(angle: f32) -> Self {
-        Self {
-            data: vec![
-                angle.cos(),
-                -angle.sin(),
-                0.,
-                0., //row 1
-                angle.sin(),
-                angle.cos(),
-                0.,
-                0., //row 2
-                0.,
-                0.,
-                1.,
-                0., //row 3
-                0.,
-                0.,
-                0.,
-                1., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        }
+        Mat4::rotation_z(angle).into()
     }
 
     pub fn model(
@@ -309,14 +338,9 @@ impl Matrix {
         Self::identity(4)
     }
 
-    /// Returns the normal matrix from a model matrix.
+    /// Returns the normal matrix from a model matrix, see `Mat4::to_normal`.
     pub fn to_normal(&self) -> Self {
-        assert!(self.is_square());
-        assert_eq!(self.rows(), 4);
-        let mut m = self.clone();
-        m.remove_row(3);
-        m.remove_col(3);
-        m.inverse().transpose()
+        Mat4::from(self).to_normal().into()
     }
 
     pub fn projection_orthographic(
@@ -327,88 +351,55 @@ impl Matrix {
         near: f32,
         far: f32,
     ) -> Self {
-        Self {
-            data: vec![
-                2. / (right - left),
-                0.,
-                0.,
-                -(right + left) / (right - left), //row 1
-                0.,
-                2. / (top - bottom),
-                0.,
-                -(top + bottom) / (top - bottom), //row 2
-                0.,
-                0.,
-                -2. / (far - near),
-                -(far + near) / (far - near), //row 3
-                0.,
-                0.,
-                0.,
-                1., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        }
+        Mat4::projection_orthographic(left, right, bottom, top, near, far).into()
     }
 
     pub fn projection_perspective(fov: f32, aspect: f32, near: f32, far: f32) -> Self {
-        let tan_of_half_fov = (fov / 2.).tan();
-        Self {
-            data: vec![
-                1. / (aspect * tan_of_half_fov),
-                0.,
-                0.,
-                0., //row 1
-                0.,
-                1. / tan_of_half_fov,
-                0.,
-                0., //row 2
-                0.,
-                0.,
-                -(far + near) / (far - near),
-                -2. * far * near / (far - near), //row 3
-                0.,
-                0.,
-                -1.,
-                0., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        }
+        Mat4::projection_perspective(fov, aspect, near, far).into()
+    }
+
+    /// See `Mat4::projection_frustum`.
+    pub fn projection_frustum(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        Mat4::projection_frustum(left, right, bottom, top, near, far).into()
+    }
+
+    pub fn projection_perspective_infinite(fov: f32, aspect: f32, near: f32) -> Self {
+        Mat4::projection_perspective_infinite(fov, aspect, near).into()
+    }
+
+    /// See `Mat4::projection_perspective_reversed`.
+    pub fn projection_perspective_reversed(fov: f32, aspect: f32, near: f32, far: f32) -> Self {
+        Mat4::projection_perspective_reversed(fov, aspect, near, far).into()
+    }
+
+    pub fn projection_perspective_reversed_infinite(fov: f32, aspect: f32, near: f32) -> Self {
+        Mat4::projection_perspective_reversed_infinite(fov, aspect, near).into()
+    }
+
+    /// See `Mat4::oblique_near_plane`.
+    pub fn oblique_near_plane(&self, plane: &Vector, depth: DepthRange) -> Self {
+        Mat4::from(self)
+            .oblique_near_plane(&plane.into(), depth)
+            .into()
+    }
+
+    /// See `Mat4::jittered`.
+    pub fn jittered(&self, offset: &Vector) -> Self {
+        Mat4::from(self).jittered(&offset.into()).into()
     }
 
     /// position: Camera position
     /// target: Target position
     /// up: Up vector in world space
     pub fn look_at(position: &Vector, target: &Vector, up: &Vector) -> Self {
-        assert_eq!(position.len(), 3);
-        assert_eq!(target.len(), 3);
-        assert_eq!(up.len(), 3);
-        let direction = (position - target).unit();
-        let cam_right = up.unit().cross(&direction).unit();
-        let cam_up = direction.cross(&cam_right);
-        Self {
-            data: vec![
-                cam_right[0],
-                cam_right[1],
-                cam_right[2],
-                0., //row 1
-                cam_up[0],
-                cam_up[1],
-                cam_up[2],
-                0., //row 2
-                direction[0],
-                direction[1],
-                direction[2],
-                0., //row 3
-                0.,
-                0.,
-                0.,
-                1., //row 4
-            ],
-            rows: 4,
-            cols: 4,
-        } * Self::translation(-position)
+        Mat4::look_at(&position.into(), &target.into(), &up.into()).into()
     }
 }
 
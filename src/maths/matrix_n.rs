@@ -0,0 +1,721 @@
+use std::ops::{self, AddAssign, DivAssign, MulAssign, SubAssign};
+use std::ops::{Index, IndexMut};
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::num_traits::ToPrimitive;
+
+use super::{lerp, Matrix, Vec2, Vec3, Vec4, VectorN};
+
+/// Depth convention of a projection, i.e. the NDC depth of its near and far
+/// planes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DepthRange {
+    /// OpenGL's default, near at -1 and far at 1.
+    #[default]
+    NegativeOneToOne,
+    /// `glClipControl(.., GL_ZERO_TO_ONE)`, near at 0 and far at 1.
+    ZeroToOne,
+    /// Reversed-Z with `GL_ZERO_TO_ONE`, near at 1 and far at 0.
+    Reversed,
+}
+
+impl DepthRange {
+    pub fn near_far(self) -> (f32, f32) {
+        match self {
+            DepthRange::NegativeOneToOne => (-1., 1.),
+            DepthRange::ZeroToOne => (0., 1.),
+            DepthRange::Reversed => (1., 0.),
+        }
+    }
+}
+
+/// Fixed-size, stack-allocated counterpart of a square `Matrix`.
+/// Stored row-major like `Matrix`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct MatrixN<const N: usize> {
+    data: [[f32; N]; N],
+}
+
+pub type Mat2 = MatrixN<2>;
+pub type Mat3 = MatrixN<3>;
+pub type Mat4 = MatrixN<4>;
+
+#[macro_export]
+macro_rules! matrix_n {
+    ( $( $arr:expr ),* ) => {
+        $crate::maths::MatrixN::from([ $( $arr ),* ])
+    };
+}
+
+//Usual methods
+
+impl<const N: usize> MatrixN<N> {
+    pub fn identity() -> Self {
+        let mut m = Self::zeroes();
+        for i in 0..N {
+            m[i][i] = 1.0;
+        }
+        m
+    }
+
+    pub fn zeroes() -> Self {
+        Self { data: [[0.; N]; N] }
+    }
+
+    pub fn ones() -> Self {
+        Self { data: [[1.; N]; N] }
+    }
+
+    pub fn rows(&self) -> usize {
+        N
+    }
+
+    pub fn cols(&self) -> usize {
+        N
+    }
+
+    pub fn row(&self, i: usize) -> VectorN<N> {
+        VectorN::from(self.data[i])
+    }
+
+    pub fn col(&self, j: usize) -> VectorN<N> {
+        VectorN::from(std::array::from_fn(|i| self[i][j]))
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self {
+            data: std::array::from_fn(|i| std::array::from_fn(|j| self[j][i])),
+        }
+    }
+
+    pub fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        Self {
+            data: self.data.map(|row| row.map(&f)),
+        }
+    }
+
+    pub fn zip_map(&self, other: &Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Self {
+            data: std::array::from_fn(|i| std::array::from_fn(|j| f(self[i][j], other[i][j]))),
+        }
+    }
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        self.zip_map(other, |a, b| lerp(a, b, t))
+    }
+
+    pub fn mul_matrix(&self, other: &Self) -> Self {
+        Self {
+            data: std::array::from_fn(|i| {
+                std::array::from_fn(|j| (0..N).map(|k| self[i][k] * other[k][j]).sum())
+            }),
+        }
+    }
+
+    pub fn mul_vector(&self, v: &VectorN<N>) -> VectorN<N> {
+        VectorN::from(std::array::from_fn(|i| {
+            (0..N).map(|k| self[i][k] * v[k]).sum()
+        }))
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        self.data.as_flattened()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [f32] {
+        self.data.as_flattened_mut()
+    }
+
+    pub fn as_ptr(&self) -> *const f32 {
+        self.as_slice().as_ptr()
+    }
+}
+
+impl<const N: usize> MatrixN<N> {
+    /// Whether `det` is too small, relative to the size of the entries, for
+    /// the inverse to be meaningful.
+    fn is_singular(&self, det: f32) -> bool {
+        let scale = self
+            .as_slice()
+            .iter()
+            .fold(0_f32, |acc, x| acc.max(x.abs()));
+        !det.is_finite() || scale == 0. || det.abs() <= f32::EPSILON * scale.powi(N as i32)
+    }
+}
+
+// Closed-form determinants and inverses. `try_inverse` returns `None` for
+// singular matrices, `inverse` panics.
+
+impl Mat2 {
+    pub fn det(&self) -> f32 {
+        self[0][0] * self[1][1] - self[0][1] * self[1][0]
+    }
+
+    pub fn try_inverse(&self) -> Option<Self> {
+        let det = self.det();
+        if self.is_singular(det) {
+            return None;
+        }
+        Some(Self::from([[self[1][1], -self[0][1]], [-self[1][0], self[0][0]]]) / det)
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.try_inverse().expect("Cannot invert a singular matrix")
+    }
+}
+
+impl Mat3 {
+    /// Transposed cofactor matrix, `det * inverse` for invertible matrices.
+    pub fn adjugate(&self) -> Self {
+        let m = &self.data;
+        Self::from([
+            [
+                m[1][1] * m[2][2] - m[1][2] * m[2][1],
+                m[0][2] * m[2][1] - m[0][1] * m[2][2],
+                m[0][1] * m[1][2] - m[0][2] * m[1][1],
+            ],
+            [
+                m[1][2] * m[2][0] - m[1][0] * m[2][2],
+                m[0][0] * m[2][2] - m[0][2] * m[2][0],
+                m[0][2] * m[1][0] - m[0][0] * m[1][2],
+            ],
+            [
+                m[1][0] * m[2][1] - m[1][1] * m[2][0],
+                m[0][1] * m[2][0] - m[0][0] * m[2][1],
+                m[0][0] * m[1][1] - m[0][1] * m[1][0],
+            ],
+        ])
+    }
+
+    pub fn det(&self) -> f32 {
+        let m = &self.data;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn try_inverse(&self) -> Option<Self> {
+        let det = self.det();
+        if self.is_singular(det) {
+            return None;
+        }
+        Some(self.adjugate() / det)
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.try_inverse().expect("Cannot invert a singular matrix")
+    }
+}
+
+impl Mat4 {
+    // 2x2 sub-determinants of the top two rows (s) and bottom two rows (c),
+    // shared by `det` and `try_inverse`.
+    fn sub_dets(&self) -> ([f32; 6], [f32; 6]) {
+        let a = &self.data;
+        (
+            [
+                a[0][0] * a[1][1] - a[1][0] * a[0][1],
+                a[0][0] * a[1][2] - a[1][0] * a[0][2],
+                a[0][0] * a[1][3] - a[1][0] * a[0][3],
+                a[0][1] * a[1][2] - a[1][1] * a[0][2],
+                a[0][1] * a[1][3] - a[1][1] * a[0][3],
+                a[0][2] * a[1][3] - a[1][2] * a[0][3],
+            ],
+            [
+                a[2][0] * a[3][1] - a[3][0] * a[2][1],
+                a[2][0] * a[3][2] - a[3][0] * a[2][2],
+                a[2][0] * a[3][3] - a[3][0] * a[2][3],
+                a[2][1] * a[3][2] - a[3][1] * a[2][2],
+                a[2][1] * a[3][3] - a[3][1] * a[2][3],
+                a[2][2] * a[3][3] - a[3][2] * a[2][3],
+            ],
+        )
+    }
+
+    /// Whether the bottom row is `[0, 0, 0, 1]`.
+    pub fn is_affine(&self) -> bool {
+        self[3] == [0., 0., 0., 1.]
+    }
+
+    /// Inverse of an affine matrix, i.e. one whose bottom row is
+    /// `[0, 0, 0, 1]`. Only the upper-left 3x3 block gets inverted.
+    pub fn try_affine_inverse(&self) -> Option<Self> {
+        let r = self.to_mat3().try_inverse()?;
+        let t = -(r * self.col(3).truncate());
+        Some(Self::from([
+            [r[0][0], r[0][1], r[0][2], t[0]],
+            [r[1][0], r[1][1], r[1][2], t[1]],
+            [r[2][0], r[2][1], r[2][2], t[2]],
+            [0., 0., 0., 1.],
+        ]))
+    }
+
+    pub fn det(&self) -> f32 {
+        let (s, c) = self.sub_dets();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    pub fn try_inverse(&self) -> Option<Self> {
+        if self.is_affine() {
+            return self.try_affine_inverse();
+        }
+        let (s, c) = self.sub_dets();
+        let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if self.is_singular(det) {
+            return None;
+        }
+        let a = &self.data;
+        Some(
+            Self::from([
+                [
+                    a[1][1] * c[5] - a[1][2] * c[4] + a[1][3] * c[3],
+                    -a[0][1] * c[5] + a[0][2] * c[4] - a[0][3] * c[3],
+                    a[3][1] * s[5] - a[3][2] * s[4] + a[3][3] * s[3],
+                    -a[2][1] * s[5] + a[2][2] * s[4] - a[2][3] * s[3],
+                ],
+                [
+                    -a[1][0] * c[5] + a[1][2] * c[2] - a[1][3] * c[1],
+                    a[0][0] * c[5] - a[0][2] * c[2] + a[0][3] * c[1],
+                    -a[3][0] * s[5] + a[3][2] * s[2] - a[3][3] * s[1],
+                    a[2][0] * s[5] - a[2][2] * s[2] + a[2][3] * s[1],
+                ],
+                [
+                    a[1][0] * c[4] - a[1][1] * c[2] + a[1][3] * c[0],
+                    -a[0][0] * c[4] + a[0][1] * c[2] - a[0][3] * c[0],
+                    a[3][0] * s[4] - a[3][1] * s[2] + a[3][3] * s[0],
+                    -a[2][0] * s[4] + a[2][1] * s[2] - a[2][3] * s[0],
+                ],
+                [
+                    -a[1][0] * c[3] + a[1][1] * c[1] - a[1][2] * c[0],
+                    a[0][0] * c[3] - a[0][1] * c[1] + a[0][2] * c[0],
+                    -a[3][0] * s[3] + a[3][1] * s[1] - a[3][2] * s[0],
+                    a[2][0] * s[3] - a[2][1] * s[1] + a[2][2] * s[0],
+                ],
+            ]) / det,
+        )
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.try_inverse().expect("Cannot invert a singular matrix")
+    }
+}
+
+//Custom matrices
+
+impl Mat4 {
+    pub fn scaling(values: Vec3) -> Self {
+        Self::from([
+            [values[0], 0., 0., 0.],
+            [0., values[1], 0., 0.],
+            [0., 0., values[2], 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn translation(values: Vec3) -> Self {
+        Self::from([
+            [1., 0., 0., values[0]],
+            [0., 1., 0., values[1]],
+            [0., 0., 1., values[2]],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn rotation(values: Vec3) -> Self {
+        Self::rotation_z(values[2]) * Self::rotation_y(values[1]) * Self::rotation_x(values[0])
+    }
+
+    pub fn rotation_x(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from([
+            [1., 0., 0., 0.],
+            [0., cos, -sin, 0.],
+            [0., sin, cos, 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn rotation_y(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from([
+            [cos, 0., sin, 0.],
+            [0., 1., 0., 0.],
+            [-sin, 0., cos, 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn rotation_z(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from([
+            [cos, -sin, 0., 0.],
+            [sin, cos, 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn model(translation_values: Vec3, rotation_values: Vec3, scaling_values: Vec3) -> Self {
+        Self::translation(translation_values)
+            * Self::rotation(rotation_values)
+            * Self::scaling(scaling_values)
+    }
+
+    /// Returns the upper-left 3x3 block.
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::from(std::array::from_fn(|i| std::array::from_fn(|j| self[i][j])))
+    }
+
+    /// Returns the normal matrix from a model matrix. If the model matrix is
+    /// singular (e.g. a zero scale) the cofactor matrix is returned instead,
+    /// which transforms normals the same way up to length.
+    pub fn to_normal(&self) -> Mat3 {
+        let m = self.to_mat3();
+        match m.try_inverse() {
+            Some(inverse) => inverse.transpose(),
+            None => m.adjugate().transpose(),
+        }
+    }
+
+    pub fn projection_orthographic(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        Self::from([
+            [
+                2. / (right - left),
+                0.,
+                0.,
+                -(right + left) / (right - left),
+            ],
+            [
+                0.,
+                2. / (top - bottom),
+                0.,
+                -(top + bottom) / (top - bottom),
+            ],
+            [0., 0., -2. / (far - near), -(far + near) / (far - near)],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    pub fn projection_perspective(fov: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let tan_of_half_fov = (fov / 2.).tan();
+        Self::from([
+            [1. / (aspect * tan_of_half_fov), 0., 0., 0.],
+            [0., 1. / tan_of_half_fov, 0., 0.],
+            [
+                0.,
+                0.,
+                -(far + near) / (far - near),
+                -2. * far * near / (far - near),
+            ],
+            [0., 0., -1., 0.],
+        ])
+    }
+
+    /// Off-axis perspective like `glFrustum`. The bounds are on the near
+    /// plane and needn't be symmetric, e.g. for stereo or tiled rendering.
+    pub fn projection_frustum(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        Self::from([
+            [
+                2. * near / (right - left),
+                0.,
+                (right + left) / (right - left),
+                0.,
+            ],
+            [
+                0.,
+                2. * near / (top - bottom),
+                (top + bottom) / (top - bottom),
+                0.,
+            ],
+            [
+                0.,
+                0.,
+                -(far + near) / (far - near),
+                -2. * far * near / (far - near),
+            ],
+            [0., 0., -1., 0.],
+        ])
+    }
+
+    /// `projection_perspective` with the far plane at infinity.
+    pub fn projection_perspective_infinite(fov: f32, aspect: f32, near: f32) -> Self {
+        let tan_of_half_fov = (fov / 2.).tan();
+        Self::from([
+            [1. / (aspect * tan_of_half_fov), 0., 0., 0.],
+            [0., 1. / tan_of_half_fov, 0., 0.],
+            [0., 0., -1., -2. * near],
+            [0., 0., -1., 0.],
+        ])
+    }
+
+    /// Reversed-Z perspective: depth goes from 1 at `near` to 0 at `far`.
+    /// Only gains precision with `[0, 1]` clip depth (`glClipControl`) and a
+    /// `GREATER` depth test, see `DepthRange::Reversed`.
+    pub fn projection_perspective_reversed(fov: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let tan_of_half_fov = (fov / 2.).tan();
+        Self::from([
+            [1. / (aspect * tan_of_half_fov), 0., 0., 0.],
+            [0., 1. / tan_of_half_fov, 0., 0.],
+            [0., 0., near / (far - near), far * near / (far - near)],
+            [0., 0., -1., 0.],
+        ])
+    }
+
+    /// `projection_perspective_reversed` with the far plane at infinity.
+    pub fn projection_perspective_reversed_infinite(fov: f32, aspect: f32, near: f32) -> Self {
+        let tan_of_half_fov = (fov / 2.).tan();
+        Self::from([
+            [1. / (aspect * tan_of_half_fov), 0., 0., 0.],
+            [0., 1. / tan_of_half_fov, 0., 0.],
+            [0., 0., 0., near],
+            [0., 0., -1., 0.],
+        ])
+    }
+
+    /// Replaces the near plane of a projection with `plane`, given in view
+    /// space as `(a, b, c, d)` with points on the visible side having
+    /// `ax + by + cz + d > 0` (Lengyel's oblique near-plane clipping). Used
+    /// to clip geometry behind mirrors and portals. The far plane is moved
+    /// as little as possible but depth precision still suffers.
+    pub fn oblique_near_plane(&self, plane: &Vec4, depth: DepthRange) -> Self {
+        let (near, far) = depth.near_far();
+        // Corner of the view volume opposite the plane, in view space
+        let sign = |x: f32| if x < 0. { -1. } else { 1. };
+        let corner = Vec4::from([sign(plane[0]), sign(plane[1]), far, 1.]);
+        let q = self.inverse() * corner;
+        let row3 = self.row(3);
+        let a = (far - near) * (row3 * q) / (plane * q);
+        let row2 = plane * a + row3 * near;
+        let mut m = *self;
+        m.data[2] = *row2.as_array();
+        m
+    }
+
+    /// Shifts the projection by `offset` in normalized device coordinates.
+    /// A sub-pixel offset of `(x, y)` pixels on a `w` by `h` target is
+    /// `(2x / w, 2y / h)`. Works for perspective and orthographic matrices.
+    pub fn jittered(&self, offset: &Vec2) -> Self {
+        let mut m = *self;
+        let row3 = self.row(3);
+        m.data[0] = *(self.row(0) + row3 * offset[0]).as_array();
+        m.data[1] = *(self.row(1) + row3 * offset[1]).as_array();
+        m
+    }
+
+    /// position: Camera position
+    /// target: Target position
+    /// up: Up vector in world space
+    pub fn look_at(position: &Vec3, target: &Vec3, up: &Vec3) -> Self {
+        let direction = (position - target).unit();
+        let cam_right = up.unit().cross(&direction).unit();
+        let cam_up = direction.cross(&cam_right);
+        Self::from([
+            [cam_right[0], cam_right[1], cam_right[2], 0.],
+            [cam_up[0], cam_up[1], cam_up[2], 0.],
+            [direction[0], direction[1], direction[2], 0.],
+            [0., 0., 0., 1.],
+        ]) * Self::translation(-position)
+    }
+
+    /// Transforms a point, i.e. `w = 1`.
+    pub fn transform_point(&self, p: &Vec3) -> Vec3 {
+        (self * p.extend(1.)).truncate()
+    }
+
+    /// Transforms a direction, i.e. `w = 0`, ignoring translation.
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        (self * v.extend(0.)).truncate()
+    }
+}
+
+impl<const N: usize> Default for MatrixN<N> {
+    fn default() -> Self {
+        Self::zeroes()
+    }
+}
+
+// Ops
+//
+// Same as for `VectorN`: impl_op_ex! needs concrete types.
+
+macro_rules! impl_matrix_n_ops {
+    ( $( $t:ident, $v:ident );* ) => { $(
+        // Unary Ops
+
+        impl_op_ex!(-|matrix: &$t| -> $t { matrix.map(|x| -x) });
+
+        // Scalar Ops
+
+        impl_op_ex_commutative!(+ |matrix: &$t, scalar: &f32| -> $t { matrix.map(|x| x + scalar) });
+
+        impl_op_ex_commutative!(+ |matrix: &$t, scalar: &i32| -> $t {
+            matrix.map(|x| x + *scalar as f32)
+        });
+
+        impl_op_ex!(-|matrix: &$t, scalar: &f32| -> $t { matrix + (-scalar) });
+
+        impl_op_ex!(-|matrix: &$t, scalar: &i32| -> $t { matrix + (-scalar as f32) });
+
+        impl_op_ex!(-|scalar: &f32, matrix: &$t| -> $t { scalar + (-matrix) });
+
+        impl_op_ex!(-|scalar: &i32, matrix: &$t| -> $t { *scalar as f32 + (-matrix) });
+
+        impl_op_ex_commutative!(*|matrix: &$t, scalar: &f32| -> $t { matrix.map(|x| x * scalar) });
+
+        impl_op_ex_commutative!(*|matrix: &$t, scalar: &i32| -> $t {
+            matrix.map(|x| x * *scalar as f32)
+        });
+
+        impl_op_ex!(/ |matrix: &$t, scalar: &f32| -> $t { matrix.map(|x| x / scalar) });
+
+        impl_op_ex!(/ |matrix: &$t, scalar: &i32| -> $t { matrix.map(|x| x / *scalar as f32) });
+
+        // Matrix Ops
+
+        impl_op_ex!(+ |lhs: &$t, rhs: &$t| -> $t { lhs.zip_map(rhs, |a, b| a + b) });
+
+        impl_op_ex!(-|lhs: &$t, rhs: &$t| -> $t { lhs.zip_map(rhs, |a, b| a - b) });
+
+        impl_op_ex!(*|lhs: &$t, rhs: &$t| -> $t { lhs.mul_matrix(rhs) });
+
+        // Vector Ops
+
+        impl_op_ex!(*|matrix: &$t, vector: &$v| -> $v { matrix.mul_vector(vector) });
+    )* };
+}
+
+impl_matrix_n_ops!(Mat2, Vec2; Mat3, Vec3; Mat4, Vec4);
+
+// Scalar Ops Assignment
+
+impl<T: ToPrimitive, const N: usize> AddAssign<T> for MatrixN<N> {
+    fn add_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        self.as_mut_slice()
+            .iter_mut()
+            .for_each(|x| *x += scalar_f32);
+    }
+}
+
+impl<T: ToPrimitive, const N: usize> SubAssign<T> for MatrixN<N> {
+    fn sub_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        self.as_mut_slice()
+            .iter_mut()
+            .for_each(|x| *x -= scalar_f32);
+    }
+}
+
+impl<T: ToPrimitive, const N: usize> MulAssign<T> for MatrixN<N> {
+    fn mul_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        self.as_mut_slice()
+            .iter_mut()
+            .for_each(|x| *x *= scalar_f32);
+    }
+}
+
+impl<T: ToPrimitive, const N: usize> DivAssign<T> for MatrixN<N> {
+    fn div_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        self.as_mut_slice()
+            .iter_mut()
+            .for_each(|x| *x /= scalar_f32);
+    }
+}
+
+// Matrix Ops Assignment
+
+impl<const N: usize> AddAssign for MatrixN<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = self.zip_map(&rhs, |a, b| a + b);
+    }
+}
+
+impl<const N: usize> SubAssign for MatrixN<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = self.zip_map(&rhs, |a, b| a - b);
+    }
+}
+
+// Indexing
+
+impl<const N: usize> Index<usize> for MatrixN<N> {
+    type Output = [f32; N];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for MatrixN<N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+// SAFETY: a transparent wrapper around [[f32; N]; N], so it can be uploaded
+// to GPU buffers as is
+unsafe impl<const N: usize> Zeroable for MatrixN<N> {}
+unsafe impl<const N: usize> Pod for MatrixN<N> {}
+
+// Conversions
+
+// - From
+
+impl<const N: usize> From<[[f32; N]; N]> for MatrixN<N> {
+    fn from(value: [[f32; N]; N]) -> Self {
+        Self { data: value }
+    }
+}
+
+impl<const N: usize> From<&MatrixN<N>> for MatrixN<N> {
+    fn from(value: &MatrixN<N>) -> Self {
+        *value
+    }
+}
+
+impl<const N: usize> From<&Matrix> for MatrixN<N> {
+    fn from(value: &Matrix) -> Self {
+        assert_eq!(value.rows(), N);
+        assert_eq!(value.cols(), N);
+        Self {
+            data: std::array::from_fn(|i| std::array::from_fn(|j| value[i][j])),
+        }
+    }
+}
+
+impl<const N: usize> From<Matrix> for MatrixN<N> {
+    fn from(value: Matrix) -> Self {
+        Self::from(&value)
+    }
+}
+
+// - Into / Reverse From
+
+impl<const N: usize> From<MatrixN<N>> for Matrix {
+    fn from(val: MatrixN<N>) -> Self {
+        Matrix::new(N, N, val.as_slice().to_vec())
+    }
+}
+
+impl<const N: usize> From<&MatrixN<N>> for Matrix {
+    fn from(val: &MatrixN<N>) -> Self {
+        Matrix::new(N, N, val.as_slice().to_vec())
+    }
+}
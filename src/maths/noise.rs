@@ -0,0 +1,274 @@
+use std::array::from_fn;
+
+use super::random::Rng;
+use super::{lerp, Vec2, Vec3, Vec4, Vector};
+
+/// Which noise `Noise::sample` evaluates.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NoiseKind {
+    Perlin,
+    Simplex,
+    Worley,
+}
+
+/// Fractal Brownian motion settings: `octaves` layers of noise, each
+/// `lacunarity` times the frequency and `gain` times the amplitude of the
+/// previous one.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Fbm {
+    octaves: u32,
+    lacunarity: f32,
+    gain: f32,
+}
+
+impl Fbm {
+    pub fn new(octaves: u32, lacunarity: f32, gain: f32) -> Self {
+        Self {
+            octaves,
+            lacunarity,
+            gain,
+        }
+    }
+}
+
+impl Default for Fbm {
+    fn default() -> Self {
+        Self::new(5, 2., 0.5)
+    }
+}
+
+/// Seeded noise generator. Perlin and simplex noise are roughly in
+/// `[-1, 1]`, Worley noise is the distance to the nearest feature point,
+/// mostly in `[0, 1]`. Output only depends on the seed and the input.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Noise {
+    seed: u64,
+    /// Shuffled 0..=255, repeated so nested lookups don't need wrapping.
+    perm: Vec<u8>,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        let mut table: Vec<u8> = (0..=255).collect();
+        Rng::new(seed).shuffle(&mut table);
+        let perm = table.iter().chain(&table).copied().collect();
+        Self { seed, perm }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn perlin2(&self, p: &Vec2) -> f32 {
+        0.507 * self.perlin_n(*p.as_array())
+    }
+
+    pub fn perlin3(&self, p: &Vec3) -> f32 {
+        0.936 * self.perlin_n(*p.as_array())
+    }
+
+    pub fn perlin4(&self, p: &Vec4) -> f32 {
+        0.87 * self.perlin_n(*p.as_array())
+    }
+
+    pub fn simplex2(&self, p: &Vec2) -> f32 {
+        40. * self.simplex_n(*p.as_array(), 0.5)
+    }
+
+    pub fn simplex3(&self, p: &Vec3) -> f32 {
+        32. * self.simplex_n(*p.as_array(), 0.6)
+    }
+
+    pub fn simplex4(&self, p: &Vec4) -> f32 {
+        27. * self.simplex_n(*p.as_array(), 0.6)
+    }
+
+    pub fn worley2(&self, p: &Vec2) -> f32 {
+        self.worley_n(*p.as_array())
+    }
+
+    pub fn worley3(&self, p: &Vec3) -> f32 {
+        self.worley_n(*p.as_array())
+    }
+
+    pub fn worley4(&self, p: &Vec4) -> f32 {
+        self.worley_n(*p.as_array())
+    }
+
+    /// Dispatches on the length of `p`, which must be 2, 3 or 4.
+    pub fn sample(&self, kind: NoiseKind, p: &Vector) -> f32 {
+        match (kind, p.len()) {
+            (NoiseKind::Perlin, 2) => self.perlin2(&p.into()),
+            (NoiseKind::Perlin, 3) => self.perlin3(&p.into()),
+            (NoiseKind::Perlin, 4) => self.perlin4(&p.into()),
+            (NoiseKind::Simplex, 2) => self.simplex2(&p.into()),
+            (NoiseKind::Simplex, 3) => self.simplex3(&p.into()),
+            (NoiseKind::Simplex, 4) => self.simplex4(&p.into()),
+            (NoiseKind::Worley, 2) => self.worley2(&p.into()),
+            (NoiseKind::Worley, 3) => self.worley3(&p.into()),
+            (NoiseKind::Worley, 4) => self.worley4(&p.into()),
+            (_, n) => panic!("Noise is only defined in 2, 3 and 4 dimensions, not {}", n),
+        }
+    }
+
+    /// Sum of octaves, normalized by the total amplitude so it stays in the
+    /// range of a single octave.
+    pub fn fbm(&self, kind: NoiseKind, p: &Vector, fbm: &Fbm) -> f32 {
+        let mut total = 0.;
+        let mut norm = 0.;
+        let mut amplitude = 1.;
+        let mut frequency = 1.;
+        for _ in 0..fbm.octaves {
+            total += amplitude * self.sample(kind, &(p * frequency));
+            norm += amplitude;
+            amplitude *= fbm.gain;
+            frequency *= fbm.lacunarity;
+        }
+        if norm > 0. {
+            total / norm
+        } else {
+            0.
+        }
+    }
+
+    /// `fbm` at `p` displaced by another fbm per axis, scaled by `strength`
+    /// (Inigo Quilez's domain warping).
+    pub fn warp(&self, kind: NoiseKind, p: &Vector, fbm: &Fbm, strength: f32) -> f32 {
+        let offset = Vector::from_iter((0..p.len()).map(|i| {
+            // Far apart offsets decorrelate the axes
+            let shift = Vector::new(p.len(), 17.31 * (i + 1) as f32);
+            self.fbm(kind, &(p + shift), fbm)
+        }));
+        self.fbm(kind, &(p + offset * strength), fbm)
+    }
+
+    fn hash<const N: usize>(&self, cell: [i32; N]) -> usize {
+        (0..N)
+            .rev()
+            .fold(0, |h, i| self.perm[(cell[i] & 255) as usize + h] as usize)
+    }
+
+    fn perlin_n<const N: usize>(&self, p: [f32; N]) -> f32 {
+        let cell: [i32; N] = from_fn(|i| p[i].floor() as i32);
+        let frac: [f32; N] = from_fn(|i| p[i] - cell[i] as f32);
+        // Gradient contribution of each corner, bit i of the index picks
+        // the far side along axis i
+        let mut values = [0.; 16];
+        for (c, value) in values.iter_mut().enumerate().take(1 << N) {
+            let offset: [i32; N] = from_fn(|i| ((c >> i) & 1) as i32);
+            let corner: [i32; N] = from_fn(|i| cell[i] + offset[i]);
+            let d: [f32; N] = from_fn(|i| frac[i] - offset[i] as f32);
+            *value = grad(self.hash(corner), d);
+        }
+        // Collapse one axis at a time
+        let mut count = 1 << N;
+        for &f in &frac {
+            count /= 2;
+            let t = fade(f);
+            for c in 0..count {
+                values[c] = lerp(values[2 * c], values[2 * c + 1], t);
+            }
+        }
+        values[0]
+    }
+
+    /// Generic version of Stefan Gustavson's simplex noise. `r2` is the
+    /// squared radius of each corner's kernel.
+    fn simplex_n<const N: usize>(&self, p: [f32; N], r2: f32) -> f32 {
+        let n = N as f32;
+        let skew = ((n + 1.).sqrt() - 1.) / n;
+        let unskew = (1. - 1. / (n + 1.).sqrt()) / n;
+
+        let s = p.iter().sum::<f32>() * skew;
+        let cell: [i32; N] = from_fn(|i| (p[i] + s).floor() as i32);
+        let t = cell.iter().sum::<i32>() as f32 * unskew;
+        let d0: [f32; N] = from_fn(|i| p[i] - (cell[i] as f32 - t));
+
+        // Walk the simplex from the cell origin, stepping along the axis
+        // with the largest offset first
+        let mut order: [usize; N] = from_fn(|i| i);
+        order.sort_by(|&a, &b| d0[b].total_cmp(&d0[a]));
+        let mut offset = [0; N];
+        let mut total = 0.;
+        for k in 0..=N {
+            if k > 0 {
+                offset[order[k - 1]] += 1;
+            }
+            let d: [f32; N] = from_fn(|i| d0[i] - offset[i] as f32 + k as f32 * unskew);
+            let t = r2 - d.iter().map(|x| x * x).sum::<f32>();
+            if t > 0. {
+                let corner: [i32; N] = from_fn(|i| cell[i] + offset[i]);
+                total += t * t * t * t * grad(self.hash(corner), d);
+            }
+        }
+        total
+    }
+
+    /// F1 cellular noise with one feature point per unit cell.
+    fn worley_n<const N: usize>(&self, p: [f32; N]) -> f32 {
+        let cell: [i32; N] = from_fn(|i| p[i].floor() as i32);
+        let mut min = f32::INFINITY;
+        for k in 0..3_usize.pow(N as u32) {
+            let neighbour: [i32; N] =
+                from_fn(|i| cell[i] + (k / 3_usize.pow(i as u32) % 3) as i32 - 1);
+            let dist2: f32 = (0..N)
+                .map(|i| {
+                    let feature = neighbour[i] as f32 + self.unit_hash(&neighbour, i);
+                    (feature - p[i]) * (feature - p[i])
+                })
+                .sum();
+            min = min.min(dist2);
+        }
+        min.sqrt()
+    }
+
+    /// Hash of a cell and axis in `[0, 1)`, finer than the permutation
+    /// table's 256 values.
+    fn unit_hash<const N: usize>(&self, cell: &[i32; N], axis: usize) -> f32 {
+        let mut h = self.seed ^ (axis as u64).wrapping_mul(0x9e3779b97f4a7c15);
+        for &c in cell {
+            h = (h ^ c as u32 as u64).wrapping_mul(0xff51afd7ed558ccd);
+            h ^= h >> 33;
+        }
+        h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+        h ^= h >> 33;
+        (h >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// `6t^5 - 15t^4 + 10t^3`.
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (6. * t - 15.) + 10.)
+}
+
+/// Dot product of `d` with one of a small set of gradients picked by `hash`
+/// (Gustavson's gradient sets).
+fn grad<const N: usize>(hash: usize, d: [f32; N]) -> f32 {
+    match N {
+        2 => {
+            let h = hash & 7;
+            let (u, v) = if h < 4 { (d[0], d[1]) } else { (d[1], d[0]) };
+            (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { 2. * v } else { -2. * v })
+        }
+        3 => {
+            let h = hash & 15;
+            let u = if h < 8 { d[0] } else { d[1] };
+            let v = match h {
+                0..=3 => d[1],
+                12 | 14 => d[0],
+                _ => d[2],
+            };
+            (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+        }
+        4 => {
+            let h = hash & 31;
+            let u = if h < 24 { d[0] } else { d[1] };
+            let v = if h < 16 { d[1] } else { d[2] };
+            let w = if h < 8 { d[2] } else { d[3] };
+            (if h & 1 == 0 { u } else { -u })
+                + (if h & 2 == 0 { v } else { -v })
+                + (if h & 4 == 0 { w } else { -w })
+        }
+        _ => unreachable!(),
+    }
+}
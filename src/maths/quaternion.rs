@@ -0,0 +1,333 @@
+use std::ops;
+
+use super::{Mat3, Mat4, Matrix, Vec3};
+use crate::{matrix_n, vector_n};
+
+/// Unit quaternions are used as rotations. `w` is the scalar part.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Quaternion {
+    w: f32,
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+impl Quaternion {
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        Self { w, x, y, z }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(1., 0., 0., 0.)
+    }
+
+    pub fn w(&self) -> f32 {
+        self.w
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn z(&self) -> f32 {
+        self.z
+    }
+
+    /// Vector (imaginary) part.
+    pub fn vector(&self) -> Vec3 {
+        vector_n![self.x, self.y, self.z]
+    }
+
+    /// Rotation of `angle` radians around `axis`.
+    pub fn from_axis_angle(axis: &Vec3, angle: f32) -> Self {
+        let axis = axis.unit();
+        let (sin, cos) = (angle / 2.).sin_cos();
+        Self::new(cos, axis[0] * sin, axis[1] * sin, axis[2] * sin)
+    }
+
+    /// Same convention as `Matrix::rotation`: x is applied first, then y,
+    /// then z.
+    pub fn from_euler(angles: &Vec3) -> Self {
+        let x = Self::from_axis_angle(&vector_n![1., 0., 0.], angles[0]);
+        let y = Self::from_axis_angle(&vector_n![0., 1., 0.], angles[1]);
+        let z = Self::from_axis_angle(&vector_n![0., 0., 1.], angles[2]);
+        z * y * x
+    }
+
+    /// Inverse of `from_euler`.
+    pub fn to_euler(&self) -> Vec3 {
+        let m = self.to_mat3();
+        let y = (-m[2][0]).clamp(-1., 1.).asin();
+        if m[2][0].abs() < 0.9999 {
+            vector_n![m[2][1].atan2(m[2][2]), y, m[1][0].atan2(m[0][0])]
+        } else {
+            // Gimbal lock, x and z rotate around the same axis
+            vector_n![0., y, (-m[0][1]).atan2(m[1][1])]
+        }
+    }
+
+    /// Rotation taking -z onto `forward` with y as close to `up` as
+    /// possible, i.e. the orientation of an OpenGL camera looking along
+    /// `forward`. When `forward` is parallel to `up`, e.g. looking straight
+    /// down, the z axis (x if `up` is along z) stands in for `up`, which
+    /// with y up is the orientation reached by pitching from -z.
+    pub fn look_rotation(forward: &Vec3, up: &Vec3) -> Self {
+        let front = forward.unit();
+        let mut right = front.cross(up);
+        if right.abs() < 1e-6 * up.abs() {
+            let axis = if up.unit()[2].abs() < 0.9 {
+                vector_n![0., 0., 1.]
+            } else {
+                vector_n![1., 0., 0.]
+            };
+            let sign = if front * up < 0. { -1. } else { 1. };
+            right = front.cross(&(axis * sign));
+        }
+        let right = right.unit();
+        let up = right.cross(&front);
+        Self::from(matrix_n![
+            [right[0], up[0], -front[0]],
+            [right[1], up[1], -front[1]],
+            [right[2], up[2], -front[2]]
+        ])
+    }
+
+    pub fn dot(&self, other: &Self) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn abs(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn unit(&self) -> Self {
+        self / self.abs()
+    }
+
+    pub fn normalize(&mut self) {
+        *self = self.unit();
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.conjugate() / self.dot(self)
+    }
+
+    /// Axis and angle in radians. The axis is arbitrary for the identity.
+    pub fn to_axis_angle(&self) -> (Vec3, f32) {
+        let q = if self.w < 0. {
+            -self.unit()
+        } else {
+            self.unit()
+        };
+        let angle = 2. * q.w.clamp(-1., 1.).acos();
+        let sin = (1. - q.w * q.w).sqrt();
+        if sin < 1e-6 {
+            (vector_n![1., 0., 0.], angle)
+        } else {
+            (q.vector() / sin, angle)
+        }
+    }
+
+    pub fn rotate(&self, v: &Vec3) -> Vec3 {
+        let u = self.vector();
+        let t = 2. * u.cross(v);
+        v + self.w * t + u.cross(&t)
+    }
+
+    /// Normalized linear interpolation. Cheaper than `slerp` but the angular
+    /// speed is not constant.
+    pub fn nlerp(&self, other: &Self, t: f32) -> Self {
+        let other = if self.dot(other) < 0. { -other } else { *other };
+        (self * (1. - t) + other * t).unit()
+    }
+
+    /// Spherical linear interpolation along the shortest arc.
+    pub fn slerp(&self, other: &Self, t: f32) -> Self {
+        let mut cos = self.dot(other);
+        let other = if cos < 0. {
+            cos = -cos;
+            -other
+        } else {
+            *other
+        };
+        if cos > 0.9995 {
+            return self.nlerp(&other, t);
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1. - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        (self * a + other * b).unit()
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        let Self { w, x, y, z } = *self;
+        matrix_n![
+            [
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y)
+            ],
+            [
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x)
+            ],
+            [
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y)
+            ]
+        ]
+    }
+
+    pub fn to_mat4(&self) -> Mat4 {
+        let m = self.to_mat3();
+        matrix_n![
+            [m[0][0], m[0][1], m[0][2], 0.],
+            [m[1][0], m[1][1], m[1][2], 0.],
+            [m[2][0], m[2][1], m[2][2], 0.],
+            [0., 0., 0., 1.]
+        ]
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+// Unary Ops
+
+impl_op_ex!(-|q: &Quaternion| -> Quaternion { Quaternion::new(-q.w, -q.x, -q.y, -q.z) });
+
+// Scalar Ops
+
+impl_op_ex_commutative!(*|q: &Quaternion, scalar: &f32| -> Quaternion {
+    Quaternion::new(q.w * scalar, q.x * scalar, q.y * scalar, q.z * scalar)
+});
+
+impl_op_ex!(/ |q: &Quaternion, scalar: &f32| -> Quaternion {
+    Quaternion::new(q.w / scalar, q.x / scalar, q.y / scalar, q.z / scalar)
+});
+
+// Quaternion Ops
+
+impl_op_ex!(+ |lhs: &Quaternion, rhs: &Quaternion| -> Quaternion {
+    Quaternion::new(lhs.w + rhs.w, lhs.x + rhs.x, lhs.y + rhs.y, lhs.z + rhs.z)
+});
+
+impl_op_ex!(-|lhs: &Quaternion, rhs: &Quaternion| -> Quaternion { lhs + (-rhs) });
+
+// Composition: (a * b).rotate(v) == a.rotate(&b.rotate(v))
+impl_op_ex!(*|lhs: &Quaternion, rhs: &Quaternion| -> Quaternion {
+    Quaternion::new(
+        lhs.w * rhs.w - lhs.x * rhs.x - lhs.y * rhs.y - lhs.z * rhs.z,
+        lhs.w * rhs.x + lhs.x * rhs.w + lhs.y * rhs.z - lhs.z * rhs.y,
+        lhs.w * rhs.y - lhs.x * rhs.z + lhs.y * rhs.w + lhs.z * rhs.x,
+        lhs.w * rhs.z + lhs.x * rhs.y - lhs.y * rhs.x + lhs.z * rhs.w,
+    )
+});
+
+// Vector Ops
+
+impl_op_ex!(*|q: &Quaternion, v: &Vec3| -> Vec3 { q.rotate(v) });
+
+// Conversions
+
+// - From
+
+impl From<Mat3> for Quaternion {
+    /// `m` must be a pure rotation.
+    fn from(m: Mat3) -> Self {
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        if trace > 0. {
+            let s = (trace + 1.).sqrt() * 2.;
+            Self::new(
+                0.25 * s,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1. + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.;
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                0.25 * s,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1. + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.;
+            Self::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                0.25 * s,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1. + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.;
+            Self::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                0.25 * s,
+            )
+        }
+        .unit()
+    }
+}
+
+impl From<Mat4> for Quaternion {
+    fn from(m: Mat4) -> Self {
+        Self::from(m.to_mat3())
+    }
+}
+
+impl From<&Matrix> for Quaternion {
+    /// Accepts a 3x3 rotation or a 4x4 matrix whose upper-left block is one.
+    fn from(m: &Matrix) -> Self {
+        assert!(m.is_square());
+        match m.rows() {
+            3 => Self::from(Mat3::from(m)),
+            4 => Self::from(Mat4::from(m)),
+            n => panic!("Cannot build a quaternion from a {}x{} matrix", n, n),
+        }
+    }
+}
+
+impl From<Matrix> for Quaternion {
+    fn from(m: Matrix) -> Self {
+        Self::from(&m)
+    }
+}
+
+// - Into / Reverse From
+
+impl From<Quaternion> for Mat3 {
+    fn from(q: Quaternion) -> Self {
+        q.to_mat3()
+    }
+}
+
+impl From<Quaternion> for Mat4 {
+    fn from(q: Quaternion) -> Self {
+        q.to_mat4()
+    }
+}
+
+impl From<Quaternion> for Matrix {
+    /// 4x4, like `Matrix::rotation`.
+    fn from(q: Quaternion) -> Self {
+        q.to_mat4().into()
+    }
+}
@@ -0,0 +1,199 @@
+use std::ops::Range;
+
+use super::{Vec2, Vec3};
+use crate::vector_n;
+
+/// Vanilla Doom's `rndtable` from m_random.c.
+pub const RNDTABLE: [u8; 256] = [
+    0, 8, 109, 220, 222, 241, 149, 107, 75, 248, 254, 140, 16, 66, 74, 21, 211, 47, 80, 242, 154,
+    27, 205, 128, 161, 89, 77, 36, 95, 110, 85, 48, 212, 140, 211, 249, 22, 79, 200, 50, 28, 188,
+    52, 140, 202, 120, 68, 145, 62, 70, 184, 190, 91, 197, 152, 224, 149, 104, 25, 178, 252, 182,
+    202, 182, 141, 197, 4, 81, 181, 242, 145, 42, 39, 227, 156, 198, 225, 193, 219, 93, 122, 175,
+    249, 0, 175, 143, 70, 239, 46, 246, 163, 53, 163, 109, 168, 135, 2, 235, 25, 92, 20, 145, 138,
+    77, 69, 166, 78, 176, 173, 212, 166, 113, 94, 161, 41, 50, 239, 49, 111, 164, 70, 60, 2, 37,
+    171, 75, 136, 156, 11, 56, 42, 146, 138, 229, 73, 146, 77, 61, 98, 196, 135, 106, 63, 197, 195,
+    86, 96, 203, 113, 101, 170, 247, 181, 113, 80, 250, 108, 7, 255, 237, 129, 226, 79, 107, 112,
+    166, 103, 241, 24, 223, 239, 120, 198, 58, 60, 82, 128, 3, 184, 66, 143, 224, 145, 224, 81,
+    206, 163, 45, 63, 90, 168, 114, 59, 33, 159, 95, 28, 139, 123, 98, 125, 196, 15, 70, 194, 253,
+    54, 14, 109, 226, 71, 17, 161, 93, 186, 87, 244, 138, 20, 52, 123, 251, 26, 36, 17, 46, 52,
+    231, 232, 76, 31, 221, 84, 37, 216, 165, 212, 106, 197, 242, 98, 43, 39, 175, 254, 145, 190,
+    84, 118, 222, 187, 136, 120, 163, 236, 249,
+];
+
+/// Doom's table based generator. `p_random` drives game logic and must stay
+/// in sync for demos; `m_random` is for everything else. The state is just
+/// the two indices, copy it to save and restore.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DoomRandom {
+    prndindex: u8,
+    rndindex: u8,
+}
+
+impl DoomRandom {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_indices(prndindex: u8, rndindex: u8) -> Self {
+        Self {
+            prndindex,
+            rndindex,
+        }
+    }
+
+    pub fn prndindex(&self) -> u8 {
+        self.prndindex
+    }
+
+    pub fn rndindex(&self) -> u8 {
+        self.rndindex
+    }
+
+    /// `P_Random`, for playsim use only.
+    pub fn p_random(&mut self) -> u8 {
+        self.prndindex = self.prndindex.wrapping_add(1);
+        RNDTABLE[self.prndindex as usize]
+    }
+
+    /// `M_Random`, for menus, sounds and effects that don't affect demos.
+    pub fn m_random(&mut self) -> u8 {
+        self.rndindex = self.rndindex.wrapping_add(1);
+        RNDTABLE[self.rndindex as usize]
+    }
+
+    /// `P_Random() - P_Random()`, evaluated left to right like vanilla.
+    pub fn p_subrandom(&mut self) -> i32 {
+        let r = self.p_random() as i32;
+        r - self.p_random() as i32
+    }
+
+    /// `M_ClearRandom`.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Seedable PCG32 (XSH RR) generator. Same seed and stream, same sequence on
+/// every platform.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rng {
+    state: u64,
+    inc: u64,
+}
+
+impl Rng {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, 0xda3e39cb94b95bdb)
+    }
+
+    /// Different streams give independent sequences for the same seed.
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            inc: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(Self::MULTIPLIER).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    /// In `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    pub fn next_bool(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Uniform in `[0, bound)` without modulo bias (Lemire's method).
+    pub fn below(&mut self, bound: u32) -> u32 {
+        assert!(bound > 0, "Empty range");
+        let mut m = self.next_u32() as u64 * bound as u64;
+        if (m as u32) < bound {
+            let threshold = bound.wrapping_neg() % bound;
+            while (m as u32) < threshold {
+                m = self.next_u32() as u64 * bound as u64;
+            }
+        }
+        (m >> 32) as u32
+    }
+
+    pub fn range_i32(&mut self, range: Range<i32>) -> i32 {
+        assert!(range.start < range.end, "Empty range");
+        let span = range.end.wrapping_sub(range.start) as u32;
+        range.start.wrapping_add(self.below(span) as i32)
+    }
+
+    pub fn range_f32(&mut self, range: Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.next_f32()
+    }
+
+    /// Uniform on the unit circle.
+    pub fn unit_vector2(&mut self) -> Vec2 {
+        let angle = self.range_f32(0.0..std::f32::consts::TAU);
+        vector_n![angle.cos(), angle.sin()]
+    }
+
+    /// Uniform on the unit sphere.
+    pub fn unit_vector3(&mut self) -> Vec3 {
+        let z = self.range_f32(-1.0..1.0);
+        let angle = self.range_f32(0.0..std::f32::consts::TAU);
+        let r = (1. - z * z).sqrt();
+        vector_n![r * angle.cos(), r * angle.sin(), z]
+    }
+
+    /// Uniform in the unit disk.
+    pub fn in_circle(&mut self) -> Vec2 {
+        self.unit_vector2() * self.next_f32().sqrt()
+    }
+
+    /// Uniform in the unit ball.
+    pub fn in_sphere(&mut self) -> Vec3 {
+        self.unit_vector3() * self.next_f32().cbrt()
+    }
+
+    /// Fisher-Yates.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u32 + 1) as usize);
+        }
+    }
+
+    /// `None` for an empty slice.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.below(items.len() as u32) as usize])
+        }
+    }
+}
+
+/// `index`-th element of the Halton sequence in `base`, in `[0, 1)`. Bases 2
+/// and 3 give the usual sub-pixel jitter pattern for temporal techniques.
+pub fn halton(index: u32, base: u32) -> f32 {
+    let mut result = 0.;
+    let mut f = 1.;
+    let mut i = index;
+    while i > 0 {
+        f /= base as f32;
+        result += f * (i % base) as f32;
+        i /= base;
+    }
+    result
+}
@@ -0,0 +1,256 @@
+use super::Vector;
+
+/// Curve parameterized over `t` in `[0, 1]`, each segment taking an equal
+/// share of `t`. Points are `Vector`s of any (matching) length.
+pub trait Curve {
+    fn segments(&self) -> usize;
+
+    /// Point on `segment` at local parameter `u` in `[0, 1]`.
+    fn segment_point(&self, segment: usize, u: f32) -> Vector;
+
+    /// Derivative with respect to `u`.
+    fn segment_derivative(&self, segment: usize, u: f32) -> Vector;
+
+    fn point(&self, t: f32) -> Vector {
+        let (segment, u) = self.locate(t);
+        self.segment_point(segment, u)
+    }
+
+    /// Derivative with respect to `t`.
+    fn derivative(&self, t: f32) -> Vector {
+        let (segment, u) = self.locate(t);
+        self.segment_derivative(segment, u) * self.segments() as f32
+    }
+
+    /// Segment and local parameter for `t`, which is clamped to `[0, 1]`.
+    fn locate(&self, t: f32) -> (usize, f32) {
+        let n = self.segments();
+        assert!(n > 0, "Curve has no segments");
+        let scaled = t.clamp(0., 1.) * n as f32;
+        let segment = (scaled as usize).min(n - 1);
+        (segment, scaled - segment as f32)
+    }
+
+    /// See `ArcLength`.
+    fn arc_length(&self, samples: usize) -> ArcLength
+    where
+        Self: Sized,
+    {
+        ArcLength::new(self, samples)
+    }
+}
+
+fn hermite(p0: &Vector, m0: &Vector, p1: &Vector, m1: &Vector, u: f32) -> Vector {
+    let (u2, u3) = (u * u, u * u * u);
+    p0 * (2. * u3 - 3. * u2 + 1.)
+        + m0 * (u3 - 2. * u2 + u)
+        + p1 * (-2. * u3 + 3. * u2)
+        + m1 * (u3 - u2)
+}
+
+fn hermite_derivative(p0: &Vector, m0: &Vector, p1: &Vector, m1: &Vector, u: f32) -> Vector {
+    let u2 = u * u;
+    p0 * (6. * u2 - 6. * u)
+        + m0 * (3. * u2 - 4. * u + 1.)
+        + p1 * (-6. * u2 + 6. * u)
+        + m1 * (3. * u2 - 2. * u)
+}
+
+/// Cubic Hermite spline through `points` with the given tangents.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Hermite {
+    points: Vec<Vector>,
+    tangents: Vec<Vector>,
+}
+
+impl Hermite {
+    pub fn new(points: Vec<Vector>, tangents: Vec<Vector>) -> Self {
+        assert_eq!(points.len(), tangents.len());
+        assert!(points.len() >= 2, "A spline needs at least 2 points");
+        Self { points, tangents }
+    }
+
+    pub fn points(&self) -> &[Vector] {
+        &self.points
+    }
+
+    pub fn tangents(&self) -> &[Vector] {
+        &self.tangents
+    }
+}
+
+impl Curve for Hermite {
+    fn segments(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    fn segment_point(&self, segment: usize, u: f32) -> Vector {
+        let (p, m) = (&self.points, &self.tangents);
+        hermite(
+            &p[segment],
+            &m[segment],
+            &p[segment + 1],
+            &m[segment + 1],
+            u,
+        )
+    }
+
+    fn segment_derivative(&self, segment: usize, u: f32) -> Vector {
+        let (p, m) = (&self.points, &self.tangents);
+        hermite_derivative(
+            &p[segment],
+            &m[segment],
+            &p[segment + 1],
+            &m[segment + 1],
+            u,
+        )
+    }
+}
+
+/// Uniform Catmull-Rom spline passing through every point. The end tangents
+/// point at the neighbouring point.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CatmullRom {
+    points: Vec<Vector>,
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Vector>) -> Self {
+        assert!(points.len() >= 2, "A spline needs at least 2 points");
+        Self { points }
+    }
+
+    pub fn points(&self) -> &[Vector] {
+        &self.points
+    }
+
+    fn tangent(&self, i: usize) -> Vector {
+        let last = self.points.len() - 1;
+        let prev = &self.points[i.saturating_sub(1)];
+        let next = &self.points[(i + 1).min(last)];
+        let span = if i == 0 || i == last { 1. } else { 2. };
+        (next - prev) / span
+    }
+
+    /// Same curve as a `Hermite` spline.
+    pub fn to_hermite(&self) -> Hermite {
+        let tangents = (0..self.points.len()).map(|i| self.tangent(i)).collect();
+        Hermite::new(self.points.clone(), tangents)
+    }
+}
+
+impl Curve for CatmullRom {
+    fn segments(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    fn segment_point(&self, segment: usize, u: f32) -> Vector {
+        let p = &self.points;
+        let (m0, m1) = (self.tangent(segment), self.tangent(segment + 1));
+        hermite(&p[segment], &m0, &p[segment + 1], &m1, u)
+    }
+
+    fn segment_derivative(&self, segment: usize, u: f32) -> Vector {
+        let p = &self.points;
+        let (m0, m1) = (self.tangent(segment), self.tangent(segment + 1));
+        hermite_derivative(&p[segment], &m0, &p[segment + 1], &m1, u)
+    }
+}
+
+/// Piecewise cubic Bezier curve. Segments share their end points, so there
+/// are `3 * segments + 1` control points.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CubicBezier {
+    points: Vec<Vector>,
+}
+
+impl CubicBezier {
+    pub fn new(points: Vec<Vector>) -> Self {
+        assert!(
+            points.len() >= 4 && (points.len() - 1).is_multiple_of(3),
+            "A cubic Bezier curve needs 3n + 1 control points"
+        );
+        Self { points }
+    }
+
+    pub fn points(&self) -> &[Vector] {
+        &self.points
+    }
+}
+
+impl Curve for CubicBezier {
+    fn segments(&self) -> usize {
+        (self.points.len() - 1) / 3
+    }
+
+    fn segment_point(&self, segment: usize, u: f32) -> Vector {
+        let p = &self.points[3 * segment..3 * segment + 4];
+        let v = 1. - u;
+        &p[0] * (v * v * v)
+            + &p[1] * (3. * v * v * u)
+            + &p[2] * (3. * v * u * u)
+            + &p[3] * (u * u * u)
+    }
+
+    fn segment_derivative(&self, segment: usize, u: f32) -> Vector {
+        let p = &self.points[3 * segment..3 * segment + 4];
+        let v = 1. - u;
+        (&p[1] - &p[0]) * (3. * v * v)
+            + (&p[2] - &p[1]) * (6. * v * u)
+            + (&p[3] - &p[2]) * (3. * u * u)
+    }
+}
+
+/// Arc length table for moving along a curve at constant speed. The curve
+/// is sampled `samples` times and approximated by the chords in between.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArcLength {
+    /// Distance along the curve at `t = i / (len - 1)`.
+    lengths: Vec<f32>,
+}
+
+impl ArcLength {
+    pub fn new(curve: &impl Curve, samples: usize) -> Self {
+        assert!(samples >= 1);
+        let mut lengths = Vec::with_capacity(samples + 1);
+        let mut total = 0.;
+        let mut prev = curve.point(0.);
+        lengths.push(0.);
+        for i in 1..=samples {
+            let p = curve.point(i as f32 / samples as f32);
+            total += (&p - &prev).abs();
+            lengths.push(total);
+            prev = p;
+        }
+        Self { lengths }
+    }
+
+    pub fn length(&self) -> f32 {
+        *self.lengths.last().unwrap()
+    }
+
+    /// Curve parameter `t` at `distance` along the curve, clamped to its
+    /// ends.
+    pub fn param(&self, distance: f32) -> f32 {
+        let samples = self.lengths.len() - 1;
+        let i = self.lengths.partition_point(|&l| l < distance);
+        if i == 0 {
+            return 0.;
+        }
+        if i > samples {
+            return 1.;
+        }
+        let (l0, l1) = (self.lengths[i - 1], self.lengths[i]);
+        let frac = if l1 > l0 {
+            (distance - l0) / (l1 - l0)
+        } else {
+            0.
+        };
+        (i as f32 - 1. + frac) / samples as f32
+    }
+
+    /// `param` for a fraction `s` in `[0, 1]` of the total length.
+    pub fn param_normalized(&self, s: f32) -> f32 {
+        self.param(s * self.length())
+    }
+}
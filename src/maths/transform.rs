@@ -0,0 +1,192 @@
+use std::ops;
+
+use super::{Mat4, Matrix, Quaternion, Vec3};
+use crate::vector_n;
+
+/// Position, rotation and scale. As a matrix it is `T * R * S`, the same
+/// order as `Matrix::model`: points are scaled, then rotated, then
+/// translated.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Transform {
+    position: Vec3,
+    rotation: Quaternion,
+    scale: Vec3,
+}
+
+impl Transform {
+    pub fn new(position: Vec3, rotation: Quaternion, scale: Vec3) -> Self {
+        Self {
+            position,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(Vec3::zeroes(), Quaternion::identity(), Vec3::ones())
+    }
+
+    pub fn from_position(position: Vec3) -> Self {
+        Self {
+            position,
+            ..Self::identity()
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn rotation(&self) -> Quaternion {
+        self.rotation
+    }
+
+    pub fn scale(&self) -> Vec3 {
+        self.scale
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+    }
+
+    pub fn set_rotation(&mut self, rotation: Quaternion) {
+        self.rotation = rotation.unit();
+    }
+
+    pub fn set_scale(&mut self, scale: Vec3) {
+        self.scale = scale;
+    }
+
+    /// Moves in world space.
+    pub fn translate(&mut self, v: Vec3) {
+        self.position += v;
+    }
+
+    /// Rotates in world space, around the transform's own position.
+    pub fn rotate(&mut self, rotation: Quaternion) {
+        self.rotation = (rotation * self.rotation).unit();
+    }
+
+    pub fn transform_point(&self, p: &Vec3) -> Vec3 {
+        self.position + self.transform_vector(p)
+    }
+
+    /// Like `transform_point` without the translation.
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        self.rotation.rotate(&v.zip_map(&self.scale, |a, b| a * b))
+    }
+
+    /// Exact for uniform scales. With a non-uniform scale `S^-1 * R^-1` has
+    /// no `R * S` form, so the result only approximates the inverse matrix.
+    pub fn inverse(&self) -> Self {
+        let rotation = self.rotation.conjugate();
+        let scale = self.scale.map(|s| 1. / s);
+        let position = -rotation
+            .rotate(&self.position)
+            .zip_map(&scale, |a, b| a * b);
+        Self::new(position, rotation, scale)
+    }
+
+    /// Linear interpolation of position and scale, slerp of rotation.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self::new(
+            self.position + (other.position - self.position) * t,
+            self.rotation.slerp(&other.rotation, t),
+            self.scale + (other.scale - self.scale) * t,
+        )
+    }
+
+    pub fn to_mat4(&self) -> Mat4 {
+        let r = self.rotation.to_mat3();
+        let (p, s) = (self.position, self.scale);
+        Mat4::from([
+            [r[0][0] * s[0], r[0][1] * s[1], r[0][2] * s[2], p[0]],
+            [r[1][0] * s[0], r[1][1] * s[1], r[1][2] * s[2], p[1]],
+            [r[2][0] * s[0], r[2][1] * s[1], r[2][2] * s[2], p[2]],
+            [0., 0., 0., 1.],
+        ])
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+// Transform Ops
+
+// Composition: (parent * child).to_mat4() == parent.to_mat4() * child.to_mat4(),
+// exactly so when the parent's scale is uniform.
+impl_op_ex!(*|parent: &Transform, child: &Transform| -> Transform {
+    Transform::new(
+        parent.transform_point(&child.position),
+        parent.rotation * child.rotation,
+        parent.scale.zip_map(&child.scale, |a, b| a * b),
+    )
+});
+
+// Vector Ops
+
+impl_op_ex!(*|transform: &Transform, point: &Vec3| -> Vec3 { transform.transform_point(point) });
+
+// Conversions
+
+impl From<Transform> for Mat4 {
+    fn from(t: Transform) -> Self {
+        t.to_mat4()
+    }
+}
+
+impl From<Transform> for Matrix {
+    fn from(t: Transform) -> Self {
+        t.to_mat4().into()
+    }
+}
+
+impl From<Vec3> for Transform {
+    fn from(position: Vec3) -> Self {
+        Self::from_position(position)
+    }
+}
+
+impl Mat4 {
+    /// Splits an affine matrix into translation, rotation and scale. Returns
+    /// `None` if the bottom row isn't `[0, 0, 0, 1]` or an axis has zero
+    /// scale. Shear can't be represented and is lost. A reflection is
+    /// returned as a negative x scale.
+    pub fn decompose(&self) -> Option<Transform> {
+        if !self.is_affine() {
+            return None;
+        }
+        let mut scale = vector_n![
+            self.col(0).truncate().abs(),
+            self.col(1).truncate().abs(),
+            self.col(2).truncate().abs()
+        ];
+        if scale.as_slice().iter().any(|&s| s <= f32::EPSILON) {
+            return None;
+        }
+        let mut m = self.to_mat3();
+        if m.det() < 0. {
+            scale[0] = -scale[0];
+        }
+        for i in 0..3 {
+            for j in 0..3 {
+                m[i][j] /= scale[j];
+            }
+        }
+        Some(Transform::new(
+            self.col(3).truncate(),
+            Quaternion::from(m),
+            scale,
+        ))
+    }
+}
+
+impl Matrix {
+    /// See `Mat4::decompose`.
+    pub fn decompose(&self) -> Option<Transform> {
+        Mat4::from(self).decompose()
+    }
+}
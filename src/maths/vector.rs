@@ -7,7 +7,7 @@ use std::ops::IndexMut;
 use std::ops::MulAssign;
 use std::ops::SubAssign;
 
-use super::Matrix;
+use super::{lerp, Matrix};
 use crate::matrix;
 
 #[derive(Clone, PartialEq, Debug, Default)]
@@ -122,6 +122,12 @@ impl Vector {
         }
     }
 
+    /// `self` at `t = 0`, `other` at `t = 1`.
+    pub fn lerp(&self, other: &Vector, t: f32) -> Self {
+        assert_eq!(self.len(), other.len());
+        Vector::from_iter((0..self.len()).map(|i| lerp(self[i], other[i], t)))
+    }
+
     // Conversion
 
     pub fn as_slice(&self) -> &[f32] {
@@ -0,0 +1,340 @@
+use bytemuck::{Pod, Zeroable};
+use cgmath::num_traits::ToPrimitive;
+use std::ops;
+use std::ops::AddAssign;
+use std::ops::DivAssign;
+use std::ops::Index;
+use std::ops::IndexMut;
+use std::ops::MulAssign;
+use std::ops::SubAssign;
+
+use super::{lerp, Vector};
+
+/// Fixed-size, stack-allocated counterpart of `Vector`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct VectorN<const N: usize> {
+    data: [f32; N],
+}
+
+pub type Vec2 = VectorN<2>;
+pub type Vec3 = VectorN<3>;
+pub type Vec4 = VectorN<4>;
+
+#[macro_export]
+macro_rules! vector_n {
+    ( $( $x:expr ),* ) => {
+        $crate::maths::VectorN::from([ $( $x ),* ])
+    };
+}
+
+impl<const N: usize> VectorN<N> {
+    pub fn new(val: f32) -> Self {
+        Self { data: [val; N] }
+    }
+
+    pub fn zeroes() -> Self {
+        Self::new(0.)
+    }
+
+    pub fn ones() -> Self {
+        Self::new(1.)
+    }
+
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    pub fn abs(&self) -> f32 {
+        self.data.iter().map(|x| x * x).sum::<f32>().sqrt()
+    }
+
+    pub fn angle(&self, other: &Self) -> f32 {
+        self.unit().dot(&other.unit()).acos()
+    }
+
+    pub fn unit(&self) -> Self {
+        let abs = self.abs();
+        self.map(|x| x / abs)
+    }
+
+    pub fn dot(&self, other: &Self) -> f32 {
+        (0..N).map(|i| self[i] * other[i]).sum()
+    }
+
+    pub fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        Self {
+            data: self.data.map(f),
+        }
+    }
+
+    pub fn zip_map(&self, other: &Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Self {
+            data: std::array::from_fn(|i| f(self[i], other[i])),
+        }
+    }
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        self.zip_map(other, |a, b| lerp(a, b, t))
+    }
+
+    // Conversion
+
+    pub fn as_array(&self) -> &[f32; N] {
+        &self.data
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [f32] {
+        &mut self.data
+    }
+
+    pub fn as_ptr(&self) -> *const f32 {
+        self.data.as_ptr()
+    }
+}
+
+impl Vec2 {
+    /// z component of the 3D cross product, i.e. twice the signed area of
+    /// the triangle `(0, self, other)`. Positive when `other` is
+    /// counter-clockwise from `self`.
+    pub fn cross(&self, other: &Vec2) -> f32 {
+        self[0] * other[1] - self[1] * other[0]
+    }
+
+    /// Rotated 90 degrees counter-clockwise.
+    pub fn perp(&self) -> Vec2 {
+        Vec2::from([-self[1], self[0]])
+    }
+
+    pub fn extend(&self, z: f32) -> Vec3 {
+        Vec3::from([self[0], self[1], z])
+    }
+}
+
+impl Vec3 {
+    pub fn cross(&self, other: &Vec3) -> Vec3 {
+        Vec3::from([
+            self[1] * other[2] - self[2] * other[1],
+            self[2] * other[0] - self[0] * other[2],
+            self[0] * other[1] - self[1] * other[0],
+        ])
+    }
+
+    /// Appends `w`, e.g. `1.` for points and `0.` for directions.
+    pub fn extend(&self, w: f32) -> Vec4 {
+        Vec4::from([self[0], self[1], self[2], w])
+    }
+}
+
+impl Vec4 {
+    /// Drops the `w` component.
+    pub fn truncate(&self) -> Vec3 {
+        Vec3::from([self[0], self[1], self[2]])
+    }
+}
+
+impl<const N: usize> Default for VectorN<N> {
+    fn default() -> Self {
+        Self::zeroes()
+    }
+}
+
+// Ops
+//
+// impl_op_ex! only takes concrete types, so the operator set is stamped out
+// for each of the sizes we actually use.
+
+macro_rules! impl_vector_n_ops {
+    ( $( $t:ident ),* ) => { $(
+        // Unary Ops
+
+        impl_op_ex!(-|vector: &$t| -> $t { vector.map(|x| -x) });
+
+        // Scalar Ops
+
+        impl_op_ex_commutative!(+ |vector: &$t, scalar: &f32| -> $t { vector.map(|x| x + scalar) });
+
+        impl_op_ex_commutative!(+ |vector: &$t, scalar: &i32| -> $t {
+            vector.map(|x| x + *scalar as f32)
+        });
+
+        impl_op_ex!(-|vector: &$t, scalar: &f32| -> $t { vector + (-scalar) });
+
+        impl_op_ex!(-|vector: &$t, scalar: &i32| -> $t { vector + (-scalar as f32) });
+
+        impl_op_ex!(-|scalar: &f32, vector: &$t| -> $t { scalar + (-vector) });
+
+        impl_op_ex!(-|scalar: &i32, vector: &$t| -> $t { *scalar as f32 + (-vector) });
+
+        impl_op_ex_commutative!(*|vector: &$t, scalar: &f32| -> $t { vector.map(|x| x * scalar) });
+
+        impl_op_ex_commutative!(*|vector: &$t, scalar: &i32| -> $t {
+            vector.map(|x| x * *scalar as f32)
+        });
+
+        impl_op_ex!(/ |vector: &$t, scalar: &f32| -> $t { vector.map(|x| x / scalar) });
+
+        impl_op_ex!(/ |vector: &$t, scalar: &i32| -> $t { vector.map(|x| x / *scalar as f32) });
+
+        // Vector Ops
+
+        impl_op_ex!(+ |lhs: &$t, rhs: &$t| -> $t { lhs.zip_map(rhs, |a, b| a + b) });
+
+        impl_op_ex!(-|lhs: &$t, rhs: &$t| -> $t { lhs.zip_map(rhs, |a, b| a - b) });
+
+        impl_op_ex!(*|lhs: &$t, rhs: &$t| -> f32 { lhs.dot(rhs) });
+    )* };
+}
+
+impl_vector_n_ops!(Vec2, Vec3, Vec4);
+
+// Scalar Ops Assignment
+
+impl<T: ToPrimitive, const N: usize> AddAssign<T> for VectorN<N> {
+    fn add_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        for i in 0..N {
+            self[i] += scalar_f32;
+        }
+    }
+}
+
+impl<T: ToPrimitive, const N: usize> SubAssign<T> for VectorN<N> {
+    fn sub_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        for i in 0..N {
+            self[i] -= scalar_f32;
+        }
+    }
+}
+
+impl<T: ToPrimitive, const N: usize> MulAssign<T> for VectorN<N> {
+    fn mul_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        for i in 0..N {
+            self[i] *= scalar_f32;
+        }
+    }
+}
+
+impl<T: ToPrimitive, const N: usize> DivAssign<T> for VectorN<N> {
+    fn div_assign(&mut self, scalar: T) {
+        let scalar_f32 = scalar.to_f32().unwrap();
+        for i in 0..N {
+            self[i] /= scalar_f32;
+        }
+    }
+}
+
+// Vector Ops Assignment
+
+impl<const N: usize> AddAssign for VectorN<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for i in 0..N {
+            self[i] += rhs[i];
+        }
+    }
+}
+
+impl<const N: usize> SubAssign for VectorN<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for i in 0..N {
+            self[i] -= rhs[i];
+        }
+    }
+}
+
+// Indexing
+
+impl<const N: usize> Index<usize> for VectorN<N> {
+    type Output = f32;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.data[index]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for VectorN<N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.data[index]
+    }
+}
+
+// SAFETY: a transparent wrapper around [f32; N], so it can be uploaded to GPU
+// buffers as is
+unsafe impl<const N: usize> Zeroable for VectorN<N> {}
+unsafe impl<const N: usize> Pod for VectorN<N> {}
+
+// Conversions
+
+// - From
+
+impl<const N: usize> From<[f32; N]> for VectorN<N> {
+    fn from(value: [f32; N]) -> Self {
+        Self { data: value }
+    }
+}
+
+impl<const N: usize> From<&VectorN<N>> for VectorN<N> {
+    fn from(value: &VectorN<N>) -> Self {
+        *value
+    }
+}
+
+impl<const N: usize> From<&[f32]> for VectorN<N> {
+    fn from(value: &[f32]) -> Self {
+        assert_eq!(value.len(), N);
+        Self {
+            data: std::array::from_fn(|i| value[i]),
+        }
+    }
+}
+
+impl<const N: usize> From<&Vector> for VectorN<N> {
+    fn from(value: &Vector) -> Self {
+        Self::from(value.as_slice())
+    }
+}
+
+impl<const N: usize> From<Vector> for VectorN<N> {
+    fn from(value: Vector) -> Self {
+        Self::from(value.as_slice())
+    }
+}
+
+// - Into / Reverse From
+
+impl<const N: usize> From<VectorN<N>> for [f32; N] {
+    fn from(val: VectorN<N>) -> Self {
+        val.data
+    }
+}
+
+impl<const N: usize> From<VectorN<N>> for Vector {
+    fn from(val: VectorN<N>) -> Self {
+        Vector::from(val.as_slice())
+    }
+}
+
+impl<const N: usize> From<&VectorN<N>> for Vector {
+    fn from(val: &VectorN<N>) -> Self {
+        Vector::from(val.as_slice())
+    }
+}
+
+impl<const N: usize> IntoIterator for VectorN<N> {
+    type Item = f32;
+    type IntoIter = std::array::IntoIter<f32, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
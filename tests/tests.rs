@@ -1,5 +1,43 @@
 #[cfg(test)]
 mod tests {
+    fn assert_close(a: &[f32], b: &[f32], tolerance: f32) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < tolerance, "{:?} != {:?}", a, b);
+        }
+    }
+
+    /// Runs `test` with a current GL 4.6 context on a hidden window, e.g.
+    /// Mesa's llvmpipe under Xvfb. Skipped without a display.
+    fn with_gl(test: impl FnOnce()) {
+        use glfw::Context;
+        use std::sync::{Mutex, PoisonError};
+
+        // GLFW isn't thread safe and tests run in parallel
+        static GL: Mutex<()> = Mutex::new(());
+        let _lock = GL.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let Ok(mut glfw) = glfw::init(glfw::log_errors) else {
+            eprintln!("No GLFW, skipping GL test");
+            return;
+        };
+        glfw.window_hint(glfw::WindowHint::ContextVersion(4, 6));
+        glfw.window_hint(glfw::WindowHint::OpenGlProfile(
+            glfw::OpenGlProfileHint::Core,
+        ));
+        glfw.window_hint(glfw::WindowHint::Visible(false));
+        let Some((mut window, _events)) =
+            glfw.create_window(1, 1, "test", glfw::WindowMode::Windowed)
+        else {
+            eprintln!("No GL 4.6 context, skipping GL test");
+            return;
+        };
+        window.make_current();
+        gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+        doom_engine::graphics::wrapper::invalidate_state();
+        test();
+    }
+
     mod vector_tests {
         use doom_engine::maths::*;
         use doom_engine::vector;
@@ -155,5 +193,2121 @@ mod tests {
             let result = matrix![[4.0, -1.0], [-7.0, 2.0]];
             assert_eq!(m.inverse(), result);
         }
+
+        fn tridiagonal(n: usize) -> Matrix {
+            let mut m = Matrix::zeroes(n, n);
+            for i in 0..n {
+                m[i][i] = 2.;
+                if i + 1 < n {
+                    m[i][i + 1] = -1.;
+                    m[i + 1][i] = -1.;
+                }
+            }
+            m
+        }
+
+        fn assert_identity(m: &Matrix) {
+            let id = Matrix::identity(m.rows());
+            for (x, y) in m.as_slice().iter().zip(id.as_slice()) {
+                assert!((x - y).abs() < 1e-4, "{:?}", m);
+            }
+        }
+
+        #[test]
+        fn determinant_large() {
+            // det of the n x n [-1, 2, -1] tridiagonal matrix is n + 1
+            assert!((tridiagonal(4).det() - 5.).abs() < 1e-4);
+            assert!((tridiagonal(7).det() - 8.).abs() < 1e-4);
+            let mut m = tridiagonal(6);
+            m.swap_rows(0, 1);
+            assert!((m.det() + 7.).abs() < 1e-4);
+        }
+
+        #[test]
+        fn try_inverse() {
+            for n in 1..8 {
+                let m = tridiagonal(n);
+                assert_identity(&(&m * m.try_inverse().unwrap()));
+            }
+            let m = matrix![
+                [0., 2., 0., 1.],
+                [1., 0., 0., 0.],
+                [0., 1., 3., 0.],
+                [1., 0., 1., 2.]
+            ];
+            assert_identity(&(&m * m.inverse()));
+            assert_eq!(matrix![[1., 2.], [2., 4.]].try_inverse(), None);
+            assert_eq!(matrix![[0.]].try_inverse(), None);
+            let mut singular = tridiagonal(6);
+            let row = singular.row(4);
+            singular[5].copy_from_slice(&row);
+            assert_eq!(singular.try_inverse(), None);
+        }
+
+        #[test]
+        fn affine_inverse() {
+            let m = Matrix::model(
+                vector![1., -2., 3.],
+                vector![0.3, 0.2, 0.1],
+                vector![2., 1., 0.5],
+            );
+            assert_identity(&(&m * m.try_affine_inverse().unwrap()));
+            let flat = Matrix::scaling(vector![1., 0., 1.]);
+            assert_eq!(flat.try_inverse(), None);
+            assert!(flat.to_normal().as_slice().iter().all(|x| x.is_finite()));
+        }
+
+        #[test]
+        fn solve() {
+            let m = tridiagonal(5);
+            let x = m.solve(&vector![1., 0., 0., 0., 1.]).unwrap();
+            for i in 0..5 {
+                assert!((x[i] - 1.).abs() < 1e-5);
+            }
+        }
+    }
+
+    mod vector_n_tests {
+        use doom_engine::maths::*;
+        use doom_engine::{vector, vector_n};
+
+        #[test]
+        fn make_vector_n() {
+            let a: Vec3 = vector_n![1., 2., 3.];
+            assert_eq!(a.len(), 3);
+            assert_eq!(a.abs(), 14_f32.sqrt());
+            assert_eq!(a[2], 3.);
+            assert_eq!(Vec4::ones(), vector_n![1., 1., 1., 1.]);
+        }
+
+        #[test]
+        fn ops() {
+            let a = vector_n![1., 2., 3.];
+            let b = vector_n![1., 2., 3.];
+            assert_eq!(a + 2, vector_n![3., 4., 5.]);
+            assert_eq!(1. - a, vector_n![0., -1., -2.]);
+            assert_eq!(a * 3, vector_n![3., 6., 9.]);
+            assert_eq!(a + b, vector_n![2., 4., 6.]);
+            assert_eq!(a * b, 14.);
+            assert_eq!(a.cross(&b), vector_n![0., 0., 0.]);
+            let mut c = a;
+            c += b;
+            c /= 2;
+            assert_eq!(c, a);
+        }
+
+        #[test]
+        fn pod_cast() {
+            let vertices: [Vec3; 2] = [vector_n![1., 2., 3.], vector_n![4., 5., 6.]];
+            let floats: &[f32] = bytemuck::cast_slice(&vertices);
+            assert_eq!(floats, &[1., 2., 3., 4., 5., 6.]);
+            let m = Mat2::identity();
+            let m: &[f32] = bytemuck::cast_slice(std::slice::from_ref(&m));
+            assert_eq!(m, &[1., 0., 0., 1.]);
+        }
+
+        #[test]
+        fn conversions() {
+            let a = vector![1., 2., 3.];
+            let b: Vec3 = (&a).into();
+            assert_eq!(b, vector_n![1., 2., 3.]);
+            assert_eq!(Vector::from(b), a);
+        }
+    }
+
+    mod matrix_n_tests {
+        use doom_engine::maths::*;
+        use doom_engine::{matrix_n, vector, vector_n};
+
+        /// NDC depth of a view space point.
+        fn ndc_depth(proj: &Mat4, z: f32) -> f32 {
+            let clip = proj * vector_n![0.3, -0.2, z, 1.];
+            clip[2] / clip[3]
+        }
+
+        #[test]
+        fn projection_variants() {
+            let fov = std::f32::consts::FRAC_PI_2;
+            let (near, far) = (0.1, 100.);
+            let perspective = Mat4::projection_perspective(fov, 1.5, near, far);
+            let t = near * (fov / 2.).tan();
+            let frustum = Mat4::projection_frustum(-1.5 * t, 1.5 * t, -t, t, near, far);
+            for (a, b) in frustum.as_slice().iter().zip(perspective.as_slice()) {
+                assert!((a - b).abs() < 1e-5);
+            }
+
+            let infinite = Mat4::projection_perspective_infinite(fov, 1.5, near);
+            assert!((ndc_depth(&infinite, -near) + 1.).abs() < 1e-5);
+            assert!(ndc_depth(&infinite, -1e6) < 1.);
+            assert!(ndc_depth(&infinite, -1e6) > 0.999);
+
+            let reversed = Mat4::projection_perspective_reversed(fov, 1.5, near, far);
+            assert!((ndc_depth(&reversed, -near) - 1.).abs() < 1e-5);
+            assert!(ndc_depth(&reversed, -far).abs() < 1e-5);
+            let reversed = Mat4::projection_perspective_reversed_infinite(fov, 1.5, near);
+            assert!((ndc_depth(&reversed, -near) - 1.).abs() < 1e-5);
+            assert!(ndc_depth(&reversed, -1e6) < 1e-6);
+
+            let m = Matrix::projection_perspective_reversed_infinite(fov, 1.5, near);
+            assert_eq!(Mat4::from(m), reversed);
+        }
+
+        #[test]
+        fn oblique_and_jitter() {
+            let proj = Mat4::projection_perspective(1.2, 1., 0.1, 100.);
+            // Mirror plane z = -5 facing away from the camera
+            let plane = vector_n![0., 0., -1., -5.];
+            for (proj, depth) in [
+                (proj, DepthRange::NegativeOneToOne),
+                (
+                    Mat4::projection_perspective_reversed_infinite(1.2, 1., 0.1),
+                    DepthRange::Reversed,
+                ),
+            ] {
+                let oblique = proj.oblique_near_plane(&plane, depth);
+                let (near, _) = depth.near_far();
+                assert!((ndc_depth(&oblique, -5.) - near).abs() < 1e-4);
+                // x and y are untouched
+                assert_eq!(oblique.row(0), proj.row(0));
+                assert_eq!(oblique.row(1), proj.row(1));
+            }
+
+            let offset = vector_n![0.01, -0.02];
+            let jittered = proj.jittered(&offset);
+            let p = vector_n![0.5, 0.25, -3., 1.];
+            let (a, b) = (proj * p, jittered * p);
+            assert!((b[0] / b[3] - a[0] / a[3] - 0.01).abs() < 1e-6);
+            assert!((b[1] / b[3] - a[1] / a[3] + 0.02).abs() < 1e-6);
+            assert_eq!(a[2], b[2]);
+        }
+
+        #[test]
+        fn matrix_n_macro() {
+            let m: Mat2 = matrix_n![[1., 2.], [3., 4.]];
+            assert_eq!(m[0], [1., 2.]);
+            assert_eq!(m.col(1), vector_n![2., 4.]);
+            assert_eq!(m.transpose(), matrix_n![[1., 3.], [2., 4.]]);
+        }
+
+        #[test]
+        fn mul() {
+            let m1: Mat3 = matrix_n![[1., 4., 7.], [2., 5., 8.], [3., 6., 9.]];
+            let m2: Mat3 = matrix_n![[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]];
+            let result12 = matrix_n![[66., 78., 90.], [78., 93., 108.], [90., 108., 126.]];
+            assert_eq!(m1 * m2, result12);
+            assert_eq!(m2 * vector_n![1., 0., 0.], vector_n![1., 4., 7.]);
+        }
+
+        #[test]
+        fn inverse_mat3() {
+            let m: Mat3 = matrix_n![[2., -1., 0.], [-1., 2., -1.], [0., -1., 2.]];
+            assert_eq!(m.det(), 4.);
+            assert_eq!(m * m.inverse(), Mat3::identity());
+        }
+
+        #[test]
+        fn matches_dynamic_matrix() {
+            let t = vector![1., 2., 3.];
+            let m = Matrix::model(t.clone(), vector![0.5, 0.25, 0.], vector![2., 2., 2.]);
+            let m4 = Mat4::model(t.into(), vector_n![0.5, 0.25, 0.], vector_n![2., 2., 2.]);
+            assert_eq!(Matrix::from(m4), m);
+            assert_eq!(Mat4::from(&m), m4);
+            assert_eq!(m4.transform_point(&Vec3::zeroes()), vector_n![1., 2., 3.]);
+        }
+    }
+
+    mod quaternion_tests {
+        use super::assert_close;
+        use doom_engine::maths::*;
+        use doom_engine::{vector, vector_n};
+        use std::f32::consts::FRAC_PI_2;
+
+        #[test]
+        fn rotate_vector() {
+            let q = Quaternion::from_axis_angle(&vector_n![0., 0., 1.], FRAC_PI_2);
+            assert_close((q * vector_n![1., 0., 0.]).as_slice(), &[0., 1., 0.], 1e-5);
+            let (axis, angle) = q.to_axis_angle();
+            assert_close(axis.as_slice(), &[0., 0., 1.], 1e-5);
+            assert!((angle - FRAC_PI_2).abs() < 1e-5);
+        }
+
+        #[test]
+        fn composition() {
+            let a = Quaternion::from_axis_angle(&vector_n![0., 1., 0.], 0.3);
+            let b = Quaternion::from_axis_angle(&vector_n![1., 0., 0.], 1.1);
+            let v = vector_n![1., 2., 3.];
+            assert_close(
+                (a * b * v).as_slice(),
+                a.rotate(&b.rotate(&v)).as_slice(),
+                1e-5,
+            );
+            assert_close((a * a.inverse() * v).as_slice(), v.as_slice(), 1e-5);
+        }
+
+        #[test]
+        fn euler_matches_matrix() {
+            let angles = vector_n![0.4, -0.7, 1.2];
+            let q = Quaternion::from_euler(&angles);
+            let m = Matrix::rotation(vector![0.4, -0.7, 1.2]);
+            assert_close(Matrix::from(q).as_slice(), m.as_slice(), 1e-5);
+            assert_close(q.to_euler().as_slice(), angles.as_slice(), 1e-5);
+        }
+
+        #[test]
+        fn matrix_round_trip() {
+            let q = Quaternion::from_axis_angle(&vector_n![1., -2., 0.5], 2.9);
+            let r = Quaternion::from(q.to_mat3());
+            assert!(q.dot(&r).abs() > 1. - 1e-5);
+            let r = Quaternion::from(&Matrix::from(q));
+            assert!(q.dot(&r).abs() > 1. - 1e-5);
+        }
+
+        #[test]
+        fn slerp() {
+            let a = Quaternion::identity();
+            let b = Quaternion::from_axis_angle(&vector_n![0., 1., 0.], FRAC_PI_2);
+            let half = Quaternion::from_axis_angle(&vector_n![0., 1., 0.], FRAC_PI_2 / 2.);
+            assert!(a.slerp(&b, 0.5).dot(&half) > 1. - 1e-5);
+            assert!(a.slerp(&-b, 0.5).dot(&half) > 1. - 1e-5);
+            assert!(a.nlerp(&b, 0.5).dot(&half) > 1. - 1e-5);
+        }
+
+        #[test]
+        fn look_rotation() {
+            let up = vector_n![0., 1., 0.];
+            let q = Quaternion::look_rotation(&vector_n![0., 0., -1.], &up);
+            assert!(q.dot(&Quaternion::identity()) > 1. - 1e-5);
+            let q = Quaternion::look_rotation(&vector_n![1., 0., 0.], &up);
+            assert_close((q * vector_n![0., 0., -1.]).as_slice(), &[1., 0., 0.], 1e-5);
+            assert_close((q * up).as_slice(), up.as_slice(), 1e-5);
+
+            // Straight up and down, where forward and up are parallel
+            for y in [1., -1.] {
+                let q = Quaternion::look_rotation(&vector_n![0., y * 2., 0.], &up);
+                assert!(q.abs().is_finite());
+                assert_close((q * vector_n![0., 0., -1.]).as_slice(), &[0., y, 0.], 1e-5);
+                // Like pitching from looking along -z, x stays to the right
+                assert_close((q * vector_n![1., 0., 0.]).as_slice(), &[1., 0., 0.], 1e-5);
+            }
+        }
+    }
+
+    mod transform_tests {
+        use super::assert_close;
+        use doom_engine::maths::*;
+        use doom_engine::vector_n;
+
+        fn sample() -> Transform {
+            Transform::new(
+                vector_n![1., -2., 3.],
+                Quaternion::from_euler(&vector_n![0.3, -0.5, 1.1]),
+                vector_n![2., 0.5, 1.5],
+            )
+        }
+
+        #[test]
+        fn matches_model_matrix() {
+            let t = sample();
+            let angles = t.rotation().to_euler();
+            let m = Mat4::model(t.position(), angles, t.scale());
+            assert_close(t.to_mat4().as_slice(), m.as_slice(), 1e-5);
+            let p = vector_n![0.5, 1., -1.];
+            assert_close((t * p).as_slice(), m.transform_point(&p).as_slice(), 1e-5);
+        }
+
+        #[test]
+        fn composition_and_inverse() {
+            let parent = Transform::new(
+                vector_n![0., 1., 0.],
+                Quaternion::from_axis_angle(&vector_n![0., 1., 0.], 0.7),
+                Vec3::new(2.),
+            );
+            let child = sample();
+            assert_close(
+                (parent * child).to_mat4().as_slice(),
+                (parent.to_mat4() * child.to_mat4()).as_slice(),
+                1e-5,
+            );
+            assert_close(
+                (parent * parent.inverse()).to_mat4().as_slice(),
+                Mat4::identity().as_slice(),
+                1e-5,
+            );
+        }
+
+        #[test]
+        fn decompose() {
+            let t = sample();
+            let d = t.to_mat4().decompose().unwrap();
+            assert_close(d.position().as_slice(), t.position().as_slice(), 1e-5);
+            assert_close(d.scale().as_slice(), t.scale().as_slice(), 1e-5);
+            assert!(d.rotation().dot(&t.rotation()).abs() > 1. - 1e-5);
+            let mirrored = Mat4::scaling(vector_n![-1., 1., 1.]);
+            assert_close(
+                mirrored.decompose().unwrap().to_mat4().as_slice(),
+                mirrored.as_slice(),
+                1e-5,
+            );
+            assert_eq!(Mat4::scaling(vector_n![1., 0., 1.]).decompose(), None);
+        }
+
+        #[test]
+        fn lerp() {
+            let a = Transform::identity();
+            let b = sample();
+            assert_eq!(a.lerp(&b, 0.), a);
+            let end = a.lerp(&b, 1.);
+            assert_close(end.position().as_slice(), b.position().as_slice(), 1e-5);
+            assert!(end.rotation().dot(&b.rotation()) > 1. - 1e-5);
+        }
+    }
+
+    mod geometry_tests {
+        use super::assert_close;
+        use doom_engine::maths::geometry::*;
+        use doom_engine::maths::*;
+        use doom_engine::vector_n;
+
+        fn unit_box() -> Aabb {
+            Aabb::new(Vec3::new(-1.), Vec3::new(1.))
+        }
+
+        #[test]
+        fn ray_plane_and_box() {
+            let ray = Ray::new(vector_n![0., 0., 5.], vector_n![0., 0., -2.]);
+            let floor = Plane::from_point_normal(&vector_n![0., 0., 1.], &vector_n![0., 0., 1.]);
+            assert_eq!(ray.intersect_plane(&floor), Some(4.));
+            assert_eq!(ray.intersect_aabb(&unit_box()), Some(4.));
+            let inside = Ray::new(Vec3::zeroes(), vector_n![1., 0., 0.]);
+            assert_eq!(inside.intersect_aabb(&unit_box()), Some(0.));
+            let miss = Ray::new(vector_n![0., 2., 5.], vector_n![0., 0., -1.]);
+            assert_eq!(miss.intersect_aabb(&unit_box()), None);
+            let away = Ray::new(vector_n![0., 0., 5.], vector_n![0., 0., 1.]);
+            assert_eq!(away.intersect_plane(&floor), None);
+            assert_eq!(away.intersect_aabb(&unit_box()), None);
+        }
+
+        #[test]
+        fn ray_triangle_and_sphere() {
+            let tri = Triangle::new(
+                vector_n![-1., -1., 0.],
+                vector_n![1., -1., 0.],
+                vector_n![0., 1., 0.],
+            );
+            let ray = Ray::new(vector_n![0., 0., 3.], vector_n![0., 0., -1.]);
+            assert_eq!(ray.intersect_triangle(&tri), Some(3.));
+            let miss = Ray::new(vector_n![0.9, 0.9, 3.], vector_n![0., 0., -1.]);
+            assert_eq!(miss.intersect_triangle(&tri), None);
+            let sphere = Sphere::new(vector_n![0., 0., -2.], 1.);
+            assert_eq!(ray.intersect_sphere(&sphere), Some(4.));
+            assert_eq!(miss.intersect_sphere(&sphere), None);
+        }
+
+        #[test]
+        fn closest_points() {
+            let p = vector_n![3., 0.5, -4.];
+            assert_close(
+                unit_box().closest_point(&p).as_slice(),
+                &[1., 0.5, -1.],
+                1e-5,
+            );
+            let plane = Plane::new(vector_n![0., 2., 0.], -2.);
+            assert_eq!(plane.distance(&p), -0.5);
+            assert_close(plane.closest_point(&p).as_slice(), &[3., 1., -4.], 1e-5);
+            let sphere = Sphere::new(Vec3::zeroes(), 2.);
+            assert_close(
+                sphere.closest_point(&vector_n![0., 0., 4.]).as_slice(),
+                &[0., 0., 2.],
+                1e-5,
+            );
+            let tri = Triangle::new(
+                vector_n![0., 0., 0.],
+                vector_n![2., 0., 0.],
+                vector_n![0., 2., 0.],
+            );
+            assert_close(
+                tri.closest_point(&vector_n![0.5, 0.5, 3.]).as_slice(),
+                &[0.5, 0.5, 0.],
+                1e-5,
+            );
+            assert_close(
+                tri.closest_point(&vector_n![2., 2., 0.]).as_slice(),
+                &[1., 1., 0.],
+                1e-5,
+            );
+            assert_close(
+                tri.closest_point(&vector_n![-1., -1., 1.]).as_slice(),
+                &[0., 0., 0.],
+                1e-5,
+            );
+            assert_close(
+                tri.barycentric(&vector_n![0.5, 0.5, 0.]).as_slice(),
+                &[0.5, 0.25, 0.25],
+                1e-5,
+            );
+        }
+
+        #[test]
+        fn transform_aabb() {
+            let m = Mat4::translation(vector_n![1., 2., 3.])
+                * Mat4::rotation_z(std::f32::consts::FRAC_PI_4)
+                * Mat4::scaling(vector_n![2., 1., 1.]);
+            let aabb = unit_box().transform(m);
+            let expected = Aabb::from_points(
+                &[
+                    vector_n![-1., -1., -1.],
+                    vector_n![-1., 1., -1.],
+                    vector_n![1., -1., -1.],
+                    vector_n![1., 1., 1.],
+                ]
+                .map(|p| m.transform_point(&p)),
+            )
+            .unwrap();
+            assert_close(aabb.min().as_slice(), expected.min().as_slice(), 1e-5);
+            assert_close(aabb.max().as_slice(), expected.max().as_slice(), 1e-5);
+            assert_eq!(unit_box().transform(Matrix::from(m)), aabb);
+        }
+
+        #[test]
+        fn frustum_culling() {
+            let view = Mat4::look_at(
+                &vector_n![0., 0., 5.],
+                &Vec3::zeroes(),
+                &vector_n![0., 1., 0.],
+            );
+            let proj = Mat4::projection_perspective(std::f32::consts::FRAC_PI_2, 1., 0.1, 100.);
+            let frustum = Frustum::from_matrix(proj * view, DepthRange::NegativeOneToOne);
+            assert!(frustum.contains(&Vec3::zeroes()));
+            assert!(!frustum.contains(&vector_n![0., 0., 6.]));
+            assert!(frustum.intersects_aabb(&unit_box()));
+            assert!(frustum.intersects_sphere(&Sphere::new(vector_n![0., 0., 5.5], 1.)));
+            let behind = Aabb::from_center(vector_n![0., 0., 10.], Vec3::new(1.));
+            assert!(!frustum.intersects_aabb(&behind));
+            assert!(!frustum.intersects_sphere(&Sphere::new(vector_n![20., 0., 0.], 1.)));
+            assert!(!frustum.intersects_sphere(&Sphere::new(vector_n![0., 0., -200.], 1.)));
+        }
+
+        #[test]
+        fn infinite_frustum() {
+            let fov = std::f32::consts::FRAC_PI_2;
+            for (proj, depth) in [
+                (
+                    Mat4::projection_perspective_infinite(fov, 1., 0.1),
+                    DepthRange::NegativeOneToOne,
+                ),
+                (
+                    Mat4::projection_perspective_reversed_infinite(fov, 1., 0.1),
+                    DepthRange::Reversed,
+                ),
+            ] {
+                let frustum = Frustum::from_matrix(proj, depth);
+                assert_eq!(frustum.planes().len(), 5);
+                assert!(frustum.far().is_none());
+                assert!(frustum
+                    .planes()
+                    .iter()
+                    .all(|plane| plane.normal().abs().is_finite()));
+                assert!(frustum.contains(&vector_n![0., 0., -1.]));
+                assert!(frustum.contains(&vector_n![0., 0., -1e6]));
+                assert!(!frustum.contains(&vector_n![0., 0., -0.05]));
+                assert!(!frustum.contains(&vector_n![0., 0., 1.]));
+                assert!(!frustum.contains(&vector_n![2., 0., -1.]));
+            }
+        }
+
+        #[test]
+        fn reversed_frustum() {
+            let fov = std::f32::consts::FRAC_PI_2;
+            let proj = Mat4::projection_perspective_reversed(fov, 1., 0.1, 100.);
+            let frustum = Frustum::from_matrix(proj, DepthRange::Reversed);
+            let regular = Frustum::from_matrix(
+                Mat4::projection_perspective(fov, 1., 0.1, 100.),
+                DepthRange::NegativeOneToOne,
+            );
+            for (a, b) in frustum.planes().iter().zip(regular.planes()) {
+                assert!((a.normal() - b.normal()).abs() < 1e-4);
+                assert!((a.d() - b.d()).abs() < 1e-4);
+            }
+            assert!(frustum.contains(&vector_n![0., 0., -50.]));
+            assert!(!frustum.contains(&vector_n![0., 0., -0.05]));
+            assert!(!frustum.contains(&vector_n![0., 0., -150.]));
+        }
+
+        #[test]
+        fn segment_side_and_distance() {
+            let line = Segment2::new(vector_n![0., 0.], vector_n![4., 0.]);
+            assert_eq!(line.side(&vector_n![1., 1.], 0.), Side::Left);
+            assert_eq!(line.side(&vector_n![1., -1.], 0.), Side::Right);
+            assert_eq!(line.side(&vector_n![9., 0.], 0.), Side::On);
+            assert_eq!(line.side(&vector_n![1., 0.01], 0.1), Side::On);
+            assert_eq!(line.distance(&vector_n![2., 3.]), 3.);
+            assert_eq!(line.distance(&vector_n![7., 4.]), 5.);
+            assert_eq!(line.distance(&vector_n![-3., -4.]), 5.);
+        }
+
+        #[test]
+        fn segment_intersection() {
+            let a = Segment2::new(vector_n![0., 0.], vector_n![4., 4.]);
+            let b = Segment2::new(vector_n![0., 4.], vector_n![4., 0.]);
+            assert_eq!(
+                a.intersect(&b, 0.),
+                Some(SegmentIntersection::Point(vector_n![2., 2.]))
+            );
+            // Touching at an endpoint
+            let c = Segment2::new(vector_n![4., 4.], vector_n![8., 0.]);
+            assert_eq!(
+                a.intersect(&c, 0.),
+                Some(SegmentIntersection::Point(vector_n![4., 4.]))
+            );
+            let d = Segment2::new(vector_n![5., 5.], vector_n![8., 0.]);
+            assert_eq!(a.intersect(&d, 0.), None);
+            // Collinear
+            let e = Segment2::new(vector_n![6., 6.], vector_n![2., 2.]);
+            assert_eq!(
+                a.intersect(&e, 0.),
+                Some(SegmentIntersection::Overlap(Segment2::new(
+                    vector_n![2., 2.],
+                    vector_n![4., 4.]
+                )))
+            );
+            let f = Segment2::new(vector_n![5., 5.], vector_n![6., 6.]);
+            assert_eq!(a.intersect(&f, 0.), None);
+            assert!(a.intersects(&f, 1.5));
+            // Near miss only counts with a tolerance
+            let g = Segment2::new(vector_n![4.01, 4.], vector_n![8., 0.]);
+            assert!(!a.intersects(&g, 0.));
+            assert!(a.intersects(&g, 0.1));
+        }
+
+        #[test]
+        fn polygon_queries() {
+            // L shape, counter-clockwise
+            let mut poly = Polygon2::new(vec![
+                vector_n![0., 0.],
+                vector_n![4., 0.],
+                vector_n![4., 2.],
+                vector_n![2., 2.],
+                vector_n![2., 4.],
+                vector_n![0., 4.],
+            ]);
+            assert_eq!(poly.signed_area(), 12.);
+            assert_eq!(poly.winding(), Some(Winding::CounterClockwise));
+            assert!(!poly.is_convex());
+            assert!(poly.contains(&vector_n![1., 1.]));
+            assert!(poly.contains(&vector_n![1., 3.]));
+            assert!(!poly.contains(&vector_n![3., 3.]));
+            assert!(poly.contains(&vector_n![4., 1.]));
+            assert!(poly.contains(&vector_n![2., 3.]));
+            assert_eq!(poly.distance(&vector_n![3., 3.]), Some(1.));
+
+            poly.set_winding(Winding::Clockwise);
+            assert_eq!(poly.signed_area(), -12.);
+            assert_eq!(poly.vertices()[0], vector_n![0., 4.]);
+            assert!(poly.contains(&vector_n![1., 1.]));
+            assert!(!poly.contains(&vector_n![3., 3.]));
+
+            let square = Polygon2::from(vec![
+                vector_n![0., 0.],
+                vector_n![1., 0.],
+                vector_n![1., 1.],
+                vector_n![0., 1.],
+            ]);
+            assert!(square.is_convex());
+            assert_eq!(Polygon2::new(vec![]).winding(), None);
+        }
+
+        fn square(min: f32, max: f32) -> Polygon2 {
+            Polygon2::new(vec![
+                vector_n![min, min],
+                vector_n![max, min],
+                vector_n![max, max],
+                vector_n![min, max],
+            ])
+        }
+
+        fn assert_covers(t: &Triangulation, area: f32) {
+            assert_eq!(t.indices().len() % 3, 0);
+            let mut total = 0.;
+            for [a, b, c] in t.triangles() {
+                let tri = Polygon2::new(vec![a, b, c]);
+                assert!(tri.signed_area() > 0., "{:?} is not counter-clockwise", tri);
+                total += tri.signed_area();
+            }
+            assert!((total - area).abs() < 1e-3, "{} != {}", total, area);
+        }
+
+        #[test]
+        fn triangulate_concave() {
+            let mut poly = Polygon2::new(vec![
+                vector_n![0., 0.],
+                vector_n![4., 0.],
+                vector_n![4., 2.],
+                vector_n![2., 2.],
+                vector_n![2., 4.],
+                vector_n![0., 4.],
+            ]);
+            poly.reverse();
+            let indices = poly.triangulate();
+            assert_eq!(indices.len(), 12);
+            assert!(indices.iter().all(|&i| (0..6).contains(&i)));
+            assert_covers(&Triangulation::new(&[poly]), 12.);
+        }
+
+        #[test]
+        fn triangulate_holes_and_islands() {
+            // Sector with a pillar that has an island inside, plus a
+            // separate sector next to it sharing an edge
+            let loops = [
+                square(0., 10.),
+                square(3., 7.),
+                square(4., 6.),
+                Polygon2::new(vec![
+                    vector_n![10., 0.],
+                    vector_n![12., 0.],
+                    vector_n![12., 10.],
+                    vector_n![10., 10.],
+                ]),
+            ];
+            let t = Triangulation::new(&loops);
+            assert_eq!(t.vertices().len(), 16);
+            assert_covers(&t, 100. - 16. + 4. + 20.);
+            // 8 triangles for the ring, 2 for the island, 2 for the sector
+            assert_eq!(t.indices().len(), 36);
+        }
+
+        #[test]
+        fn triangulate_hole_touching_outer() {
+            // Pillar sharing a vertex with the wall
+            let loops = [
+                square(0., 4.),
+                Polygon2::new(vec![
+                    vector_n![0., 0.],
+                    vector_n![2., 1.],
+                    vector_n![1., 2.],
+                ]),
+            ];
+            let t = Triangulation::new(&loops);
+            assert_covers(&t, 16. - 1.5);
+        }
+    }
+
+    mod fixed_tests {
+        use doom_engine::maths::fixed::*;
+        use doom_engine::maths::*;
+        use doom_engine::vector_n;
+
+        #[test]
+        fn fixed_mul_div() {
+            let a = Fixed::from(2.5);
+            let b = Fixed::from(-1.25);
+            assert_eq!(f32::from(a * b), -3.125);
+            assert_eq!(f32::from(a / b), -2.);
+            assert_eq!((a * 3).to_bits(), 3 * a.to_bits());
+            assert_eq!(Fixed::from_int(-3).to_int(), -3);
+            assert_eq!(Fixed::from(-0.5).to_int(), -1);
+            // FixedDiv saturates instead of overflowing
+            assert_eq!(Fixed::ONE / Fixed::ZERO, Fixed::MAX);
+            assert_eq!(-Fixed::ONE / Fixed::ZERO, Fixed::MIN);
+            assert_eq!(Fixed::from_int(30000) / Fixed::from_bits(1), Fixed::MAX);
+            // FixedMul and addition wrap
+            assert_eq!(
+                Fixed::from_int(300) * Fixed::from_int(300),
+                Fixed::from_bits(90000 << 16)
+            );
+            assert_eq!(Fixed::MAX + Fixed::from_bits(1), Fixed::MIN);
+        }
+
+        #[test]
+        fn fine_tables() {
+            assert_eq!(finesine(0).to_bits(), 25);
+            assert_eq!(finesine(4).to_bits(), 226);
+            assert_eq!(finesine(31).to_bits(), 1583);
+            assert_eq!(finesine(2047).to_bits(), 65535);
+            assert_eq!(finecosine(0), finesine(2048));
+            assert_eq!(finetangent(2048).to_bits(), 25);
+            assert_eq!(finetangent(2047).to_bits(), -25);
+            assert_eq!(finetangent(0).to_bits(), -170910304);
+            assert_eq!(finetangent(1).to_bits(), -56965752);
+            assert_eq!(finetangent(2).to_bits(), -34178904);
+            assert_eq!(finetangent(9).to_bits(), -8994149);
+            assert_eq!(finetangent(4095).to_bits(), 170910304);
+            assert_eq!(tantoangle(0), Angle::from_bits(0));
+            assert_eq!(tantoangle(1), Angle::from_bits(333772));
+            assert_eq!(tantoangle(9), Angle::from_bits(3003929));
+            assert_eq!(tantoangle(SLOPERANGE), Angle::ANG45);
+            assert_eq!(Angle::ANG90.sin().to_bits(), 65535);
+            assert_eq!(Angle::ANG180.cos().to_bits(), -65535);
+            assert_eq!(Angle::ANG45.tan(), (Angle::ANG45 + Angle::ANG180).tan());
+        }
+
+        #[test]
+        fn angles() {
+            assert_eq!(Angle::from_degrees(90.), Angle::ANG90);
+            assert_eq!(Angle::from_degrees(-90.), Angle::ANG270);
+            assert_eq!(Angle::ANG270 + Angle::ANG180, Angle::ANG90);
+            assert_eq!(Angle::ANG180.to_degrees(), 180.);
+            let one = Fixed::ONE;
+            assert_eq!(
+                Angle::from_delta(Fixed::ZERO, Fixed::ZERO),
+                Angle::from_bits(0)
+            );
+            assert_eq!(Angle::from_delta(one, Fixed::ZERO), Angle::from_bits(0));
+            // Off by one like vanilla, the diagonal is in the second octant
+            assert_eq!(
+                Angle::from_delta(one, one),
+                Angle::ANG45 - Angle::from_bits(1)
+            );
+            assert_eq!(
+                Angle::from_delta(Fixed::ZERO, one),
+                Angle::ANG90 - Angle::from_bits(1)
+            );
+            assert_eq!(Angle::from_delta(one, -one), Angle::ANG270 + Angle::ANG45);
+            for degrees in [10., 60., 100., 170., 200., 260., 300., 350.] {
+                let r = f32::to_radians(degrees);
+                let a = Angle::from_delta(Fixed::from(100. * r.cos()), Fixed::from(100. * r.sin()));
+                assert!(
+                    (a.to_degrees() - degrees).abs() < 0.1,
+                    "{} != {}",
+                    a.to_degrees(),
+                    degrees
+                );
+            }
+        }
+
+        #[test]
+        fn fixed_conversions() {
+            let v = vector_n![1.5, -2.].to_fixed();
+            assert_eq!(v, [Fixed::from_bits(0x18000), Fixed::from_int(-2)]);
+            assert_eq!(Vec2::from_fixed(v), vector_n![1.5, -2.]);
+            let w = Vector::from(vec![0.25, 4.]).to_fixed();
+            assert_eq!(Vector::from_fixed(&w), Vector::from(vec![0.25, 4.]));
+        }
+    }
+
+    mod interpolation_tests {
+        use super::assert_close;
+        use doom_engine::maths::*;
+        use doom_engine::{matrix_n, vector, vector_n};
+
+        #[test]
+        fn lerps() {
+            assert_eq!(lerp(2., 4., 0.25), 2.5);
+            assert_eq!(inverse_lerp(2., 4., 2.5), 0.25);
+            let a = vector![0., 2., 4.];
+            let b = vector![4., 2., 0.];
+            assert_eq!(a.lerp(&b, 0.5), vector![2., 2., 2.]);
+            let m = Matrix::identity(2).lerp(&Matrix::zeroes(2, 2), 0.25);
+            assert_eq!(m.as_slice(), &[0.75, 0., 0., 0.75]);
+            assert_eq!(
+                vector_n![0., 2.].lerp(&vector_n![2., 0.], 0.5),
+                vector_n![1., 1.]
+            );
+            assert_eq!(
+                Mat2::identity().lerp(&matrix_n![[3., 1.], [1., 3.]], 0.5),
+                matrix_n![[2., 0.5], [0.5, 2.]]
+            );
+        }
+
+        #[test]
+        fn steps_and_easings() {
+            assert_eq!(smoothstep(0., 2., -1.), 0.);
+            assert_eq!(smoothstep(0., 2., 1.), 0.5);
+            assert_eq!(smoothstep(0., 2., 3.), 1.);
+            assert_eq!(smootherstep(0., 2., 1.), 0.5);
+            let easings = [
+                Easing::Linear,
+                Easing::Quad,
+                Easing::Cubic,
+                Easing::Quart,
+                Easing::Quint,
+                Easing::Sine,
+                Easing::Expo,
+                Easing::Circ,
+                Easing::Back,
+                Easing::Elastic,
+                Easing::Bounce,
+            ];
+            for e in easings {
+                for f in [Easing::ease_in, Easing::ease_out, Easing::ease_in_out] {
+                    assert_close(&[f(e, 0.), f(e, 1.)], &[0., 1.], 1e-4);
+                }
+                assert_close(&[e.ease_in_out(0.5)], &[0.5], 1e-4);
+            }
+            assert_eq!(Easing::Quad.ease_in(0.5), 0.25);
+            assert_eq!(Easing::Quad.ease_out(0.5), 0.75);
+            assert!(Easing::Back.ease_in(0.2) < 0.);
+        }
+
+        #[test]
+        fn splines_interpolate() {
+            let points = vec![
+                vector![0., 0.],
+                vector![1., 1.],
+                vector![2., 0.],
+                vector![3., 1.],
+            ];
+            let catmull = CatmullRom::new(points.clone());
+            assert_eq!(catmull.segments(), 3);
+            for (i, p) in points.iter().enumerate() {
+                assert_close(catmull.point(i as f32 / 3.).as_slice(), p.as_slice(), 1e-4);
+            }
+            let hermite = catmull.to_hermite();
+            for t in [0.1, 0.5, 0.9] {
+                assert_close(
+                    hermite.point(t).as_slice(),
+                    catmull.point(t).as_slice(),
+                    1e-4,
+                );
+            }
+            // Tangent at the middle point is the neighbours' chord
+            assert_close(catmull.derivative(1. / 3.).as_slice(), &[3., 0.], 1e-4);
+
+            let bezier = CubicBezier::new(points);
+            assert_close(bezier.point(0.).as_slice(), &[0., 0.], 1e-4);
+            assert_close(bezier.point(1.).as_slice(), &[3., 1.], 1e-4);
+            assert_close(bezier.point(0.5).as_slice(), &[1.5, 0.5], 1e-4);
+            assert_close(bezier.derivative(0.).as_slice(), &[3., 3.], 1e-4);
+        }
+
+        #[test]
+        fn arc_length() {
+            // Straight line with uneven speed
+            let curve = CubicBezier::new(vec![
+                vector![0., 0.],
+                vector![0.1, 0.],
+                vector![0.2, 0.],
+                vector![3., 0.],
+            ]);
+            let table = curve.arc_length(256);
+            assert!((table.length() - 3.).abs() < 1e-4);
+            for d in [0., 0.5, 1.5, 2.9, 3.] {
+                let t = table.param(d);
+                assert!((curve.point(t)[0] - d).abs() < 1e-2, "{} at {}", d, t);
+            }
+            assert_eq!(table.param(-1.), 0.);
+            assert_eq!(table.param(10.), 1.);
+            assert_eq!(table.param_normalized(1.), 1.);
+        }
+    }
+
+    mod random_tests {
+        use doom_engine::maths::random::*;
+
+        #[test]
+        fn doom_random() {
+            let mut rng = DoomRandom::new();
+            assert_eq!(
+                [rng.p_random(), rng.p_random(), rng.p_random()],
+                [8, 109, 220]
+            );
+            assert_eq!(rng.m_random(), 8);
+            let saved = rng;
+            let a: Vec<u8> = (0..300).map(|_| rng.p_random()).collect();
+            assert_eq!(rng.prndindex(), 47);
+            rng = saved;
+            let b: Vec<u8> = (0..300).map(|_| rng.p_random()).collect();
+            assert_eq!(a, b);
+            assert_eq!(rng.rndindex(), 1);
+            // The index wraps back to the start of the table
+            let mut rng = DoomRandom::from_indices(255, 0);
+            assert_eq!(rng.p_random(), RNDTABLE[0]);
+            assert_eq!(rng.p_subrandom(), 8 - 109);
+            rng.clear();
+            assert_eq!(rng, DoomRandom::new());
+        }
+
+        #[test]
+        fn rng_is_deterministic() {
+            let mut a = Rng::new(42);
+            let mut b = Rng::new(42);
+            let mut c = Rng::with_stream(42, 7);
+            let xs: Vec<u32> = (0..8).map(|_| a.next_u32()).collect();
+            let ys: Vec<u32> = (0..8).map(|_| b.next_u32()).collect();
+            let zs: Vec<u32> = (0..8).map(|_| c.next_u32()).collect();
+            assert_eq!(xs, ys);
+            assert_ne!(xs, zs);
+            // Reference output of the PCG32 demo program (seed 42, stream 54)
+            let mut pcg = Rng::with_stream(42, 54);
+            assert_eq!(
+                [pcg.next_u32(), pcg.next_u32(), pcg.next_u32()],
+                [0xa15c02b7, 0x7b47f409, 0xba1d3330]
+            );
+        }
+
+        #[test]
+        fn rng_helpers() {
+            let mut rng = Rng::new(1);
+            let mut counts = [0; 6];
+            for _ in 0..6000 {
+                let x = rng.range_i32(-3..3);
+                assert!((-3..3).contains(&x));
+                counts[(x + 3) as usize] += 1;
+                let f = rng.range_f32(2.0..4.0);
+                assert!((2.0..4.0).contains(&f));
+            }
+            assert!(counts.iter().all(|&c| c > 800), "{:?}", counts);
+            for _ in 0..100 {
+                assert!((rng.unit_vector3().abs() - 1.).abs() < 1e-5);
+                assert!((rng.unit_vector2().abs() - 1.).abs() < 1e-5);
+                assert!(rng.in_sphere().abs() <= 1.);
+                assert!(rng.in_circle().abs() <= 1.);
+            }
+            let mut items = [1, 2, 3, 4, 5];
+            rng.shuffle(&mut items);
+            items.sort();
+            assert_eq!(items, [1, 2, 3, 4, 5]);
+            assert!(rng.choose(&items).is_some());
+            assert_eq!(rng.choose::<i32>(&[]), None);
+        }
+
+        #[test]
+        fn halton_sequence() {
+            let xs: Vec<f32> = (1..5).map(|i| halton(i, 2)).collect();
+            assert_eq!(xs, [0.5, 0.25, 0.75, 0.125]);
+            assert_eq!(halton(0, 3), 0.);
+            assert!((halton(2, 3) - 2. / 3.).abs() < 1e-6);
+        }
+    }
+
+    mod noise_tests {
+        use doom_engine::maths::noise::*;
+        use doom_engine::maths::random::Rng;
+        use doom_engine::maths::*;
+        use doom_engine::vector;
+
+        const KINDS: [NoiseKind; 3] = [NoiseKind::Perlin, NoiseKind::Simplex, NoiseKind::Worley];
+
+        fn random_point(rng: &mut Rng, n: usize) -> Vector {
+            Vector::from_iter((0..n).map(|_| rng.range_f32(-50.0..50.0)))
+        }
+
+        #[test]
+        fn deterministic_per_seed() {
+            let (a, b, c) = (Noise::new(7), Noise::new(7), Noise::new(8));
+            let p = vector![1.3, -2.7, 0.4];
+            for kind in KINDS {
+                assert_eq!(a.sample(kind, &p), b.sample(kind, &p));
+                assert_ne!(a.sample(kind, &p), c.sample(kind, &p));
+            }
+            let fbm = Fbm::default();
+            assert_eq!(
+                a.warp(NoiseKind::Simplex, &p, &fbm, 2.),
+                b.warp(NoiseKind::Simplex, &p, &fbm, 2.)
+            );
+        }
+
+        #[test]
+        fn ranges_and_lattice() {
+            let noise = Noise::new(3);
+            let mut rng = Rng::new(3);
+            for n in 2..=4 {
+                for _ in 0..2000 {
+                    let p = random_point(&mut rng, n);
+                    for kind in [NoiseKind::Perlin, NoiseKind::Simplex] {
+                        let v = noise.sample(kind, &p);
+                        assert!(v.abs() <= 1.1, "{:?} {} at {:?}", kind, v, p);
+                    }
+                    let w = noise.sample(NoiseKind::Worley, &p);
+                    assert!((0. ..=(n as f32).sqrt()).contains(&w));
+                }
+                // Perlin noise vanishes on the integer lattice
+                let lattice = Vector::from_iter((0..n).map(|i| i as f32 * 3. - 4.));
+                assert_eq!(noise.sample(NoiseKind::Perlin, &lattice), 0.);
+            }
+        }
+
+        #[test]
+        fn continuous() {
+            let noise = Noise::new(11);
+            let mut rng = Rng::new(11);
+            for n in 2..=4 {
+                for _ in 0..200 {
+                    let p = random_point(&mut rng, n);
+                    let q = &p + Vector::new(n, 1e-3);
+                    for kind in KINDS {
+                        let (a, b) = (noise.sample(kind, &p), noise.sample(kind, &q));
+                        assert!((a - b).abs() < 0.05, "{:?} jumps at {:?}", kind, p);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn fractal_sums() {
+            let noise = Noise::new(5);
+            let p = vector![0.37, 0.71];
+            let one = Fbm::new(1, 2., 0.5);
+            assert_eq!(
+                noise.fbm(NoiseKind::Perlin, &p, &one),
+                noise.sample(NoiseKind::Perlin, &p)
+            );
+            assert_eq!(noise.fbm(NoiseKind::Perlin, &p, &Fbm::new(0, 2., 0.5)), 0.);
+            let v = noise.fbm(NoiseKind::Simplex, &p, &Fbm::default());
+            assert!(v.abs() <= 1.);
+            assert_ne!(v, noise.warp(NoiseKind::Simplex, &p, &Fbm::default(), 4.));
+        }
+    }
+
+    mod batch_tests {
+        use super::assert_close;
+        use doom_engine::maths::geometry::*;
+        use doom_engine::maths::*;
+        use doom_engine::vector_n;
+
+        fn model() -> Mat4 {
+            Mat4::translation(vector_n![1., -2., 3.])
+                * Mat4::rotation(vector_n![0.3, -0.7, 1.1])
+                * Mat4::scaling(vector_n![2., 0.5, 1.5])
+        }
+
+        /// Interleaved position(3) + normal(3) + uv(2), like `Mesh` uploads.
+        fn vertices() -> Vec<f32> {
+            (0..7)
+                .flat_map(|i| {
+                    let t = i as f32;
+                    [t, -t * 0.5, t * t, t.sin(), t.cos(), 0.5, t, -t]
+                })
+                .collect()
+        }
+
+        #[test]
+        fn points_and_vectors() {
+            let m = model();
+            let original = [vector_n![1., 2., 3.], vector_n![-4., 0.5, 0.]];
+            let mut points = original;
+            m.transform_points(&mut points);
+            let mut vectors = original;
+            m.transform_vectors(&mut vectors);
+            for i in 0..2 {
+                assert_close(
+                    points[i].as_slice(),
+                    m.transform_point(&original[i]).as_slice(),
+                    1e-5,
+                );
+                assert_close(
+                    vectors[i].as_slice(),
+                    m.transform_vector(&original[i]).as_slice(),
+                    1e-5,
+                );
+            }
+        }
+
+        #[test]
+        fn strided_stream() {
+            let m = model();
+            let original = vertices();
+            let mut data = original.clone();
+            m.transform_points_strided(&mut data, 8, 0);
+            m.to_normal().transform_normals_strided(&mut data, 8, 3);
+            for (before, after) in original.chunks(8).zip(data.chunks(8)) {
+                let p = m.transform_point(&Vec3::from(&before[0..3]));
+                let n = (m.to_normal() * Vec3::from(&before[3..6])).unit();
+                assert_close(&after[0..3], p.as_slice(), 1e-5);
+                assert_close(&after[3..6], n.as_slice(), 1e-5);
+                assert_eq!(&after[6..], &before[6..]);
+            }
+        }
+
+        #[test]
+        fn strided_bounds() {
+            let data = vertices();
+            let points: Vec<Vec3> = data.chunks(8).map(|v| Vec3::from(&v[0..3])).collect();
+            let expected = Aabb::from_points(&points).unwrap();
+            let aabb = Aabb::from_strided(&data, 8, 0).unwrap();
+            assert_eq!(aabb.min(), expected.min());
+            assert_eq!(aabb.max(), expected.max());
+            assert!(Aabb::from_strided(&[], 8, 0).is_none());
+        }
+
+        #[test]
+        #[should_panic]
+        fn attribute_outside_stride() {
+            Mat4::identity().transform_points_strided(&mut [0.; 8], 8, 6);
+        }
+    }
+
+    mod preprocessor_tests {
+        use doom_engine::graphics::wrapper::*;
+        use std::{fs, path::PathBuf};
+
+        /// A fresh directory with the given files.
+        fn shader_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+            let dir = std::env::temp_dir().join(format!("doom_engine_{}", name));
+            let _ = fs::remove_dir_all(&dir);
+            for (file, src) in files {
+                let path = dir.join(file);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, src).unwrap();
+            }
+            dir
+        }
+
+        #[test]
+        fn includes_map_back_to_files() {
+            let dir = shader_dir(
+                "includes",
+                &[
+                    (
+                        "main.vert",
+                        "#version 460 core\n#include \"common/a.glsl\"\nvoid main() {}\n",
+                    ),
+                    (
+                        "common/a.glsl",
+                        "#pragma once\nuniform mat4 a;\n#include \"b.glsl\"\n",
+                    ),
+                    (
+                        "lib/b.glsl",
+                        "#include \"../common/a.glsl\"\nuniform mat4 b;\n",
+                    ),
+                ],
+            );
+            let source = Preprocessor::new()
+                .include_dir(dir.join("lib"))
+                .process(&dir.join("main.vert"), &Defines::new())
+                .unwrap();
+
+            assert_eq!(
+                source.text(),
+                "#version 460 core\nuniform mat4 a;\nuniform mat4 b;\nvoid main() {}\n"
+            );
+            assert_eq!(source.files().len(), 3);
+            let (file, line) = source.locate(3).unwrap();
+            assert!(file.ends_with("b.glsl"));
+            assert_eq!(line, 2);
+            assert_eq!(source.locate(4).unwrap().1, 3);
+            assert!(source.locate(5).is_none());
+        }
+
+        #[test]
+        fn defines_follow_version() {
+            let defines: Defines = ["TEXTURED", "NUM_LIGHTS=4"].into_iter().collect();
+            let source = Preprocessor::new()
+                .process_str("#version 460 core\nvoid main() {}", "test", &defines)
+                .unwrap();
+
+            assert_eq!(
+                source.text(),
+                "#version 460 core\n#define NUM_LIGHTS 4\n#define TEXTURED \nvoid main() {}\n"
+            );
+            assert!(source.locate(2).is_none());
+            assert_eq!(source.locate(4), Some(("test", 2)));
+            assert_eq!(
+                defines,
+                Defines::new().with("NUM_LIGHTS=4").with("TEXTURED")
+            );
+        }
+
+        #[test]
+        fn default_version() {
+            let defines = Defines::new().with("TEXTURED");
+            let source = Preprocessor::new()
+                .process_str("void main() {}", "test", &defines)
+                .unwrap();
+            assert_eq!(
+                source.text(),
+                "#version 460 core\n#define TEXTURED \nvoid main() {}\n"
+            );
+            assert!(source.locate(1).is_none());
+            assert_eq!(source.locate(3), Some(("test", 1)));
+
+            let preprocessor = Preprocessor::new().default_version("450 core");
+            assert_eq!(preprocessor.version(), "450 core");
+            let source = preprocessor
+                .process_str("void main() {}", "test", &Defines::new())
+                .unwrap();
+            assert_eq!(source.text(), "#version 450 core\nvoid main() {}\n");
+            let source = preprocessor
+                .process_str("#version 330 core\nvoid main() {}", "test", &Defines::new())
+                .unwrap();
+            assert_eq!(source.text(), "#version 330 core\nvoid main() {}\n");
+        }
+
+        #[test]
+        fn include_errors() {
+            let dir = shader_dir(
+                "include_errors",
+                &[
+                    ("cycle.frag", "#include \"cycle.frag\"\n"),
+                    ("missing.frag", "\n#include \"nowhere.glsl\"\n"),
+                ],
+            );
+            let preprocessor = Preprocessor::new();
+            for (file, line) in [("cycle.frag", 1), ("missing.frag", 2)] {
+                match preprocessor.process(&dir.join(file), &Defines::new()) {
+                    Err(ShaderError::Preprocess { line: l, .. }) => assert_eq!(l, line),
+                    _ => panic!("{} should fail to preprocess", file),
+                }
+            }
+        }
+    }
+
+    mod shader_error_tests {
+        use doom_engine::graphics::wrapper::*;
+
+        fn locate(source: u32, line: u32) -> Option<String> {
+            (source == 0).then(|| format!("shaders/main.frag:{}", line + 1))
+        }
+
+        #[test]
+        fn vendor_locations() {
+            // Mesa
+            assert_eq!(
+                map_log("0:12(5): error: `x' undeclared", locate),
+                "shaders/main.frag:13(5): error: `x' undeclared"
+            );
+            // NVIDIA
+            assert_eq!(
+                map_log("0(12) : error C1008: undefined variable \"x\"", locate),
+                "shaders/main.frag:13 : error C1008: undefined variable \"x\""
+            );
+            // AMD, Intel
+            assert_eq!(
+                map_log("ERROR: 0:12: 'x' : undeclared identifier", locate),
+                "ERROR: shaders/main.frag:13: 'x' : undeclared identifier"
+            );
+        }
+
+        #[test]
+        fn builder_without_stages() {
+            assert!(matches!(
+                ShaderProgram::builder().build(),
+                Err(ShaderError::NoStages)
+            ));
+        }
+
+        #[test]
+        fn unchanged_lines() {
+            let log = "ERROR: 1 compilation errors.  No code generated.\n\
+                       1:4(2): warning: unknown source\n\
+                       Fragment info\n\
+                       -------------";
+            assert_eq!(map_log(log, locate), log);
+            assert_eq!(
+                map_log("0:3(1): error: a\nlinking failed\n0(7) : warning C7050: b", locate),
+                "shaders/main.frag:4(1): error: a\nlinking failed\nshaders/main.frag:8 : warning C7050: b"
+            );
+        }
+    }
+
+    mod compute_tests {
+        use super::with_gl;
+        use doom_engine::graphics::wrapper::*;
+
+        #[test]
+        fn work_group_rounding() {
+            assert_eq!(work_group_count([100, 1, 1], [64, 1, 1]), [2, 1, 1]);
+            assert_eq!(work_group_count([128, 1, 1], [64, 1, 1]), [2, 1, 1]);
+            assert_eq!(work_group_count([1920, 1080, 1], [16, 16, 1]), [120, 68, 1]);
+            assert_eq!(work_group_count([0, 5, 1], [8, 8, 1]), [0, 1, 1]);
+            // A size of 0 counts as 1
+            assert_eq!(work_group_count([3, 3, 3], [0, 0, 0]), [3, 3, 3]);
+        }
+
+        /// Runs a compute shader filling an SSBO and reads it back.
+        #[test]
+        fn compute_writes_ssbo() {
+            with_gl(|| {
+                let mut program = ComputeProgram::from_source(
+                    "layout (local_size_x = 64) in;\n\
+                 layout (std430, binding = 0) buffer Squares { uint squares[]; };\n\
+                 uniform uint count;\n\
+                 void main() {\n\
+                     uint i = gl_GlobalInvocationID.x;\n\
+                     if (i < count) squares[i] = i * i;\n\
+                 }\n",
+                )
+                .unwrap();
+                assert_eq!(program.work_group_size(), [64, 1, 1]);
+
+                let squares = SSBO::<u32>::zeroed(gl::DYNAMIC_COPY, 100);
+                squares.bind_base(0);
+                program.set_uniform("count", &100u32);
+                program.dispatch_for([100, 1, 1]);
+                memory_barrier(Barrier::BUFFER_UPDATE);
+
+                let expected: Vec<u32> = (0..100).map(|i| i * i).collect();
+                assert_eq!(squares.read(), expected);
+                assert_eq!(squares.read_range(10..12), [100, 121]);
+            });
+        }
+    }
+
+    mod buffer_tests {
+        use super::with_gl;
+        use doom_engine::graphics::wrapper::*;
+        use std::mem;
+
+        #[test]
+        fn buffer_updates() {
+            with_gl(|| {
+                let data: Vec<u32> = (0..8).collect();
+                let mut buffer = Buffer::new(BufferTarget::ShaderStorage, gl::DYNAMIC_COPY, &data);
+                assert_eq!(buffer.len(), 8);
+                assert_eq!(buffer.size_bytes(), 32);
+                assert_eq!(buffer.read(), data);
+
+                buffer.sub_data(2, &[20, 30]);
+                assert_eq!(buffer.read(), [0, 1, 20, 30, 4, 5, 6, 7]);
+                assert_eq!(buffer.read_range(1..4), [1, 20, 30]);
+                assert_eq!(buffer.read_range(8..8), []);
+
+                let src = Buffer::new(BufferTarget::CopyRead, gl::STATIC_DRAW, &[100, 200, 300]);
+                buffer.copy_from(&src, 1, 5, 2);
+                assert_eq!(buffer.read(), [0, 1, 20, 30, 4, 200, 300, 7]);
+
+                buffer.resize(16);
+                assert_eq!(buffer.len(), 16);
+                assert_eq!(buffer.size_bytes(), 64);
+                // Contents are undefined after a resize, only the size is kept
+                assert_eq!(buffer.read().len(), 16);
+                buffer.sub_data(15, &[9]);
+                assert_eq!(buffer.read_range(15..16), [9]);
+
+                buffer.set_data(&[1, 2]);
+                assert_eq!(buffer.len(), 2);
+                assert_eq!(buffer.read(), [1, 2]);
+            });
+        }
+
+        #[test]
+        fn index_types() {
+            with_gl(|| {
+                let target = BufferTarget::ElementArray;
+                let bytes = Buffer::new(target, gl::STATIC_DRAW, &[0u8, 1, 2]);
+                let shorts = Buffer::new(target, gl::STATIC_DRAW, &[0u16, 1, 2]);
+                let ints = Buffer::new(target, gl::STATIC_DRAW, &[0u32, 1, 2]);
+                assert_eq!(bytes.index_type(), gl::UNSIGNED_BYTE);
+                assert_eq!(shorts.index_type(), gl::UNSIGNED_SHORT);
+                assert_eq!(ints.index_type(), gl::UNSIGNED_INT);
+            });
+        }
+
+        /// Writes the frame number to each region for more frames than there
+        /// are regions, copying every region out on the GPU right after its
+        /// fence, so reused regions must have been waited for.
+        #[test]
+        fn stream_buffer_cycles() {
+            with_gl(|| {
+                const LEN: usize = 4;
+                const FRAMES: usize = 7;
+                let mut stream = StreamBuffer::<u32>::new(BufferTarget::ShaderStorage, LEN, 3);
+                assert_eq!(stream.regions(), 3);
+                assert_eq!(stream.len(), LEN);
+                let copies = Buffer::<u32>::with_len(
+                    BufferTarget::CopyWrite,
+                    gl::DYNAMIC_COPY,
+                    LEN * FRAMES,
+                );
+
+                let size = mem::size_of::<u32>();
+                for frame in 0..FRAMES {
+                    stream.next_region().fill(frame as u32);
+                    assert_eq!(stream.offset(), frame % 3 * LEN);
+                    assert_eq!(stream.region(), stream.offset()..stream.offset() + LEN);
+                    unsafe {
+                        gl::CopyNamedBufferSubData(
+                            stream.id(),
+                            copies.id(),
+                            (stream.offset() * size) as isize,
+                            (frame * LEN * size) as isize,
+                            (LEN * size) as isize,
+                        );
+                    }
+                    stream.fence();
+                }
+
+                let expected: Vec<u32> =
+                    (0..FRAMES as u32).flat_map(|frame| [frame; LEN]).collect();
+                assert_eq!(copies.read(), expected);
+            });
+        }
+    }
+
+    mod shader_program_tests {
+        use super::with_gl;
+        use doom_engine::graphics::wrapper::*;
+        use doom_engine::maths::*;
+        use doom_engine::vector_n;
+        use gl::types::*;
+        use std::{
+            fs,
+            path::Path,
+            time::{Duration, SystemTime},
+        };
+
+        const VERTEX: &str = "void main() { gl_Position = vec4(0.0); }\n";
+
+        /// Writes `src` to `path` with a modification time `step` seconds
+        /// ahead, so the change is seen whatever the file system's mtime
+        /// resolution.
+        fn write_shader(path: &Path, src: &str, step: u64) {
+            fs::write(path, src).unwrap();
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() + Duration::from_secs(step))
+                .unwrap();
+        }
+
+        fn uniform_value(program: &ShaderProgram, name: &str) -> f32 {
+            let (info, _) = program.reflection().uniform(name).unwrap();
+            let mut value = 0.;
+            unsafe { gl::GetUniformfv(program.id(), info.location(), &mut value) };
+            value
+        }
+
+        #[test]
+        fn uniform_value_types() {
+            assert!(1.0f32.accepts(gl::FLOAT));
+            assert!(!1.0f32.accepts(gl::INT));
+            assert!(1i32.accepts(gl::INT) && 1i32.accepts(gl::BOOL));
+            assert!(1u32.accepts(gl::UNSIGNED_INT));
+            assert!(true.accepts(gl::BOOL) && !true.accepts(gl::INT));
+            assert_eq!(1.0f32.count(), 1);
+
+            let v = Vec3::zeroes();
+            assert!(v.accepts(gl::FLOAT_VEC3) && !v.accepts(gl::FLOAT_VEC4));
+            assert!(Vec2::zeroes().accepts(gl::FLOAT_VEC2));
+            assert!(Vec4::zeroes().accepts(gl::FLOAT_VEC4));
+            assert!(Mat2::identity().accepts(gl::FLOAT_MAT2));
+            assert!(Mat3::identity().accepts(gl::FLOAT_MAT3));
+            assert!(Mat4::identity().accepts(gl::FLOAT_MAT4));
+            assert!(!Mat4::identity().accepts(gl::FLOAT_MAT3));
+            assert_eq!(Mat4::identity().count(), 1);
+
+            let lights = [v; 3];
+            assert!(lights.accepts(gl::FLOAT_VEC3));
+            assert_eq!(lights.count(), 3);
+            assert_eq!(lights[..2].count(), 2);
+            assert_eq!(vec![1.0f32; 5].count(), 5);
+
+            // Samplers are set to a unit, as an int
+            assert!(0i32.accepts(gl::SAMPLER_2D));
+            assert!([0i32, 1].accepts(gl::SAMPLER_CUBE));
+            assert!(!0u32.accepts(gl::SAMPLER_2D));
+            assert!(!0.0f32.accepts(gl::SAMPLER_2D));
+            // Images too, to their image unit
+            assert!(0i32.accepts(gl::IMAGE_2D));
+            assert!(is_sampler(gl::SAMPLER_2D) && !is_image(gl::SAMPLER_2D));
+            assert!(is_image(gl::IMAGE_2D) && !is_sampler(gl::IMAGE_2D));
+        }
+
+        #[test]
+        fn uniform_lookup() {
+            with_gl(|| {
+                let mut program = ShaderProgram::from_sources(
+                    VERTEX,
+                    "out vec4 color;\n\
+                     uniform vec3 lights[4];\n\
+                     uniform float scale;\n\
+                     void main() {\n\
+                         color = vec4(lights[0] + lights[1] + lights[2] + lights[3], scale);\n\
+                     }\n",
+                )
+                .unwrap();
+                let (info, index) = program.reflection().uniform("lights[2]").unwrap();
+                assert_eq!((info.size(), index), (4, 2));
+                assert_eq!(info.gl_type(), gl::FLOAT_VEC3);
+                let location = info.location();
+                assert!(program.reflection().uniform("lights[4]").is_none());
+                assert!(program.reflection().uniform("lights[x]").is_none());
+
+                let read = |program: &ShaderProgram, location: i32| {
+                    let mut value = [0f32; 3];
+                    unsafe { gl::GetUniformfv(program.id(), location, value.as_mut_ptr()) };
+                    value
+                };
+                program.set_uniform("lights[2]", &vector_n![1., 2., 3.]);
+                assert_eq!(read(&program, location + 2), [1., 2., 3.]);
+                program.set_uniform("lights[1]", &[vector_n![4., 5., 6.]; 2]);
+                assert_eq!(read(&program, location + 2), [4., 5., 6.]);
+
+                // Out of range, the wrong type and missing uniforms are
+                // skipped, warned about once
+                for _ in 0..2 {
+                    program.set_uniform("lights[2]", &[Vec3::new(9.); 3]);
+                    program.set_uniform("scale", &Mat4::identity());
+                    program.set_uniform("missing", &1.0f32);
+                }
+                assert_eq!(read(&program, location + 2), [4., 5., 6.]);
+                assert_eq!(read(&program, location + 3), [0., 0., 0.]);
+                assert_eq!(uniform_value(&program, "scale"), 0.);
+                program.set_uniform("scale", &2.0f32);
+                assert_eq!(uniform_value(&program, "scale"), 2.);
+            });
+        }
+
+        #[test]
+        fn geometry_programs() {
+            with_gl(|| {
+                let wireframe = ShaderProgram::builder()
+                    .vertex("resources/shaders/basic.vert")
+                    .geometry("resources/shaders/wireframe.geom")
+                    .fragment("resources/shaders/wireframe.frag")
+                    .build()
+                    .unwrap();
+                assert!(wireframe.reflection().uniform("viewport").is_some());
+                assert!(wireframe.reflection().uniform("line_color").is_some());
+
+                let normals = ShaderProgram::builder()
+                    .vertex("resources/shaders/normals.vert")
+                    .geometry("resources/shaders/normals.geom")
+                    .fragment("resources/shaders/basic.frag")
+                    .build()
+                    .unwrap();
+                assert!(normals.reflection().uniform("normal_length").is_some());
+            });
+        }
+
+        #[test]
+        fn tessellation_program() {
+            with_gl(|| {
+                let program = ShaderProgram::builder()
+                    .stage_source(ShaderStage::Vertex, "void main() { gl_Position = vec4(0.0); }")
+                    .stage_source(
+                        ShaderStage::TessControl,
+                        "layout (vertices = 3) out;\n\
+                         uniform float level;\n\
+                         void main() {\n\
+                             gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;\n\
+                             gl_TessLevelOuter[0] = level;\n\
+                             gl_TessLevelOuter[1] = level;\n\
+                             gl_TessLevelOuter[2] = level;\n\
+                             gl_TessLevelInner[0] = level;\n\
+                         }",
+                    )
+                    .stage_source(
+                        ShaderStage::TessEvaluation,
+                        "layout (triangles) in;\n\
+                         void main() {\n\
+                             gl_Position = gl_TessCoord.x * gl_in[0].gl_Position\n\
+                                 + gl_TessCoord.y * gl_in[1].gl_Position\n\
+                                 + gl_TessCoord.z * gl_in[2].gl_Position;\n\
+                         }",
+                    )
+                    .stage_source(
+                        ShaderStage::Fragment,
+                        "out vec4 color;\nvoid main() { color = vec4(1.0); }",
+                    )
+                    .build()
+                    .unwrap();
+                assert!(program.reflection().uniform("level").is_some());
+            });
+        }
+
+        #[test]
+        fn separable_pipeline() {
+            with_gl(|| {
+                let vertex = ShaderProgram::builder()
+                    .stage_source(
+                        ShaderStage::Vertex,
+                        "out gl_PerVertex { vec4 gl_Position; };\n\
+                         layout (location = 0) in vec3 pos;\n\
+                         layout (location = 0) out vec3 _pos;\n\
+                         void main() { _pos = pos; gl_Position = vec4(pos, 1.0); }",
+                    )
+                    .separable(true)
+                    .build()
+                    .unwrap();
+                let fragment = ShaderProgram::builder()
+                    .stage_source(
+                        ShaderStage::Fragment,
+                        "layout (location = 0) in vec3 _pos;\n\
+                         out vec4 color;\n\
+                         void main() { color = vec4(_pos, 1.0); }",
+                    )
+                    .separable(true)
+                    .build()
+                    .unwrap();
+
+                let mut pipeline = ProgramPipeline::new();
+                pipeline.use_stages(&[ShaderStage::Vertex], &vertex);
+                pipeline.use_stages(&[ShaderStage::Fragment], &fragment);
+                assert_eq!(pipeline.validate(), Ok(()));
+            });
+        }
+
+        #[test]
+        fn hot_reload() {
+            with_gl(|| {
+                let dir = std::env::temp_dir().join("doom_engine_hot_reload");
+                let _ = fs::remove_dir_all(&dir);
+                fs::create_dir_all(&dir).unwrap();
+                let (vert, frag) = (dir.join("a.vert"), dir.join("a.frag"));
+                write_shader(&vert, VERTEX, 0);
+                write_shader(
+                    &frag,
+                    "out vec4 color;\nuniform float a;\nvoid main() { color = vec4(a); }\n",
+                    0,
+                );
+                let mut program =
+                    ShaderProgram::new(vert.to_str().unwrap(), frag.to_str().unwrap()).unwrap();
+                program.set_uniform("a", &1.0f32);
+                let old = program.id();
+                assert!(ShaderProgram::reload_changed().is_empty());
+
+                // A broken edit keeps the old program
+                write_shader(&frag, "out vec4 color;\nvoid main() { color = x; }\n", 1);
+                let errors = ShaderProgram::reload_changed();
+                assert!(matches!(errors[..], [ShaderError::Compile { .. }]));
+                program.bind();
+                assert_eq!(program.id(), old);
+
+                // The fix is swapped in on bind, with fresh locations
+                write_shader(
+                    &frag,
+                    "out vec4 color;\nuniform float b;\nuniform float a;\n\
+                     void main() { color = vec4(a + b); }\n",
+                    2,
+                );
+                assert!(ShaderProgram::reload_changed().is_empty());
+                assert_eq!(program.id(), old);
+                program.bind();
+                assert_ne!(program.id(), old);
+                assert!(program.reflection().uniform("b").is_some());
+                program.set_uniform("a", &2.0f32);
+                program.set_uniform("b", &3.0f32);
+                assert_eq!(uniform_value(&program, "a"), 2.);
+                assert_eq!(uniform_value(&program, "b"), 3.);
+            });
+        }
+
+        #[test]
+        fn reload_after_missing_include() {
+            with_gl(|| {
+                let dir = std::env::temp_dir().join("doom_engine_missing_include");
+                let _ = fs::remove_dir_all(&dir);
+                fs::create_dir_all(&dir).unwrap();
+                let (vert, frag) = (dir.join("a.vert"), dir.join("a.frag"));
+                write_shader(&vert, VERTEX, 0);
+                write_shader(
+                    &frag,
+                    "out vec4 color;\nvoid main() { color = vec4(1.0); }\n",
+                    0,
+                );
+                let mut program =
+                    ShaderProgram::new(vert.to_str().unwrap(), frag.to_str().unwrap()).unwrap();
+                let old = program.id();
+
+                write_shader(
+                    &frag,
+                    "#include \"color.glsl\"\nout vec4 color;\nvoid main() { color = COLOR; }\n",
+                    1,
+                );
+                let errors = ShaderProgram::reload_changed();
+                assert!(matches!(errors[..], [ShaderError::Preprocess { .. }]));
+
+                // Creating the include is enough to reload
+                write_shader(&dir.join("color.glsl"), "#define COLOR vec4(0.5)\n", 2);
+                assert!(ShaderProgram::reload_changed().is_empty());
+                program.bind();
+                assert_ne!(program.id(), old);
+            });
+        }
+
+        fn texture(value: u8) -> Texture2D {
+            Texture2D::builder()
+                .pixels(1, 1, PixelFormat::Rgba8, &[value; 4])
+                .build()
+                .unwrap()
+        }
+
+        /// The value a sampler or image uniform was linked with or set to.
+        fn unit_value(program: &ShaderProgram, name: &str) -> GLint {
+            let (info, index) = program.reflection().uniform(name).unwrap();
+            let mut value = -1;
+            unsafe { gl::GetUniformiv(program.id(), info.location() + index as GLint, &mut value) };
+            value
+        }
+
+        fn bound_texture(unit: GLuint) -> GLuint {
+            let mut id = 0;
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                gl::GetIntegerv(gl::TEXTURE_BINDING_2D, &mut id);
+                gl::ActiveTexture(gl::TEXTURE0);
+            }
+            id as GLuint
+        }
+
+        #[test]
+        fn texture_set_units() {
+            with_gl(|| {
+                let mut program = ShaderProgram::from_sources(
+                    VERTEX,
+                    "out vec4 color;\n\
+                     uniform sampler2D diffuse;\n\
+                     layout (binding = 1) uniform sampler2D normal_map;\n\
+                     uniform sampler2D specular;\n\
+                     layout (binding = 3, r32f) uniform writeonly image2D target;\n\
+                     void main() {\n\
+                         imageStore(target, ivec2(0), vec4(1.0));\n\
+                         color = texture(diffuse, vec2(0.0)) + texture(normal_map, vec2(0.0))\n\
+                             + texture(specular, vec2(0.0));\n\
+                     }\n",
+                )
+                .unwrap();
+
+                // The explicit binding is kept and the others placed around it
+                let reflection = program.reflection();
+                let unit = |name| reflection.uniform(name).unwrap().0.unit();
+                assert_eq!(unit("normal_map"), Some(1));
+                let mut units = [unit("diffuse").unwrap(), unit("specular").unwrap()];
+                units.sort();
+                assert_eq!(units, [0, 2]);
+                assert_eq!(unit_value(&program, "normal_map"), 1);
+                // Images aren't given a texture unit
+                assert_eq!(unit("target"), None);
+                assert_eq!(unit_value(&program, "target"), 3);
+
+                let (diffuse, normal_map) = (texture(10), texture(20));
+                let textures = TextureSet::new()
+                    .with("diffuse", &diffuse)
+                    .with("normal_map", &normal_map);
+                program.bind_textures(&textures).unwrap();
+                let diffuse_unit = program.reflection().uniform("diffuse").unwrap().0.unit();
+                assert_eq!(
+                    unit_value(&program, "diffuse"),
+                    diffuse_unit.unwrap() as GLint
+                );
+                assert_eq!(bound_texture(diffuse_unit.unwrap()), diffuse.id());
+                assert_eq!(bound_texture(1), normal_map.id());
+            });
+        }
+
+        #[test]
+        fn sampler_array_conflict() {
+            with_gl(|| {
+                let mut program = ShaderProgram::from_sources(
+                    VERTEX,
+                    "out vec4 color;\n\
+                     uniform sampler2D maps[2];\n\
+                     void main() {\n\
+                         color = texture(maps[0], vec2(0.0)) + texture(maps[1], vec2(0.0));\n\
+                     }\n",
+                )
+                .unwrap();
+                let unit = program
+                    .reflection()
+                    .uniform("maps")
+                    .unwrap()
+                    .0
+                    .unit()
+                    .unwrap();
+                let (first, second) = (texture(10), texture(20));
+
+                let conflict = program
+                    .bind_textures(
+                        &TextureSet::new()
+                            .with("maps", &first)
+                            .with("maps[0]", &second),
+                    )
+                    .unwrap_err();
+                assert_eq!(conflict.samplers(), ("maps", "maps[0]"));
+                assert_eq!(conflict.unit(), Some(unit));
+                assert_eq!(conflict.textures(), (first.id(), second.id()));
+
+                // The same texture twice, or the next element, is fine
+                let same = TextureSet::new()
+                    .with("maps", &first)
+                    .with("maps[0]", &first);
+                assert_eq!(program.bind_textures(&same), Ok(()));
+                let next = TextureSet::new()
+                    .with("maps", &first)
+                    .with("maps[1]", &second);
+                assert_eq!(program.bind_textures(&next), Ok(()));
+                assert_eq!(bound_texture(unit), first.id());
+                assert_eq!(bound_texture(unit + 1), second.id());
+            });
+        }
+    }
+
+    mod state_tests {
+        use super::with_gl;
+        use doom_engine::graphics::wrapper::*;
+
+        fn new_program() -> ShaderProgram {
+            ShaderProgram::from_sources(
+                "void main() { gl_Position = vec4(0.0); }\n",
+                "out vec4 color;\nvoid main() { color = vec4(1.0); }\n",
+            )
+            .unwrap()
+        }
+
+        fn new_texture() -> Texture2D {
+            Texture2D::builder()
+                .pixels(1, 1, PixelFormat::Rgba8, &[255u8; 4])
+                .build()
+                .unwrap()
+        }
+
+        /// Binds `program`, `vao` and `texture` to unit 0.
+        fn bind_all(program: &mut ShaderProgram, vao: &VAO, texture: &Texture2D) {
+            program.bind();
+            vao.bind();
+            texture.bind_unit(0);
+        }
+
+        #[test]
+        fn redundant_binds_skipped() {
+            with_gl(|| {
+                let (mut program, vao, texture) = (new_program(), VAO::new(), new_texture());
+                reset_state_counters();
+
+                bind_all(&mut program, &vao, &texture);
+                bind_all(&mut program, &vao, &texture);
+                let counters = state_counters();
+                assert_eq!(counters.program_binds, 1);
+                assert_eq!(counters.program_skipped, 1);
+                assert_eq!(counters.vertex_array_binds, 1);
+                assert_eq!(counters.vertex_array_skipped, 1);
+                assert_eq!(counters.texture_binds, 1);
+                assert_eq!(counters.texture_skipped, 1);
+
+                // Another unit is a separate bind
+                texture.bind_unit(1);
+                assert_eq!(state_counters().texture_binds, 2);
+
+                invalidate_state();
+                bind_all(&mut program, &vao, &texture);
+                let counters = state_counters();
+                assert_eq!(counters.program_binds, 2);
+                assert_eq!(counters.vertex_array_binds, 2);
+                assert_eq!(counters.texture_binds, 3);
+                assert_eq!(counters.texture_skipped, 1);
+
+                reset_state_counters();
+                assert_eq!(state_counters(), StateCounters::default());
+            });
+        }
+
+        /// A deleted object's name may be reused by the next one created, which
+        /// must still be bound.
+        #[test]
+        fn deleted_objects_rebound() {
+            with_gl(|| {
+                let (mut program, vao, texture) = (new_program(), VAO::new(), new_texture());
+                bind_all(&mut program, &vao, &texture);
+                drop((program, vao, texture));
+                reset_state_counters();
+
+                let (mut program, vao, texture) = (new_program(), VAO::new(), new_texture());
+                bind_all(&mut program, &vao, &texture);
+                let counters = state_counters();
+                assert_eq!(counters.program_binds, 1);
+                assert_eq!(counters.program_skipped, 0);
+                assert_eq!(counters.vertex_array_binds, 1);
+                assert_eq!(counters.vertex_array_skipped, 0);
+                assert_eq!(counters.texture_binds, 1);
+                assert_eq!(counters.texture_skipped, 0);
+            });
+        }
+    }
+
+    mod std140_tests {
+        use doom_engine::graphics::wrapper::*;
+        use doom_engine::maths::*;
+        use doom_engine::{matrix_n, std140, vector_n};
+
+        std140! {
+            struct Light {
+                position: Vec3,
+                intensity: f32,
+                color: Vec3,
+            }
+        }
+
+        std140! {
+            struct Lights {
+                count: i32,
+                lights: [Light; 2],
+                weights: [f32; 3],
+                normal: Mat3,
+            }
+        }
+
+        fn floats(bytes: &[u8]) -> Vec<f32> {
+            bytes
+                .chunks(4)
+                .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
+                .collect()
+        }
+
+        #[test]
+        fn vec3_packing() {
+            let light = Light {
+                position: vector_n![1., 2., 3.],
+                intensity: 4.,
+                color: vector_n![5., 6., 7.],
+            };
+            assert_eq!(Light::ALIGN, 16);
+            assert_eq!(Light::SIZE, 32);
+            assert_eq!(floats(&light.to_std140()), [1., 2., 3., 4., 5., 6., 7., 0.]);
+        }
+
+        #[test]
+        fn array_stride() {
+            assert_eq!(<[f32; 3]>::SIZE, 48);
+            assert_eq!(<[Vec2; 2]>::SIZE, 32);
+            assert_eq!(<[Vec4; 2]>::SIZE, 32);
+            let bytes = [1_f32, 2., 3.].to_std140();
+            assert_eq!(floats(&bytes)[..9], [1., 0., 0., 0., 2., 0., 0., 0., 3.]);
+        }
+
+        #[test]
+        fn nested_offsets() {
+            // count at 0, lights at 16, weights at 80, normal at 128
+            assert_eq!(Lights::SIZE, 176);
+            let block = Lights {
+                count: 2,
+                lights: [
+                    Light {
+                        position: vector_n![0., 0., 0.],
+                        intensity: 1.,
+                        color: vector_n![0., 0., 0.],
+                    },
+                    Light {
+                        position: vector_n![0., 0., 0.],
+                        intensity: 2.,
+                        color: vector_n![0., 0., 0.],
+                    },
+                ],
+                weights: [0.5, 0.25, 0.125],
+                normal: matrix_n![[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]],
+            };
+            let bytes = block.to_std140();
+            assert_eq!(i32::from_ne_bytes(bytes[..4].try_into().unwrap()), 2);
+            let f = floats(&bytes);
+            assert_eq!((f[4 + 3], f[12 + 3]), (1., 2.));
+            assert_eq!((f[20], f[24], f[28]), (0.5, 0.25, 0.125));
+            // Columns of the row-major matrix, each padded to a vec4
+            assert_eq!(f[32..44], [1., 4., 7., 0., 2., 5., 8., 0., 3., 6., 9., 0.]);
+        }
+    }
+
+    mod vertex_layout_tests {
+        use doom_engine::graphics::wrapper::*;
+        use doom_engine::maths::*;
+        use doom_engine::vertex;
+
+        vertex! {
+            #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+            struct MeshVertex {
+                pos: Vec3,
+                tex: Vec2,
+                color: Normalized<[u8; 4]>,
+                bone: u32,
+            }
+        }
+
+        vertex! {
+            #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+            struct Instance {
+                model: Mat4,
+                tint: Vec4,
+            }
+        }
+
+        #[test]
+        fn derived_offsets() {
+            let layout = MeshVertex::layout();
+            assert_eq!(layout.stride(), 28);
+            let offsets: Vec<_> = layout.attributes().iter().map(|a| a.offset()).collect();
+            assert_eq!(offsets, [0, 12, 20, 24]);
+            let locations: Vec<_> = layout.attributes().iter().map(|a| a.location()).collect();
+            assert_eq!(locations, [0, 1, 2, 3]);
+            assert_eq!(
+                layout.attributes()[2].format().kind(),
+                AttribKind::Normalized
+            );
+            assert_eq!(layout.divisor(), 0);
+        }
+
+        #[test]
+        fn instanced_matrices() {
+            let layout = Instance::layout().instanced(1).starting_at(4);
+            assert_eq!(layout.divisor(), 1);
+            assert_eq!(layout.attributes()[0].location(), 4);
+            assert_eq!(layout.attributes()[0].format().locations(), 4);
+            assert_eq!(layout.attributes()[1].location(), 8);
+            assert_eq!(layout.attributes()[1].offset(), 64);
+            assert_eq!(layout.end_location(), 9);
+        }
+
+        #[test]
+        fn glsl_types() {
+            assert_eq!(Vec3::FORMAT.glsl_type(), gl::FLOAT_VEC3);
+            assert_eq!(Normalized::<[u8; 4]>::FORMAT.glsl_type(), gl::FLOAT_VEC4);
+            assert_eq!(<[u8; 4]>::FORMAT.glsl_type(), gl::UNSIGNED_INT_VEC4);
+            assert_eq!(<[i32; 2]>::FORMAT.glsl_type(), gl::INT_VEC2);
+            assert_eq!(Mat3::FORMAT.glsl_type(), gl::FLOAT_MAT3);
+            assert_eq!(Mat3::FORMAT.size(), 36);
+        }
+
+        #[test]
+        #[should_panic]
+        fn attribute_past_stride() {
+            let _ = VertexLayout::new(12).attribute("pos", 0, Vec4::FORMAT, 0);
+        }
+    }
+
+    mod texture_builder_tests {
+        use doom_engine::graphics::wrapper::*;
+
+        #[test]
+        fn mip_levels() {
+            assert_eq!(mip_level_count(1, 1), 1);
+            assert_eq!(mip_level_count(256, 256), 9);
+            assert_eq!(mip_level_count(300, 17), 9);
+        }
+
+        #[test]
+        fn raw_pixels_flipped() {
+            let pixels: [u8; 6] = [1, 2, 3, 4, 5, 6];
+            let builder = Texture2D::builder().pixels(3, 2, PixelFormat::R8, &pixels);
+            let data = builder.load().unwrap();
+            assert_eq!((data.width(), data.height()), (3, 2));
+            assert_eq!(data.data(), [4, 5, 6, 1, 2, 3]);
+            let data = builder.flip_vertically(false).load().unwrap();
+            assert_eq!(data.data(), pixels);
+        }
+
+        #[test]
+        fn invalid_pixels() {
+            let floats = [0_f32; 4];
+            let short = Texture2D::builder().pixels(2, 1, PixelFormat::Rgba32F, &floats);
+            assert!(matches!(
+                short.load(),
+                Err(TextureError::InvalidPixels { .. })
+            ));
+            let srgb = Texture2D::builder()
+                .pixels(1, 1, PixelFormat::R8, &[0_u8])
+                .srgb(true);
+            assert!(matches!(
+                srgb.load(),
+                Err(TextureError::InvalidPixels { .. })
+            ));
+            let missing = Texture2D::builder().path("does/not/exist.png");
+            assert!(matches!(missing.load(), Err(TextureError::Io { .. })));
+            assert!(matches!(
+                Texture2D::builder().load(),
+                Err(TextureError::NoSource)
+            ));
+        }
+
+        #[test]
+        fn decode_bytes() {
+            let img = image::RgbaImage::from_fn(2, 2, |_, y| image::Rgba([y as u8, 0, 0, 255]));
+            let mut png = std::io::Cursor::new(Vec::new());
+            img.write_to(&mut png, image::ImageOutputFormat::Png)
+                .unwrap();
+            let data = Texture2D::builder().bytes(png.get_ref()).load().unwrap();
+            assert_eq!(data.format(), PixelFormat::Rgba8);
+            // The bottom row comes first
+            assert_eq!(data.data()[..4], [1, 0, 0, 255]);
+            let garbage = Texture2D::builder().bytes(&[1, 2, 3]);
+            assert!(matches!(garbage.load(), Err(TextureError::Decode { .. })));
+        }
     }
 }